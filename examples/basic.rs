@@ -43,7 +43,7 @@ fn main() {
     for (i, p) in prices.iter().enumerate() {
         let price_decimal: Decimal = (*p).try_into().expect("Unable to convert f64 into Decimal");
         let spread: Decimal = Decimal::ONE / Decimal::from(10);
-        let exec_orders = exchange
+        let (exec_orders, fills) = exchange
             .update_state(
                 i as u64,
                 MarketUpdate::Bba {
@@ -53,7 +53,7 @@ fn main() {
             )
             .expect("Got REKT. Try again next time :D");
         if !exec_orders.is_empty() {
-            println!("executed orders: {:?}", exec_orders);
+            println!("executed orders: {:?}, fills: {:?}", exec_orders, fills);
         }
 
         if i % 100 == 0 {
@@ -68,7 +68,12 @@ fn main() {
             };
             // Handle order error here if needed
             match exchange.submit_order(order) {
-                Ok(order) => println!("succesfully submitted order: {:?}", order),
+                Ok((order, fills)) => {
+                    println!(
+                        "succesfully submitted order: {:?}, fills: {:?}",
+                        order, fills
+                    )
+                }
                 Err(order_err) => error!("an error has occurred: {}", order_err),
             }
         }
@@ -78,7 +83,7 @@ fn main() {
         exchange.account_tracker().num_trades(),
         t0.elapsed().as_millis()
     );
-    analyze_results(&exchange.account_tracker());
+    analyze_results(exchange.account_tracker());
 }
 
 /// analyze the resulting performance metrics of the traded orders
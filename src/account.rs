@@ -4,7 +4,9 @@ use crate::{
     order_margin::compute_order_margin,
     position::Position,
     prelude::AccountTracker,
-    types::{Currency, Error, Fee, Leverage, MarginCurrency, Order, OrderType, Result},
+    types::{
+        Currency, Error, Fee, Leverage, MarginCurrency, Order, OrderType, QuoteCurrency, Result,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -20,8 +22,20 @@ where
     pub(crate) position: Position<M>,
     // Maps the order `id` to the actual `Order`.
     pub(crate) active_limit_orders: HashMap<u64, Order<M::PairedCurrency>>,
+    // Maps the order `id` to the conditional orders that have not been triggered yet.
+    pub(crate) untriggered_orders: HashMap<u64, Order<M::PairedCurrency>>,
     // Maps the `user_order_id` to the internal order nonce
     pub(crate) lookup_order_nonce_from_user_order_id: HashMap<u64, u64>,
+    // Maps the order `id` of linked orders to the id of their one-cancels-other group.
+    pub(crate) order_group_ids: HashMap<u64, u64>,
+    // Maps the group id to the order ids of the one-cancels-other group.
+    pub(crate) order_groups: HashMap<u64, Vec<u64>>,
+    // Maps the `id` of a bracket entry order to its child orders,
+    // which become active once the entry order is filled.
+    pub(crate) pending_child_orders: HashMap<u64, Vec<Order<M::PairedCurrency>>>,
+    // Maps the `id` of a partially filled bracket entry order to the ids of its active child orders,
+    // together with the quantity they grow to along with the fills of the entry.
+    pub(crate) bracket_child_orders: HashMap<u64, Vec<(u64, M::PairedCurrency)>>,
     maker_fee: Fee,
     order_margin: M,
 }
@@ -38,7 +52,12 @@ where
             wallet_balance: starting_balance,
             position,
             active_limit_orders: HashMap::new(),
+            untriggered_orders: HashMap::new(),
             lookup_order_nonce_from_user_order_id: HashMap::new(),
+            order_group_ids: HashMap::new(),
+            order_groups: HashMap::new(),
+            pending_child_orders: HashMap::new(),
+            bracket_child_orders: HashMap::new(),
             maker_fee,
             order_margin: M::new_zero(),
         }
@@ -68,12 +87,49 @@ where
         &self.active_limit_orders
     }
 
+    /// Return a reference to the conditional orders of the account, which have not been triggered yet.
+    /// These don't require any order margin until they are triggered.
+    #[inline(always)]
+    pub fn untriggered_orders(&self) -> &HashMap<u64, Order<M::PairedCurrency>> {
+        &self.untriggered_orders
+    }
+
+    /// Return the one-cancels-other groups of the account,
+    /// mapping the group id to the ids of the linked orders.
+    #[inline(always)]
+    pub fn order_groups(&self) -> &HashMap<u64, Vec<u64>> {
+        &self.order_groups
+    }
+
+    /// Return the child orders of bracket entry orders, which become active once the entry is filled.
+    #[inline(always)]
+    pub fn pending_child_orders(&self) -> &HashMap<u64, Vec<Order<M::PairedCurrency>>> {
+        &self.pending_child_orders
+    }
+
+    /// Return the number of open orders, which count towards `Config::max_num_open_orders`.
+    /// These are the active limit orders, the untriggered conditional orders
+    /// and the children of bracket orders, whose entry order has not been filled yet.
+    pub fn num_open_orders(&self) -> usize {
+        self.active_limit_orders.len()
+            + self.untriggered_orders.len()
+            + self
+                .pending_child_orders
+                .values()
+                .map(Vec::len)
+                .sum::<usize>()
+    }
+
     /// Return the available balance of the `Account`
     #[inline(always)]
     pub fn available_balance(&self) -> M {
         // TODO: this call is expensive so maybe compute once and store
-        let order_margin =
-            compute_order_margin(&self.position, &self.active_limit_orders, self.maker_fee);
+        let order_margin = compute_order_margin(
+            &self.position,
+            &self.active_limit_orders,
+            &self.order_group_ids,
+            self.maker_fee,
+        );
         let ab = self.wallet_balance - self.position.position_margin - order_margin;
         debug_assert!(ab >= M::new_zero());
         ab
@@ -112,9 +168,183 @@ where
         self.cancel_order(id, account_tracker)
     }
 
+    /// Append a new conditional order, which waits for its trigger price.
+    pub(crate) fn append_untriggered_order(&mut self, order: Order<M::PairedCurrency>) {
+        debug_assert!(order.trigger_price().is_some());
+
+        debug!("append_untriggered_order: order: {:?}", order);
+
+        let order_id = order.id();
+        if let Some(user_order_id) = order.user_order_id() {
+            self.lookup_order_nonce_from_user_order_id
+                .insert(*user_order_id, order_id);
+        }
+        self.untriggered_orders.insert(order_id, order);
+    }
+
+    /// Removes a conditional order that got triggered from the untriggered ones.
+    pub(crate) fn remove_triggered_order(&mut self, order_id: u64) -> Order<M::PairedCurrency> {
+        let order = self
+            .untriggered_orders
+            .remove(&order_id)
+            .expect("The order must have been untriggered; qed");
+        if let Some(user_order_id) = order.user_order_id() {
+            self.lookup_order_nonce_from_user_order_id
+                .remove(user_order_id);
+        }
+        order
+    }
+
+    /// Link the orders into a one-cancels-other group, whose id is the id of the first order.
+    /// This must happen before the orders are appended, so the order margin is computed for the group.
+    pub(crate) fn link_orders(&mut self, order_ids: Vec<u64>) {
+        debug_assert!(order_ids.len() > 1);
+
+        debug!("link_orders: {:?}", order_ids);
+
+        let group_id = order_ids[0];
+        for order_id in order_ids.iter() {
+            self.order_group_ids.insert(*order_id, group_id);
+        }
+        self.order_groups.insert(group_id, order_ids);
+    }
+
+    /// Dissolve the one-cancels-other group of an order, as one of its orders got executed.
+    ///
+    /// # Returns:
+    /// The ids of the sibling orders, which must be cancelled.
+    pub(crate) fn unlink_order_group(&mut self, order_id: u64) -> Vec<u64> {
+        let Some(group_id) = self.order_group_ids.get(&order_id).copied() else {
+            return Vec::new();
+        };
+        let order_ids = self
+            .order_groups
+            .remove(&group_id)
+            .expect("The group exists; qed");
+        for id in order_ids.iter() {
+            self.order_group_ids.remove(id);
+        }
+
+        Vec::from_iter(order_ids.into_iter().filter(|id| *id != order_id))
+    }
+
+    /// Remove a single order from its one-cancels-other group, e.g.: when it got cancelled.
+    /// A group with a single remaining order is dissolved.
+    fn remove_from_order_group(&mut self, order_id: u64) {
+        let Some(group_id) = self.order_group_ids.remove(&order_id) else {
+            return;
+        };
+        let order_ids = self
+            .order_groups
+            .get_mut(&group_id)
+            .expect("The group exists; qed");
+        order_ids.retain(|id| *id != order_id);
+        if order_ids.len() < 2 {
+            for id in order_ids.iter() {
+                self.order_group_ids.remove(id);
+            }
+            self.order_groups.remove(&group_id);
+        }
+    }
+
+    /// Attach the child orders of a bracket to its entry order.
+    pub(crate) fn append_pending_child_orders(
+        &mut self,
+        entry_order_id: u64,
+        child_orders: Vec<Order<M::PairedCurrency>>,
+    ) {
+        debug!(
+            "append_pending_child_orders: entry_order_id: {}, child_orders: {:?}",
+            entry_order_id, child_orders
+        );
+
+        for order in child_orders.iter() {
+            if let Some(user_order_id) = order.user_order_id() {
+                self.lookup_order_nonce_from_user_order_id
+                    .insert(*user_order_id, order.id());
+            }
+        }
+        self.pending_child_orders
+            .insert(entry_order_id, child_orders);
+    }
+
+    /// Remove the child orders of a bracket entry order, e.g.: once it got filled.
+    pub(crate) fn take_pending_child_orders(
+        &mut self,
+        entry_order_id: u64,
+    ) -> Vec<Order<M::PairedCurrency>> {
+        let child_orders = self
+            .pending_child_orders
+            .remove(&entry_order_id)
+            .unwrap_or_default();
+        for order in child_orders.iter() {
+            if let Some(user_order_id) = order.user_order_id() {
+                self.lookup_order_nonce_from_user_order_id
+                    .remove(user_order_id);
+            }
+        }
+        child_orders
+    }
+
+    /// Remove a single pending child order of a bracket.
+    fn remove_pending_child_order(&mut self, order_id: u64) -> Option<Order<M::PairedCurrency>> {
+        let entry_order_id = *self
+            .pending_child_orders
+            .iter()
+            .find(|(_, orders)| orders.iter().any(|order| order.id() == order_id))?
+            .0;
+        let child_orders = self
+            .pending_child_orders
+            .get_mut(&entry_order_id)
+            .expect("The entry order exists; qed");
+        let idx = child_orders
+            .iter()
+            .position(|order| order.id() == order_id)
+            .expect("The child order exists; qed");
+        let order = child_orders.remove(idx);
+        if child_orders.is_empty() {
+            self.pending_child_orders.remove(&entry_order_id);
+        }
+        Some(order)
+    }
+
+    /// Grow an open child order of a bracket to the `quantity`, along with the fills of its entry order.
+    ///
+    /// # Returns:
+    /// The resized order, if it is an active limit order, whose queue position has to be updated.
+    pub(crate) fn resize_child_order(
+        &mut self,
+        order_id: u64,
+        quantity: M::PairedCurrency,
+    ) -> Option<Order<M::PairedCurrency>> {
+        if let Some(order) = self.untriggered_orders.get_mut(&order_id) {
+            if quantity > order.quantity() {
+                order.set_quantity(quantity);
+            }
+            return None;
+        }
+        let order = self.active_limit_orders.get_mut(&order_id)?;
+        if quantity <= order.quantity() {
+            return None;
+        }
+        order.set_quantity(quantity);
+        let order = order.clone();
+        self.order_margin = compute_order_margin(
+            &self.position,
+            &self.active_limit_orders,
+            &self.order_group_ids,
+            self.maker_fee,
+        );
+
+        Some(order)
+    }
+
     /// Append a new limit order as active order
     pub(crate) fn append_limit_order(&mut self, order: Order<M::PairedCurrency>) {
-        debug_assert!(matches!(order.order_type(), OrderType::Limit));
+        debug_assert!(matches!(
+            order.order_type(),
+            OrderType::Limit | OrderType::StopLimit | OrderType::TakeProfitLimit
+        ));
 
         debug!("append_limit_order: order: {:?}", order);
 
@@ -138,8 +368,12 @@ where
                     .insert(user_order_id, order_id);
             }
         };
-        self.order_margin =
-            compute_order_margin(&self.position, &self.active_limit_orders, self.maker_fee);
+        self.order_margin = compute_order_margin(
+            &self.position,
+            &self.active_limit_orders,
+            &self.order_group_ids,
+            self.maker_fee,
+        );
     }
 
     /// Cancel an active order
@@ -153,26 +387,175 @@ where
         A: AccountTracker<M>,
     {
         debug!("cancel_order: {}", order_id);
-        let removed_order = self
-            .active_limit_orders
-            .remove(&order_id)
-            .ok_or(Error::OrderIdNotFound)?;
-        self.order_margin =
-            compute_order_margin(&self.position, &self.active_limit_orders, self.maker_fee);
+        if self.active_limit_orders.contains_key(&order_id) {
+            account_tracker.log_limit_order_cancellation();
+        }
+        self.remove_open_order(order_id)
+    }
 
-        account_tracker.log_limit_order_cancellation();
+    /// Cancel all open orders with the given `order_ids`, computing the order margin only once.
+    /// Orders which are no longer open, e.g. the children of a cancelled bracket entry, are skipped.
+    ///
+    /// # Returns:
+    /// The cancelled orders.
+    pub(crate) fn cancel_orders<A>(
+        &mut self,
+        order_ids: &[u64],
+        account_tracker: &mut A,
+    ) -> Vec<Order<M::PairedCurrency>>
+    where
+        A: AccountTracker<M>,
+    {
+        debug!("cancel_orders: {:?}", order_ids);
+        let mut cancelled = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            if self.active_limit_orders.contains_key(order_id) {
+                account_tracker.log_limit_order_cancellation();
+            }
+            if let Ok(order) = self.take_open_order(*order_id) {
+                cancelled.push(order);
+            }
+        }
+        self.order_margin = compute_order_margin(
+            &self.position,
+            &self.active_limit_orders,
+            &self.order_group_ids,
+            self.maker_fee,
+        );
+
+        cancelled
+    }
+
+    /// Cancel an open order, whose expiry timestamp has passed.
+    pub(crate) fn expire_order<A>(
+        &mut self,
+        order_id: u64,
+        account_tracker: &mut A,
+    ) -> Result<Order<M::PairedCurrency>>
+    where
+        A: AccountTracker<M>,
+    {
+        debug!("expire_order: {}", order_id);
+        let mut order = self.remove_open_order(order_id)?;
+        order.mark_expired();
+        account_tracker.log_order_expiry();
+
+        Ok(order)
+    }
+
+    /// Get an open order, which is either active, untriggered or a pending child order.
+    pub(crate) fn open_order(&self, order_id: u64) -> Option<&Order<M::PairedCurrency>> {
+        self.active_limit_orders
+            .get(&order_id)
+            .or_else(|| self.untriggered_orders.get(&order_id))
+            .or_else(|| {
+                self.pending_child_orders
+                    .values()
+                    .flatten()
+                    .find(|order| order.id() == order_id)
+            })
+    }
+
+    /// Remove an open order, which is either active, untriggered or a pending child order.
+    fn remove_open_order(&mut self, order_id: u64) -> Result<Order<M::PairedCurrency>> {
+        let was_active = self.active_limit_orders.contains_key(&order_id);
+        let removed_order = self.take_open_order(order_id)?;
+        // Untriggered and pending orders don't require order margin.
+        if was_active {
+            self.order_margin = compute_order_margin(
+                &self.position,
+                &self.active_limit_orders,
+                &self.order_group_ids,
+                self.maker_fee,
+            );
+        }
+
+        Ok(removed_order)
+    }
+
+    /// Remove an open order, without updating the order margin.
+    fn take_open_order(&mut self, order_id: u64) -> Result<Order<M::PairedCurrency>> {
+        let removed_order = match self.active_limit_orders.remove(&order_id) {
+            Some(order) => order,
+            None => match self.untriggered_orders.remove(&order_id) {
+                Some(order) => order,
+                None => self
+                    .remove_pending_child_order(order_id)
+                    .ok_or(Error::OrderIdNotFound)?,
+            },
+        };
+        if let Some(user_order_id) = removed_order.user_order_id() {
+            self.lookup_order_nonce_from_user_order_id
+                .remove(user_order_id);
+        }
+        self.remove_from_order_group(order_id);
+        // The children of a bracket can never become active without their entry order,
+        // while those already covering a partial fill of the entry are kept.
+        self.take_pending_child_orders(order_id);
+        self.bracket_child_orders.remove(&order_id);
 
         Ok(removed_order)
     }
 
+    /// Fill some `quantity` of an active limit order at the `fill_price`.
+    /// Once the order is completely filled, it is removed from the active ones.
+    ///
+    /// # Returns:
+    /// The order after it has been filled.
+    pub(crate) fn fill_limit_order(
+        &mut self,
+        order_id: u64,
+        quantity: M::PairedCurrency,
+        fill_price: QuoteCurrency,
+    ) -> Order<M::PairedCurrency> {
+        let order = self
+            .active_limit_orders
+            .get_mut(&order_id)
+            .expect("The order must have been active; qed");
+        order.fill(quantity, fill_price);
+        let order = order.clone();
+
+        if order.remaining_quantity().is_zero() {
+            self.remove_executed_order_from_active(order_id);
+        } else {
+            // The order margin shrinks with the remaining quantity.
+            self.order_margin = compute_order_margin(
+                &self.position,
+                &self.active_limit_orders,
+                &self.order_group_ids,
+                self.maker_fee,
+            );
+        }
+
+        order
+    }
+
+    /// Replace an active limit order with its amended version.
+    pub(crate) fn amend_limit_order(&mut self, order: Order<M::PairedCurrency>) {
+        debug!("amend_limit_order: order: {:?}", order);
+
+        let prev = self.active_limit_orders.insert(order.id(), order);
+        debug_assert!(prev.is_some(), "The order must have been active");
+        self.order_margin = compute_order_margin(
+            &self.position,
+            &self.active_limit_orders,
+            &self.order_group_ids,
+            self.maker_fee,
+        );
+    }
+
     /// Removes an executed limit order from the list of active ones
     pub(crate) fn remove_executed_order_from_active(&mut self, order_id: u64) {
         let order = self
             .active_limit_orders
             .remove(&order_id)
             .expect("The order must have been active; qed");
-        self.order_margin =
-            compute_order_margin(&self.position, &self.active_limit_orders, self.maker_fee);
+        self.order_margin = compute_order_margin(
+            &self.position,
+            &self.active_limit_orders,
+            &self.order_group_ids,
+            self.maker_fee,
+        );
         if let Some(user_order_id) = order.user_order_id() {
             self.lookup_order_nonce_from_user_order_id
                 .remove(user_order_id);
@@ -1,4 +1,4 @@
-use crate::prelude::{Currency, QuoteCurrency, Side};
+use crate::prelude::{AutoDeleverage, Currency, Fill, Liquidation, QuoteCurrency, Side};
 
 /// Something that tracks the performance of the Account.
 /// This allows for greated flexibility over using the FullAccountTracker
@@ -33,6 +33,12 @@ where
     /// Log a limit order cancellation event
     fn log_limit_order_cancellation(&mut self);
 
+    /// Log a limit order amend event
+    fn log_limit_order_amend(&mut self);
+
+    /// Log an order expiry event, where an order got cancelled because its expiry timestamp passed.
+    fn log_order_expiry(&mut self);
+
     /// Log a limit order fill event.
     fn log_limit_order_fill(&mut self);
 
@@ -41,4 +47,20 @@ where
 
     /// Log a trade event where some order got filled and the position changed
     fn log_trade(&mut self, side: Side, price: QuoteCurrency, quantity: M::PairedCurrency);
+
+    /// Log the execution of an order, with the fee paid and the realized profit and loss of the fill.
+    fn log_fill(&mut self, fill: &Fill<M::PairedCurrency>);
+
+    /// Log a funding payment at the end of a funding period,
+    /// which the account received if positive, or paid if negative.
+    fn log_funding_payment(&mut self, funding_payment: M);
+
+    /// Log a liquidation, where the exchange took over and closed the position.
+    /// Its fill is logged as trade and fill as well.
+    fn log_liquidation(&mut self, liquidation: &Liquidation<M::PairedCurrency>);
+
+    /// Log an auto-deleveraging, where the exchange force-reduced the profitable position
+    /// to cover the liquidation of another trader.
+    /// Its fill is logged as trade and fill as well.
+    fn log_auto_deleverage(&mut self, auto_deleverage: &AutoDeleverage<M::PairedCurrency>);
 }
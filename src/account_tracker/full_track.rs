@@ -6,7 +6,7 @@ use crate::{
     account_tracker::AccountTracker,
     cornish_fisher::cornish_fisher_value_at_risk,
     quote,
-    types::{Currency, MarginCurrency, QuoteCurrency, Side},
+    types::{AutoDeleverage, Currency, Fill, Liquidation, MarginCurrency, QuoteCurrency, Side},
     utils::{decimal_pow, decimal_sqrt, decimal_sum, decimal_to_f64, min, variance},
 };
 
@@ -39,8 +39,13 @@ pub struct FullAccountTracker<M> {
     num_losses: usize,
     num_submitted_limit_orders: usize,
     num_cancelled_limit_orders: usize,
+    num_amended_limit_orders: usize,
+    num_expired_orders: usize,
     num_limit_order_fills: usize,
     num_market_order_fills: usize,
+    num_maker_fills: usize,
+    num_liquidations: usize,
+    num_auto_deleverages: usize,
     num_trading_opportunities: usize,
     total_turnover: M,
     max_drawdown_wallet_balance: Decimal,
@@ -65,6 +70,7 @@ pub struct FullAccountTracker<M> {
     last_hourly_pnl: M,
     last_tick_pnl: M,
     cumulative_fees: M,
+    cumulative_funding: M,
     total_profit: M,
     total_loss: M,
     price_first: QuoteCurrency,
@@ -99,8 +105,13 @@ where
             num_losses: 0,
             num_submitted_limit_orders: 0,
             num_cancelled_limit_orders: 0,
+            num_amended_limit_orders: 0,
+            num_expired_orders: 0,
             num_limit_order_fills: 0,
             num_market_order_fills: 0,
+            num_maker_fills: 0,
+            num_liquidations: 0,
+            num_auto_deleverages: 0,
             num_trading_opportunities: 0,
             total_turnover: M::new_zero(),
             max_drawdown_wallet_balance: Decimal::from(0),
@@ -120,6 +131,7 @@ where
             last_hourly_pnl: M::new_zero(),
             last_tick_pnl: M::new_zero(),
             cumulative_fees: M::new_zero(),
+            cumulative_funding: M::new_zero(),
             total_profit: M::new_zero(),
             total_loss: M::new_zero(),
             price_first: quote!(0.0),
@@ -175,6 +187,12 @@ where
         self.cumulative_fees
     }
 
+    /// Cumulative funding payments received, negative if more funding was paid than received
+    #[inline(always)]
+    pub fn cumulative_funding(&self) -> M {
+        self.cumulative_funding
+    }
+
     /// Would be return of buy and hold strategy
     #[inline(always)]
     pub fn buy_and_hold_return(&self) -> M {
@@ -543,6 +561,18 @@ where
         self.num_submitted_limit_orders
     }
 
+    /// Return the number of amended limit orders.
+    #[inline(always)]
+    pub fn num_amended_limit_orders(&self) -> usize {
+        self.num_amended_limit_orders
+    }
+
+    /// Return the number of orders that got cancelled because their expiry timestamp passed.
+    #[inline(always)]
+    pub fn num_expired_orders(&self) -> usize {
+        self.num_expired_orders
+    }
+
     /// Return the ratio of executed trades vs total trading opportunities
     /// Higher values means a more active trading agent
     #[inline(always)]
@@ -603,6 +633,24 @@ where
     pub fn market_order_trade_ratio(&self) -> f64 {
         self.num_market_order_fills as f64 / self.num_trades as f64
     }
+
+    /// The ratio of fills which provided liquidity relative to total trades.
+    #[inline(always)]
+    pub fn maker_fill_ratio(&self) -> f64 {
+        self.num_maker_fills as f64 / self.num_trades as f64
+    }
+
+    /// Return the number of times the position got liquidated.
+    #[inline(always)]
+    pub fn num_liquidations(&self) -> usize {
+        self.num_liquidations
+    }
+
+    /// Return the number of times the position got auto-deleveraged.
+    #[inline(always)]
+    pub fn num_auto_deleverages(&self) -> usize {
+        self.num_auto_deleverages
+    }
 }
 
 impl<M> AccountTracker<M> for FullAccountTracker<M>
@@ -741,6 +789,16 @@ where
         self.num_cancelled_limit_orders += 1;
     }
 
+    #[inline(always)]
+    fn log_limit_order_amend(&mut self) {
+        self.num_amended_limit_orders += 1;
+    }
+
+    #[inline(always)]
+    fn log_order_expiry(&mut self) {
+        self.num_expired_orders += 1;
+    }
+
     #[inline(always)]
     fn log_limit_order_fill(&mut self) {
         self.num_limit_order_fills += 1;
@@ -758,6 +816,28 @@ where
             self.num_buys += 1
         }
     }
+
+    #[inline(always)]
+    fn log_fill(&mut self, fill: &Fill<M::PairedCurrency>) {
+        if fill.is_maker() {
+            self.num_maker_fills += 1;
+        }
+    }
+
+    #[inline(always)]
+    fn log_funding_payment(&mut self, funding_payment: M) {
+        self.cumulative_funding += funding_payment;
+    }
+
+    #[inline(always)]
+    fn log_liquidation(&mut self, _liquidation: &Liquidation<M::PairedCurrency>) {
+        self.num_liquidations += 1;
+    }
+
+    #[inline(always)]
+    fn log_auto_deleverage(&mut self, _auto_deleverage: &AutoDeleverage<M::PairedCurrency>) {
+        self.num_auto_deleverages += 1;
+    }
 }
 
 impl<M> Display for FullAccountTracker<M>
@@ -790,6 +870,7 @@ profit_loss_ratio: {},
 buy_and_hold_returns: {},
 trade_percentage: {},
 cumulative_fees: {},
+cumulative_funding: {},
 num_trading_days: {},
             ",
             self.total_rpnl(),
@@ -814,6 +895,7 @@ num_trading_days: {},
             self.buy_and_hold_return(),
             self.trade_percentage(),
             self.cumulative_fees(),
+            self.cumulative_funding(),
             self.num_trading_days(),
         )
     }
@@ -1328,7 +1410,7 @@ mod tests {
 
     #[test]
     fn acc_tracker_historical_value_at_risk_from_n_hourly_returns() {
-        if let Err(_) = pretty_env_logger::try_init() {}
+        let _ = pretty_env_logger::try_init();
 
         let mut at = FullAccountTracker::new(quote!(100.0));
         at.hist_ln_returns_hourly_acc = LN_RETS_H.into();
@@ -1374,7 +1456,7 @@ mod tests {
 
     #[test]
     fn acc_tracker_cornish_fisher_value_at_risk_from_n_hourly_returns() {
-        if let Err(_) = pretty_env_logger::try_init() {}
+        let _ = pretty_env_logger::try_init();
 
         let mut at = FullAccountTracker::new(quote!(100.0));
         at.hist_ln_returns_hourly_acc = LN_RETS_H.into();
@@ -1397,7 +1479,7 @@ mod tests {
 
     #[test]
     fn acc_tracker_sortino() {
-        if let Err(_) = pretty_env_logger::try_init() {}
+        let _ = pretty_env_logger::try_init();
 
         let mut at = FullAccountTracker::new(quote!(100.0));
 
@@ -2,7 +2,7 @@ use std::fmt::Display;
 
 use crate::{
     account_tracker::AccountTracker,
-    types::{Currency, QuoteCurrency, Side},
+    types::{AutoDeleverage, Currency, Fill, Liquidation, QuoteCurrency, Side},
 };
 
 /// Performs no tracking of account performance
@@ -23,11 +23,23 @@ where
 
     fn log_limit_order_cancellation(&mut self) {}
 
+    fn log_limit_order_amend(&mut self) {}
+
+    fn log_order_expiry(&mut self) {}
+
     fn log_limit_order_fill(&mut self) {}
 
     fn log_market_order_fill(&mut self) {}
 
     fn log_trade(&mut self, _side: Side, _price: QuoteCurrency, _size: M::PairedCurrency) {}
+
+    fn log_fill(&mut self, _fill: &Fill<M::PairedCurrency>) {}
+
+    fn log_funding_payment(&mut self, _funding_payment: M) {}
+
+    fn log_liquidation(&mut self, _liquidation: &Liquidation<M::PairedCurrency>) {}
+
+    fn log_auto_deleverage(&mut self, _auto_deleverage: &AutoDeleverage<M::PairedCurrency>) {}
 }
 
 impl Display for NoAccountTracker {
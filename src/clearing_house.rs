@@ -4,7 +4,7 @@ use fpdec::Decimal;
 
 use crate::{
     prelude::{Account, AccountTracker},
-    types::{Currency, Fee, MarginCurrency, QuoteCurrency, Side},
+    types::{Currency, Fee, Fill, MarginCurrency, QuoteCurrency, Side},
 };
 
 /// A clearing house acts as an intermediary in futures transactions.
@@ -34,14 +34,33 @@ where
     }
 
     /// The funding period for perpetual futures has ended.
-    /// Funding = `mark_value` * `funding_rate`.
-    /// `mark_value` is denoted in the margin currency.
+    /// Funding = `mark_value` * `funding_rate`, which is transferred between the wallet and the exchange.
     /// If the funding rate is positive, longs pay shorts.
     /// Else its the otherway around.
-    /// TODO: not used but may be in the future.
-    #[allow(unused)]
-    pub(crate) fn settle_funding_period(&mut self, _mark_value: M, _funding_rate: Decimal) {
-        todo!("Support `settle_funding_period`")
+    ///
+    /// # Arguments:
+    /// `mark_value`: The absolute value of the position at the mark price, denoted in the margin currency.
+    /// `funding_rate`: The funding rate of the period.
+    ///
+    /// # Returns:
+    /// The funding payment the account received, or paid if negative.
+    pub(crate) fn settle_funding_period(
+        &mut self,
+        account: &mut Account<M>,
+        account_tracker: &mut A,
+        mark_value: M,
+        funding_rate: Decimal,
+    ) -> M {
+        let funding = mark_value * funding_rate;
+        let payment = if account.position.size() > M::PairedCurrency::new_zero() {
+            funding.into_negative()
+        } else {
+            funding
+        };
+        account.wallet_balance += payment;
+        account_tracker.log_funding_payment(payment);
+
+        payment
     }
 
     /// Settlement referes to the actual transfer of funds or assets between the buyer and seller to fulfill the trade.
@@ -50,53 +69,122 @@ where
     /// and the seller of every buy order.
     ///
     /// # Arguments:
-    /// `quantity`: The number of contract traded, where a negative number indicates a sell.
-    /// `fill_price`: The execution price of the trade
+    /// `fill`: The execution to settle, which receives the fee paid and the realized profit and loss.
     /// `fee`: The fee fraction for this type of order settlement.
     ///
     pub(crate) fn settle_filled_order(
         &mut self,
         account: &mut Account<M>,
         account_tracker: &mut A,
-        quantity: M::PairedCurrency,
-        fill_price: QuoteCurrency,
+        fill: &mut Fill<M::PairedCurrency>,
         fee: Fee,
+    ) {
+        let fee = fill.quantity().convert(fill.price()) * fee;
+        self.settle(account, account_tracker, fill, fee);
+    }
+
+    /// Settle the forced close of a liquidated position, or a part of it,
+    /// where the liquidation fee replaces the trading fee.
+    ///
+    /// # Arguments:
+    /// `fill`: The execution closing the position, which receives the fee paid and the realized profit and loss.
+    /// `liquidation_fee`: The liquidation fee, denoted in the margin currency.
+    ///
+    pub(crate) fn settle_liquidation(
+        &mut self,
+        account: &mut Account<M>,
+        account_tracker: &mut A,
+        fill: &mut Fill<M::PairedCurrency>,
+        liquidation_fee: M,
+    ) {
+        let position_margin = account.position.position_margin;
+        self.settle(account, account_tracker, fill, liquidation_fee);
+        // With a partial liquidation, the isolated margin of the closed part stays with the remaining position,
+        // reduced by its loss, so the position meets the maintenance margin at a lower size.
+        if !account.position.size().is_zero() {
+            account.position.position_margin = position_margin + fill.realized_pnl() - fill.fee();
+        }
+    }
+
+    /// Settle the forced reduction of a profitable position by auto-deleveraging,
+    /// which is executed at the bankruptcy price of the liquidation without any fee.
+    ///
+    /// # Arguments:
+    /// `fill`: The execution reducing the position, which receives the realized profit and loss.
+    ///
+    pub(crate) fn settle_auto_deleverage(
+        &mut self,
+        account: &mut Account<M>,
+        account_tracker: &mut A,
+        fill: &mut Fill<M::PairedCurrency>,
+    ) {
+        self.settle(account, account_tracker, fill, M::new_zero());
+    }
+
+    /// Charge the account with its part of a shortfall, which the insurance fund could not cover.
+    ///
+    /// # Arguments:
+    /// `amount`: The loss, denoted in the margin currency, which must not exceed the available balance.
+    /// `ts_ns`: The timestamp in nanoseconds of the liquidation, which caused the shortfall.
+    ///
+    pub(crate) fn settle_socialized_loss(
+        &mut self,
+        account: &mut Account<M>,
+        account_tracker: &mut A,
+        amount: M,
         ts_ns: i64,
     ) {
-        let side = if quantity > M::PairedCurrency::new_zero() {
-            Side::Buy
-        } else {
-            Side::Sell
-        };
-        account_tracker.log_trade(side, fill_price, quantity);
+        debug_assert!(amount <= account.available_balance());
+        account.wallet_balance -= amount;
+        account_tracker.log_rpnl(amount.into_negative(), ts_ns);
+    }
 
-        if quantity > M::PairedCurrency::new_zero() {
-            self.settle_buy_order(account, account_tracker, quantity, fill_price, fee, ts_ns);
-        } else {
-            self.settle_sell_order(
+    /// Settle a `fill`, paying the given `fee`.
+    fn settle(
+        &mut self,
+        account: &mut Account<M>,
+        account_tracker: &mut A,
+        fill: &mut Fill<M::PairedCurrency>,
+        fee: M,
+    ) {
+        let quantity = fill.signed_quantity();
+        let fill_price = fill.price();
+        let ts_ns = fill.timestamp_ns();
+        account_tracker.log_trade(fill.side(), fill_price, quantity);
+
+        let (fee, rpnl) = match fill.side() {
+            Side::Buy => {
+                self.settle_buy_order(account, account_tracker, quantity, fill_price, fee, ts_ns)
+            }
+            Side::Sell => self.settle_sell_order(
                 account,
                 account_tracker,
                 quantity.abs(),
                 fill_price,
                 fee,
                 ts_ns,
-            );
-        }
+            ),
+        };
+        fill.set_settlement(fee, rpnl);
+        account_tracker.log_fill(fill);
     }
 
+    /// Settle a buy, which increases a long or decreases a short position.
+    ///
+    /// # Returns:
+    /// The fee paid and the realized profit and loss, excluding the fee.
     fn settle_buy_order(
         &mut self,
         account: &mut Account<M>,
         account_tracker: &mut A,
         quantity: M::PairedCurrency,
         fill_price: QuoteCurrency,
-        fee: Fee,
+        fee: M,
         ts_ns: i64,
-    ) {
-        let notional_value = quantity.convert(fill_price);
-        let fee = notional_value * fee;
+    ) -> (M, M) {
         account.wallet_balance -= fee;
         account_tracker.log_fee(fee);
+        let mut realized_pnl = M::new_zero();
 
         if account.position.size() >= M::PairedCurrency::new_zero() {
             account.position.increase_long(quantity, fill_price);
@@ -107,6 +195,7 @@ where
                 let rpnl = account.position.decrease_short(quantity, fill_price);
                 account.wallet_balance += rpnl;
                 account_tracker.log_rpnl(rpnl - fee, ts_ns);
+                realized_pnl = rpnl;
             } else {
                 let new_long_size = quantity - account.position.size().abs();
 
@@ -116,26 +205,32 @@ where
                     .decrease_short(account.position.size().abs(), fill_price);
                 account.wallet_balance += rpnl;
                 account_tracker.log_rpnl(rpnl - fee, ts_ns);
+                realized_pnl = rpnl;
 
                 // also open a long
                 account.position.open_position(new_long_size, fill_price);
             }
         }
+
+        (fee, realized_pnl)
     }
 
+    /// Settle a sell, which increases a short or decreases a long position.
+    ///
+    /// # Returns:
+    /// The fee paid and the realized profit and loss, excluding the fee.
     fn settle_sell_order(
         &mut self,
         account: &mut Account<M>,
         account_tracker: &mut A,
         quantity: M::PairedCurrency,
         fill_price: QuoteCurrency,
-        fee: Fee,
+        fee: M,
         ts_ns: i64,
-    ) {
-        let notional_value = quantity.convert(fill_price);
-        let fee = notional_value * fee;
+    ) -> (M, M) {
         account.wallet_balance -= fee;
         account_tracker.log_fee(fee);
+        let mut realized_pnl = M::new_zero();
 
         if account.position.size() > M::PairedCurrency::new_zero() {
             if quantity <= account.position.size() {
@@ -143,6 +238,7 @@ where
                 let rpnl = account.position.decrease_long(quantity, fill_price);
                 account.wallet_balance += rpnl;
                 account_tracker.log_rpnl(rpnl - fee, ts_ns);
+                realized_pnl = rpnl;
             } else {
                 let new_short_size = quantity - account.position.size();
 
@@ -153,6 +249,7 @@ where
 
                 account.wallet_balance += rpnl;
                 account_tracker.log_rpnl(rpnl - fee, ts_ns);
+                realized_pnl = rpnl;
 
                 // Open a short as well
                 account
@@ -163,5 +260,7 @@ where
             // Increase short position
             account.position.increase_short(quantity, fill_price);
         }
+
+        (fee, realized_pnl)
     }
 }
@@ -1,6 +1,11 @@
+use fpdec::Decimal;
+
 use crate::{
     contract_specification::ContractSpecification,
-    types::{Currency, Error, Leverage, Result},
+    types::{
+        AdlRank, Currency, Error, Fee, Latency, Leverage, LiquidationClosePrice, LiquidationMode,
+        LossPolicy, MarketImpact, RateLimit, Result,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -21,6 +26,29 @@ where
     initial_leverage: Leverage,
     /// The contract specification.
     contract_specification: ContractSpecification<M::PairedCurrency>,
+    /// Whether limit orders which cross the book are executed immediately as taker,
+    /// instead of being rejected.
+    execute_marketable_limit_orders: bool,
+    /// The one-way latency of order submissions, amendments and cancellations.
+    order_entry_latency: Latency,
+    /// The delay in nanoseconds, with which the strategy sees the market data.
+    market_data_delay_ns: u64,
+    /// How the taker fills of market orders move the price.
+    market_impact: MarketImpact,
+    /// The request budgets, which limit the rate of order actions.
+    rate_limits: Vec<RateLimit>,
+    /// The fee charged on the value of a liquidated position.
+    liquidation_fee: Fee,
+    /// The price at which a liquidated position is closed.
+    liquidation_close_price: LiquidationClosePrice,
+    /// How much of a position is liquidated at once.
+    liquidation_mode: LiquidationMode,
+    /// The starting balance of the insurance fund of the exchange.
+    insurance_fund_balance: M,
+    /// What happens to a shortfall, which the insurance fund can't cover.
+    loss_policy: LossPolicy,
+    /// The rank of the account in the auto-deleveraging queue.
+    adl_rank: AdlRank<M::PairedCurrency>,
 }
 
 impl<M> Config<M>
@@ -60,6 +88,17 @@ where
             max_num_open_orders,
             initial_leverage,
             contract_specification,
+            execute_marketable_limit_orders: false,
+            order_entry_latency: Latency::None,
+            market_data_delay_ns: 0,
+            market_impact: MarketImpact::None,
+            rate_limits: Vec::new(),
+            liquidation_fee: Fee::new(Decimal::ZERO),
+            liquidation_close_price: LiquidationClosePrice::LiquidationPrice,
+            liquidation_mode: LiquidationMode::Full,
+            insurance_fund_balance: M::new_zero(),
+            loss_policy: LossPolicy::WriteOff,
+            adl_rank: AdlRank::default(),
         })
     }
 
@@ -86,4 +125,214 @@ where
     pub fn initial_leverage(&self) -> Leverage {
         self.initial_leverage
     }
+
+    /// Whether limit orders which cross the book are executed immediately as taker.
+    #[inline(always)]
+    pub fn execute_marketable_limit_orders(&self) -> bool {
+        self.execute_marketable_limit_orders
+    }
+
+    /// Configure how limit orders which cross the book are handled.
+    /// If enabled, they are filled immediately at the touch (or by walking the book), pay the taker fee
+    /// and any remainder rests in the book as a maker order.
+    /// By default they are rejected with `LimitPriceAboveAsk` or `LimitPriceBelowBid`.
+    #[inline(always)]
+    pub fn set_execute_marketable_limit_orders(&mut self, enabled: bool) {
+        self.execute_marketable_limit_orders = enabled
+    }
+
+    /// The one-way latency between the user and the exchange.
+    #[inline(always)]
+    pub fn order_entry_latency(&self) -> &Latency {
+        &self.order_entry_latency
+    }
+
+    /// Configure the one-way latency between the user and the exchange.
+    /// With any latency, submitted, amended and cancelled orders only take effect
+    /// once the market clock has passed their arrival time.
+    /// By default there is no latency.
+    pub fn set_order_entry_latency(&mut self, latency: Latency) -> Result<()> {
+        if let Latency::Sampled { samples, .. } = &latency {
+            if samples.is_empty() {
+                return Err(Error::InvalidLatency);
+            }
+        }
+        self.order_entry_latency = latency;
+
+        Ok(())
+    }
+
+    /// The delay in nanoseconds, with which `Exchange::market_state` lags the true market state.
+    #[inline(always)]
+    pub fn market_data_delay_ns(&self) -> u64 {
+        self.market_data_delay_ns
+    }
+
+    /// Configure the delay of the market data feed, which the strategy sees through `Exchange::market_state`.
+    /// The exchange itself keeps matching orders and checking risk against the true market state.
+    /// By default there is no delay.
+    #[inline(always)]
+    pub fn set_market_data_delay_ns(&mut self, delay_ns: u64) {
+        self.market_data_delay_ns = delay_ns
+    }
+
+    /// How the taker fills of market orders move the price.
+    #[inline(always)]
+    pub fn market_impact(&self) -> &MarketImpact {
+        &self.market_impact
+    }
+
+    /// Configure the impact of the users market orders on the price.
+    /// The fill price is shifted against the user and the simulated best bid and ask
+    /// stay shifted until the impact has worn off.
+    /// By default there is no market impact.
+    pub fn set_market_impact(&mut self, market_impact: MarketImpact) -> Result<()> {
+        let valid = match market_impact {
+            MarketImpact::None => true,
+            MarketImpact::Linear {
+                coefficient,
+                duration_ns,
+            }
+            | MarketImpact::SquareRoot {
+                coefficient,
+                duration_ns,
+            } => coefficient >= Decimal::ZERO && duration_ns > 0,
+            MarketImpact::Transient {
+                coefficient,
+                half_life_ns,
+            } => coefficient >= Decimal::ZERO && half_life_ns > 0,
+        };
+        if !valid {
+            return Err(Error::InvalidMarketImpact);
+        }
+        self.market_impact = market_impact;
+
+        Ok(())
+    }
+
+    /// The request budgets, which limit the rate of order actions.
+    #[inline(always)]
+    pub fn rate_limits(&self) -> &[RateLimit] {
+        &self.rate_limits
+    }
+
+    /// Configure the request budgets of the venue, which all have to be met by every order action.
+    /// Submissions, amendments and cancellations are rejected with `Error::RateLimitExceeded`
+    /// once a budget is used up for the current window of the market clock.
+    /// Requests failing the order filters are rejected before they use up any of the budget.
+    /// By default there are no rate limits.
+    pub fn set_rate_limits(&mut self, rate_limits: Vec<RateLimit>) -> Result<()> {
+        for rate_limit in rate_limits.iter() {
+            rate_limit.validate()?;
+        }
+        self.rate_limits = rate_limits;
+
+        Ok(())
+    }
+
+    /// The fee charged on the value of a liquidated position, as a fraction.
+    #[inline(always)]
+    pub fn liquidation_fee(&self) -> Fee {
+        self.liquidation_fee
+    }
+
+    /// Configure the fee, which is charged on the value of a liquidated position at its close price.
+    /// As the position is isolated, the fee is limited to what is left of its position margin.
+    /// By default there is no liquidation fee.
+    pub fn set_liquidation_fee(&mut self, liquidation_fee: Fee) -> Result<()> {
+        if liquidation_fee.inner() < Decimal::ZERO || liquidation_fee.inner() >= Decimal::ONE {
+            return Err(Error::InvalidLiquidationFee);
+        }
+        self.liquidation_fee = liquidation_fee;
+
+        Ok(())
+    }
+
+    /// The price at which a liquidated position is closed.
+    #[inline(always)]
+    pub fn liquidation_close_price(&self) -> LiquidationClosePrice {
+        self.liquidation_close_price
+    }
+
+    /// Configure whether a liquidated position is closed at its liquidation price or at its bankruptcy price.
+    /// By default it is closed at the liquidation price.
+    #[inline(always)]
+    pub fn set_liquidation_close_price(&mut self, close_price: LiquidationClosePrice) {
+        self.liquidation_close_price = close_price
+    }
+
+    /// How much of a position is liquidated at once.
+    #[inline(always)]
+    pub fn liquidation_mode(&self) -> LiquidationMode {
+        self.liquidation_mode
+    }
+
+    /// Configure whether a liquidated position is closed at once or reduced step by step,
+    /// until it meets the maintenance margin again.
+    /// By default the whole position is closed.
+    pub fn set_liquidation_mode(&mut self, liquidation_mode: LiquidationMode) -> Result<()> {
+        if let LiquidationMode::Partial { fraction } = liquidation_mode {
+            if fraction <= Decimal::ZERO || fraction >= Decimal::ONE {
+                return Err(Error::InvalidLiquidationMode);
+            }
+        }
+        self.liquidation_mode = liquidation_mode;
+
+        Ok(())
+    }
+
+    /// The starting balance of the insurance fund of the exchange.
+    #[inline(always)]
+    pub fn insurance_fund_balance(&self) -> M {
+        self.insurance_fund_balance
+    }
+
+    /// Configure the starting balance of the insurance fund,
+    /// which covers the shortfall of liquidations closed beyond their bankruptcy price.
+    /// By default the insurance fund starts empty.
+    pub fn set_insurance_fund_balance(&mut self, balance: M) -> Result<()> {
+        if balance < M::new_zero() {
+            return Err(Error::InvalidInsuranceFundBalance);
+        }
+        self.insurance_fund_balance = balance;
+
+        Ok(())
+    }
+
+    /// What happens to a shortfall, which the insurance fund can't cover.
+    #[inline(always)]
+    pub fn loss_policy(&self) -> LossPolicy {
+        self.loss_policy
+    }
+
+    /// Configure what happens to a shortfall, once the insurance fund runs dry.
+    /// By default the exchange writes it off.
+    pub fn set_loss_policy(&mut self, loss_policy: LossPolicy) -> Result<()> {
+        if let LossPolicy::SocializedLoss { share } = loss_policy {
+            if share < Decimal::ZERO || share > Decimal::ONE {
+                return Err(Error::InvalidLossPolicy);
+            }
+        }
+        self.loss_policy = loss_policy;
+
+        Ok(())
+    }
+
+    /// The rank of the account in the auto-deleveraging queue of `LossPolicy::AutoDeleverage`.
+    #[inline(always)]
+    pub fn adl_rank(&self) -> &AdlRank<M::PairedCurrency> {
+        &self.adl_rank
+    }
+
+    /// Configure the rank of the account in the auto-deleveraging queue,
+    /// either as a fixed quantity ranked ahead of it or among a synthetic population of other traders.
+    /// By default the account is first in the queue.
+    pub fn set_adl_rank(&mut self, adl_rank: AdlRank<M::PairedCurrency>) -> Result<()> {
+        if !adl_rank.is_valid() {
+            return Err(Error::InvalidAdlRank);
+        }
+        self.adl_rank = adl_rank;
+
+        Ok(())
+    }
 }
@@ -1,31 +1,65 @@
+use std::cmp::{max, min};
+
+use fpdec::Decimal;
+
 use crate::{
     account::Account,
-    account_tracker::AccountTracker,
+    account_tracker::{AccountTracker, NoAccountTracker},
     clearing_house::ClearingHouse,
     config::Config,
-    market_state::MarketState,
+    contract_specification::MarkMethod,
+    fill_model::{CandleFills, FillModel, PessimisticFillModel},
+    insurance_fund::InsuranceFund,
+    market_state::{MarketDataFeed, MarketState},
+    matching_engine::MatchingEngine,
+    pending_actions::{PendingAction, PendingActions},
     risk_engine::{IsolatedMarginRiskEngine, RiskEngine},
     types::{
-        Currency, Error, MarginCurrency, MarketUpdate, Order, OrderError, OrderType, Result, Side,
+        adl_score, AutoDeleverage, BatchOrders, Currency, Error, Fill, LatencySampler, Liquidation,
+        LiquidationClosePrice, LossPolicy, MarginCurrency, MarketUpdate, Order, OrderAction,
+        OrderError, OrderType, PriceImpact, QuoteCurrency, RateLimiter, Result, Side, TimeInForce,
+        TriggerPriceSource,
     },
 };
 
 pub(crate) const EXPECT_LIMIT_PRICE: &str = "A limit price must be present for a limit order; qed";
+pub(crate) const EXPECT_TRIGGER_PRICE: &str =
+    "A trigger price must be present for a conditional order; qed";
+
+/// The executed, expired or rejected orders, returned by `Exchange::update_state`,
+/// together with every `Fill` settled in the meantime.
+/// `Exchange::submit_bracket` returns the orders of the bracket with the fills of its entry order.
+pub type Executions<S> = (Vec<Order<S>>, Vec<Fill<S>>);
 
 #[derive(Debug, Clone)]
 /// The main leveraged futures exchange for simulated trading
-pub struct Exchange<A, S>
+///
+/// Generic over:
+/// `A`: The `AccountTracker` for performance statistics.
+/// `S`: The `Currency` representing the order quantity.
+/// `F`: The `FillModel`, which decides how resting limit orders are filled by candles.
+pub struct Exchange<A, S, F = PessimisticFillModel>
 where
     S: Currency,
     S::PairedCurrency: MarginCurrency,
 {
     config: Config<S::PairedCurrency>,
     market_state: MarketState,
+    market_data_feed: MarketDataFeed,
     account: Account<S::PairedCurrency>,
     account_tracker: A,
     risk_engine: IsolatedMarginRiskEngine<S::PairedCurrency>,
     clearing_house: ClearingHouse<A, S::PairedCurrency>,
+    matching_engine: MatchingEngine<S>,
     next_order_id: u64,
+    latency: LatencySampler,
+    pending_actions: PendingActions<S>,
+    fill_model: F,
+    price_impact: PriceImpact,
+    rate_limiter: RateLimiter,
+    insurance_fund: InsuranceFund<S::PairedCurrency>,
+    /// The fills of the current call to `update_state` or `submit_order`.
+    fills: Vec<Fill<S>>,
 }
 
 impl<A, S> Exchange<A, S>
@@ -37,7 +71,33 @@ where
     /// Create a new Exchange with the desired config and whether to use candles
     /// as infomation source
     pub fn new(account_tracker: A, config: Config<S::PairedCurrency>) -> Self {
+        Self::with_fill_model(account_tracker, config, PessimisticFillModel::default())
+    }
+}
+
+impl<A, S, F> Exchange<A, S, F>
+where
+    A: AccountTracker<S::PairedCurrency>,
+    S: Currency,
+    S::PairedCurrency: MarginCurrency,
+    F: FillModel,
+{
+    /// Create a new Exchange, which fills resting limit orders by candles according to the `fill_model`.
+    ///
+    /// # Arguments:
+    /// `account_tracker`: The `AccountTracker` for performance statistics.
+    /// `config`: The exchange configuration.
+    /// `fill_model`: Decides how resting limit orders are filled by a `MarketUpdate::Candle`.
+    pub fn with_fill_model(
+        account_tracker: A,
+        config: Config<S::PairedCurrency>,
+        fill_model: F,
+    ) -> Self {
         let market_state = MarketState::new(config.contract_specification().price_filter.clone());
+        let market_data_feed = MarketDataFeed::new(
+            config.market_data_delay_ns(),
+            config.contract_specification().price_filter.clone(),
+        );
         let account = Account::new(
             config.starting_balance(),
             config.initial_leverage(),
@@ -45,17 +105,31 @@ where
         );
         let risk_engine = IsolatedMarginRiskEngine::<S::PairedCurrency>::new(
             config.contract_specification().clone(),
+            config.liquidation_mode(),
         );
         let clearing_house = ClearingHouse::new();
+        let latency = LatencySampler::new(config.order_entry_latency().clone());
+        let price_impact = PriceImpact::new(*config.market_impact());
+        let rate_limiter = RateLimiter::new(config.rate_limits());
+        let insurance_fund = InsuranceFund::new(config.insurance_fund_balance());
 
         Self {
             config,
             market_state,
+            market_data_feed,
             clearing_house,
             risk_engine,
+            matching_engine: MatchingEngine::new(),
             account,
             account_tracker,
             next_order_id: 0,
+            latency,
+            pending_actions: PendingActions::new(),
+            fill_model,
+            price_impact,
+            rate_limiter,
+            insurance_fund,
+            fills: Vec::new(),
         }
     }
 
@@ -83,10 +157,34 @@ where
         &self.account_tracker
     }
 
-    /// Return a reference to the currency `MarketState`
+    /// Return a reference to the currency `MarketState`, as seen by the strategy.
+    /// With a market data delay configured, it lags the true state used for matching and risk.
     #[inline(always)]
     pub fn market_state(&self) -> &MarketState {
-        &self.market_state
+        if self.market_data_feed.is_immediate() {
+            &self.market_state
+        } else {
+            self.market_data_feed.view()
+        }
+    }
+
+    /// Return a reference to the `MatchingEngine`,
+    /// which holds the simulated order book and the queue positions of resting orders.
+    #[inline(always)]
+    pub fn matching_engine(&self) -> &MatchingEngine<S> {
+        &self.matching_engine
+    }
+
+    /// Return the insurance fund of the exchange, with its balance over time.
+    #[inline(always)]
+    pub fn insurance_fund(&self) -> &InsuranceFund<S::PairedCurrency> {
+        &self.insurance_fund
+    }
+
+    /// Return the price at which the current position gets liquidated, if there is a position.
+    #[inline(always)]
+    pub fn liquidation_price(&self) -> Option<QuoteCurrency> {
+        self.risk_engine.liquidation_price(self.account.position())
     }
 
     /// Update the exchange state with new information
@@ -97,15 +195,23 @@ where
     /// `market_update`: Newest market information
     ///
     /// ### Returns:
-    /// If Ok, the executed orders,
+    /// If Ok, the executed orders, which may also be partially filled,
+    /// the orders which expired, tagged by `Order::is_expired`,
+    /// and the orders rejected after they had been accepted, tagged by `Order::rejection`,
+    /// together with every `Fill` settled during this update,
+    /// including the close of a liquidated position and the reduction by auto-deleveraging.
     /// Some Error otherwise
     pub fn update_state(
         &mut self,
         timestamp_ns: u64,
         market_update: MarketUpdate<S>,
-    ) -> Result<Vec<Order<S>>> {
+    ) -> Result<Executions<S>> {
+        self.fills.clear();
+        // Order actions arriving before this update see the market as it was until now.
+        let mut to_be_exec = self.apply_pending_actions(timestamp_ns as i64);
         self.market_state
             .update_state(timestamp_ns, &market_update)?;
+        self.apply_price_impact(&market_update);
         self.account_tracker.update(
             timestamp_ns,
             self.market_state.mid_price(),
@@ -117,80 +223,610 @@ where
             .risk_engine
             .check_maintenance_margin(&self.market_state, &self.account)
         {
-            // TODO: liquidate position properly
-            return Err(e.into());
+            debug!("{}", e);
+            self.liquidate();
+        };
+        match &market_update {
+            MarketUpdate::Liquidation {
+                side,
+                bankruptcy_price,
+                quantity,
+            } => self.absorb_liquidation(*side, *bankruptcy_price, *quantity),
+            MarketUpdate::Funding { rate } => self.settle_funding(*rate),
+            _ => {}
+        }
+
+        to_be_exec.append(&mut self.expire_orders());
+        match &market_update {
+            // The legs of the intrabar path are matched one after the other,
+            // so the orders which are hit first along the path get executed first.
+            MarketUpdate::Candle { .. } => {
+                let mut candle_fills =
+                    CandleFills::new(&market_update, self.fill_model.max_volume_fraction());
+                for leg in self.fill_model.intrabar_path().legs(&market_update) {
+                    to_be_exec.append(&mut self.match_market_update(&leg, Some(&mut candle_fills)));
+                }
+            }
+            _ => to_be_exec.append(&mut self.match_market_update(&market_update, None)),
+        }
+        if !self.market_data_feed.is_immediate() {
+            self.market_data_feed.publish(&self.market_state);
+        }
+        let filled_entries = Vec::from_iter(
+            to_be_exec
+                .iter()
+                .filter(|order| !order.filled_quantity().is_zero())
+                .map(|order| (order.id(), order.filled_quantity())),
+        );
+        for (order_id, filled_quantity) in filled_entries {
+            to_be_exec.append(&mut self.update_child_orders(order_id, filled_quantity));
+        }
+
+        Ok((to_be_exec, std::mem::take(&mut self.fills)))
+    }
+
+    /// Take over the position of the account, as it can't meet the maintenance margin anymore.
+    /// All open orders are cancelled and the position is closed at the configured `LiquidationClosePrice`,
+    /// charging the liquidation fee from what is left of the position margin.
+    /// With `LiquidationMode::Partial`, the position is reduced step by step,
+    /// until it meets the maintenance margin again.
+    /// The account keeps its remaining balance, so the simulation continues.
+    fn liquidate(&mut self) {
+        let mut order_ids = Vec::from_iter(
+            self.account
+                .active_limit_orders
+                .keys()
+                .chain(self.account.untriggered_orders.keys())
+                .copied()
+                .chain(
+                    self.account
+                        .pending_child_orders
+                        .values()
+                        .flatten()
+                        .map(|order| order.id()),
+                ),
+        );
+        order_ids.sort_unstable();
+        let mut cancelled_orders = self.process_cancel_many(&order_ids);
+
+        let initial_size = self.account.position().size().abs();
+        loop {
+            self.liquidation_step(initial_size, std::mem::take(&mut cancelled_orders));
+            if self.account.position().size().is_zero()
+                || self
+                    .risk_engine
+                    .check_maintenance_margin(&self.market_state, &self.account)
+                    .is_ok()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Close the position, or a part of it with `LiquidationMode::Partial`, and report the `Liquidation`.
+    ///
+    /// # Arguments:
+    /// `initial_size`: The absolute position size at the start of the liquidation.
+    /// `cancelled_orders`: The open orders, which got cancelled by the liquidation.
+    fn liquidation_step(&mut self, initial_size: S, cancelled_orders: Vec<Order<S>>) {
+        let position = self.account.position().clone();
+        let liquidation_price = self
+            .risk_engine
+            .liquidation_price(&position)
+            .expect("A liquidated position has a liquidation price; qed");
+        let bankruptcy_price = self.risk_engine.bankruptcy_price(&position);
+        let close_price = match self.config.liquidation_close_price() {
+            LiquidationClosePrice::LiquidationPrice => liquidation_price,
+            // If no price can lose the whole position margin, the market price is no worse.
+            LiquidationClosePrice::BankruptcyPrice => {
+                bankruptcy_price.unwrap_or(self.market_state.mid_price())
+            }
+        };
+        let quantity = self
+            .risk_engine
+            .liquidation_quantity(&position, initial_size);
+
+        let (side, closed_size) = if position.size() > S::new_zero() {
+            (Side::Sell, quantity)
+        } else {
+            (Side::Buy, quantity.into_negative())
+        };
+        let mut order = Order::market(side, quantity).expect("The quantity is positive; qed");
+        order.set_timestamp(self.market_state.current_timestamp_ns());
+        order.set_id(self.next_order_id());
+        let mut fill = Fill::new(
+            &order,
+            quantity,
+            close_price,
+            false,
+            self.market_state.current_timestamp_ns(),
+        );
+        // The fee is limited to what is left of the share of the position margin, which backs the closed part.
+        let remaining_margin = position.position_margin()
+            * (quantity.inner() / position.size().abs().inner())
+            + S::PairedCurrency::pnl(position.entry_price(), close_price, closed_size);
+        let liquidation_fee = min(
+            quantity.convert(close_price) * self.config.liquidation_fee(),
+            max(remaining_margin, S::PairedCurrency::new_zero()),
+        );
+        self.clearing_house.settle_liquidation(
+            &mut self.account,
+            &mut self.account_tracker,
+            &mut fill,
+            liquidation_fee,
+        );
+        // The exchange closes the position it took over in the market,
+        // so the insurance fund receives the liquidation fee and the difference to the market price.
+        let market_price = match side {
+            Side::Sell => self.market_state.bid(),
+            Side::Buy => self.market_state.ask(),
+        };
+        let insurance_fund_change =
+            liquidation_fee + S::PairedCurrency::pnl(close_price, market_price, closed_size);
+        let uncovered = self
+            .insurance_fund
+            .settle(insurance_fund_change, fill.timestamp_ns());
+        let socialized_loss = self.apply_loss_policy(uncovered, fill.timestamp_ns());
+        warn!(
+            "liquidated {} of position {} at {}, liquidation price: {}, bankruptcy price: {:?}",
+            quantity,
+            position.size(),
+            close_price,
+            liquidation_price,
+            bankruptcy_price
+        );
+        self.account_tracker.log_liquidation(&Liquidation::new(
+            fill.clone(),
+            liquidation_price,
+            bankruptcy_price,
+            cancelled_orders,
+            self.account.position().size(),
+            insurance_fund_change,
+            socialized_loss,
+        ));
+        self.fills.push(fill);
+    }
+
+    /// Charge the account with its part of a shortfall, which the insurance fund could not cover,
+    /// according to the configured `LossPolicy`.
+    ///
+    /// # Returns:
+    /// The loss the account paid, which is limited to its available balance.
+    fn apply_loss_policy(&mut self, uncovered: S::PairedCurrency, ts_ns: i64) -> S::PairedCurrency {
+        let loss = match self.config.loss_policy() {
+            // The profitable traders opposing the account are deleveraged instead.
+            LossPolicy::WriteOff | LossPolicy::AutoDeleverage => S::PairedCurrency::new_zero(),
+            LossPolicy::SocializedLoss { share } => uncovered * share,
+            LossPolicy::Clawback => uncovered,
+        };
+        let loss = min(
+            loss,
+            max(
+                self.account.available_balance(),
+                S::PairedCurrency::new_zero(),
+            ),
+        );
+        if loss > S::PairedCurrency::new_zero() {
+            warn!(
+                "the account pays {} of an uncovered shortfall of {}",
+                loss, uncovered
+            );
+            self.clearing_house.settle_socialized_loss(
+                &mut self.account,
+                &mut self.account_tracker,
+                loss,
+                ts_ns,
+            );
+        }
+
+        loss
+    }
+
+    /// Settle the end of a funding period with the position of the account,
+    /// whose value at the mark price times the `funding_rate` is paid or received.
+    fn settle_funding(&mut self, funding_rate: Decimal) {
+        let size = self.account.position().size();
+        if size.is_zero() {
+            return;
+        }
+        let mark_value = size
+            .abs()
+            .convert(self.trigger_reference_price(TriggerPriceSource::MarkPrice));
+        let payment = self.clearing_house.settle_funding_period(
+            &mut self.account,
+            &mut self.account_tracker,
+            mark_value,
+            funding_rate,
+        );
+        debug!(
+            "funding payment of {} at a rate of {} for position {}",
+            payment, funding_rate, size
+        );
+    }
+
+    /// Take over the position of another trader, which got liquidated according to a `MarketUpdate::Liquidation`.
+    /// The exchange closes it in the market, so the insurance fund receives the difference to the bankruptcy price,
+    /// or covers it. A shortfall, which the insurance fund can't cover, is handled according to the `LossPolicy`.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the liquidation order.
+    /// `bankruptcy_price`: The bankruptcy price of the liquidated position.
+    /// `quantity`: The liquidated quantity.
+    fn absorb_liquidation(&mut self, side: Side, bankruptcy_price: QuoteCurrency, quantity: S) {
+        let (market_price, closed_size) = match side {
+            Side::Sell => (self.market_state.bid(), quantity),
+            Side::Buy => (self.market_state.ask(), quantity.into_negative()),
+        };
+        let insurance_fund_change =
+            S::PairedCurrency::pnl(bankruptcy_price, market_price, closed_size);
+        let ts_ns = self.market_state.current_timestamp_ns();
+        let uncovered = self.insurance_fund.settle(insurance_fund_change, ts_ns);
+        if uncovered.is_zero() {
+            return;
+        }
+        match self.config.loss_policy() {
+            // The shortfall is written off or clawed back from the liquidated trader.
+            LossPolicy::WriteOff | LossPolicy::Clawback => {}
+            LossPolicy::SocializedLoss { .. } => {
+                self.apply_loss_policy(uncovered, ts_ns);
+            }
+            LossPolicy::AutoDeleverage => {
+                // Only the part of the position, whose shortfall is uncovered, needs to be deleveraged.
+                let step_size = self
+                    .config
+                    .contract_specification()
+                    .quantity_filter
+                    .step_size;
+                let mut deleveraged_quantity =
+                    quantity * (uncovered.inner() / insurance_fund_change.abs().inner());
+                let remainder = deleveraged_quantity % step_size;
+                if remainder != S::new_zero() {
+                    deleveraged_quantity = deleveraged_quantity - remainder + step_size;
+                }
+                self.auto_deleverage(side, bankruptcy_price, min(deleveraged_quantity, quantity));
+            }
+        }
+    }
+
+    /// Reduce the position of the account, if it is profitable and opposes the liquidation,
+    /// by what is left of the `deleveraged_quantity` after the positions ranked ahead of it in the ADL queue.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the liquidation order.
+    /// `bankruptcy_price`: The bankruptcy price of the liquidated position, at which the account is deleveraged.
+    /// `deleveraged_quantity`: The quantity of the liquidation, which is deleveraged across all opposing positions.
+    fn auto_deleverage(
+        &mut self,
+        side: Side,
+        bankruptcy_price: QuoteCurrency,
+        deleveraged_quantity: S,
+    ) {
+        let position = self.account.position().clone();
+        let is_opposing = match side {
+            Side::Sell => position.size() < S::new_zero(),
+            Side::Buy => position.size() > S::new_zero(),
+        };
+        if !is_opposing {
+            return;
+        }
+        let mark_price = self.market_state.mid_price();
+        let Some(score) = adl_score(
+            S::PairedCurrency::pnl(position.entry_price(), mark_price, position.size()),
+            position.size().abs().convert(position.entry_price()),
+            position.position_margin(),
+        ) else {
+            return;
         };
+        let quantity_ahead = self.config.adl_rank().quantity_ahead(
+            position.size() > S::new_zero(),
+            score,
+            mark_price,
+        );
+        if deleveraged_quantity <= quantity_ahead {
+            return;
+        }
+        let quantity = min(deleveraged_quantity - quantity_ahead, position.size().abs());
+
+        let mut order =
+            Order::market(side.inverted(), quantity).expect("The quantity is positive; qed");
+        order.set_timestamp(self.market_state.current_timestamp_ns());
+        order.set_id(self.next_order_id());
+        let mut fill = Fill::new(
+            &order,
+            quantity,
+            bankruptcy_price,
+            false,
+            self.market_state.current_timestamp_ns(),
+        );
+        self.clearing_house.settle_auto_deleverage(
+            &mut self.account,
+            &mut self.account_tracker,
+            &mut fill,
+        );
+        self.cancel_obsolete_reduce_only_orders();
+        warn!(
+            "auto-deleveraged {} of position {} at {}, quantity ahead: {}",
+            quantity,
+            position.size(),
+            bankruptcy_price,
+            quantity_ahead
+        );
+        self.account_tracker
+            .log_auto_deleverage(&AutoDeleverage::new(
+                fill.clone(),
+                deleveraged_quantity,
+                quantity_ahead,
+                self.account.position().size(),
+            ));
+        self.fills.push(fill);
+    }
+
+    /// Match the resting limit orders and the conditional orders against the `market_update`.
+    ///
+    /// # Arguments:
+    /// `market_update`: The newest market information.
+    /// `candle_fills`: The state of matching the candle, of which the `market_update` is a leg.
+    ///
+    /// # Returns:
+    /// The executed orders and the triggered orders, which got rejected.
+    fn match_market_update(
+        &mut self,
+        market_update: &MarketUpdate<S>,
+        mut candle_fills: Option<&mut CandleFills<S>>,
+    ) -> Vec<Order<S>> {
+        let fills = self.matching_engine.check_resting_orders(
+            &self.account.active_limit_orders,
+            market_update,
+            candle_fills.as_deref_mut(),
+            &mut self.fill_model,
+            self.config.contract_specification().price_filter.tick_size,
+        );
+        self.matching_engine.update_book(market_update);
+        if matches!(
+            market_update,
+            MarketUpdate::L2Update { .. } | MarketUpdate::L2Snapshot { .. }
+        ) {
+            self.sync_bba_with_book();
+        }
 
-        let mut to_be_exec = self.check_resting_orders(&market_update);
-        for order in to_be_exec.iter_mut() {
-            let qty = match order.side() {
-                Side::Buy => order.quantity(),
-                Side::Sell => order.quantity().into_negative(),
+        let mut executed = Vec::new();
+        for (order_id, mut filled_qty) in fills {
+            let (side, l_price, reduce_only) = {
+                // The order may have been cancelled by a linked order in the meantime.
+                let Some(order) = self.account.active_limit_orders.get(&order_id) else {
+                    continue;
+                };
+                (
+                    order.side(),
+                    order.limit_price().expect(EXPECT_LIMIT_PRICE),
+                    order.reduce_only(),
+                )
             };
-            let l_price = order.limit_price().expect(EXPECT_LIMIT_PRICE);
+            if reduce_only {
+                // A reduce only order never fills beyond the current position.
+                filled_qty = min(filled_qty, self.reducible_quantity(side));
+            }
+            if let Some(capacity) = candle_fills
+                .as_deref_mut()
+                .and_then(|candle_fills| candle_fills.fill_capacity())
+            {
+                filled_qty = min(filled_qty, *capacity);
+                *capacity -= filled_qty;
+            }
+            if filled_qty.is_zero() {
+                continue;
+            }
+            // Each partial fill is settled on its own.
+            let mut fill = Fill::new(
+                &self.account.active_limit_orders[&order_id],
+                filled_qty,
+                l_price,
+                true,
+                self.market_state.current_timestamp_ns(),
+            );
             self.clearing_house.settle_filled_order(
                 &mut self.account,
                 &mut self.account_tracker,
-                qty,
-                l_price,
+                &mut fill,
                 self.config.contract_specification().fee_maker,
-                self.market_state.current_timestamp_ns(),
             );
-            self.account.remove_executed_order_from_active(order.id());
-            self.account_tracker.log_limit_order_fill();
-            order.mark_filled(l_price);
+            self.fills.push(fill);
+            let order = self.account.fill_limit_order(order_id, filled_qty, l_price);
+            self.cancel_linked_orders(order_id);
+            if order.remaining_quantity().is_zero() {
+                self.matching_engine.remove_order(order_id);
+                self.account_tracker.log_limit_order_fill();
+            }
+            executed.push(order);
         }
+        self.cancel_obsolete_reduce_only_orders();
+        self.update_trailing_stops(market_update);
+        executed.append(&mut self.check_untriggered_orders(market_update));
 
-        Ok(to_be_exec)
+        executed
     }
 
-    /// Check if any resting orders have been executed
-    fn check_resting_orders(&mut self, market_update: &MarketUpdate<S>) -> Vec<Order<S>> {
-        Vec::from_iter(
-            self.account
-                .active_limit_orders
-                .values()
-                .filter(|order| self.check_limit_order_execution(order, market_update))
-                .cloned(),
-        )
+    /// Shift the best bid and ask of the `MarketState` by what remains of the impact of the users market orders.
+    fn apply_price_impact(&mut self, market_update: &MarketUpdate<S>) {
+        let prev_offset = self.price_impact.offset();
+        let offset = self.price_impact.decay(
+            self.market_state.current_timestamp_ns(),
+            self.config.contract_specification().price_filter.tick_size,
+        );
+        // Only some updates carry a fresh best bid and ask, the others still include the previous offset.
+        let shift = match market_update {
+            MarketUpdate::Bba { .. } | MarketUpdate::Candle { .. } => offset,
+            MarketUpdate::Trade { .. }
+            | MarketUpdate::L2Update { .. }
+            | MarketUpdate::L2Snapshot { .. }
+            | MarketUpdate::Liquidation { .. }
+            | MarketUpdate::Funding { .. } => offset - prev_offset,
+        };
+        if !shift.is_zero() {
+            self.shift_bba(shift);
+        }
+    }
+
+    /// Shift the best bid and ask of the `MarketState` by `shift`.
+    fn shift_bba(&mut self, shift: QuoteCurrency) {
+        let (bid, ask) = (self.market_state.bid(), self.market_state.ask());
+        self.market_state.set_bba(bid + shift, ask + shift);
     }
 
-    /// Check an individual resting order if it has been executed.
+    /// Use up the request budget of the configured rate limits, based on the market clock.
+    ///
+    /// # Arguments:
+    /// `actions`: The kinds of order actions in the request and how many of each.
     ///
     /// # Returns:
-    /// If `Some`, The order is filled and needs to be settled.
-    fn check_limit_order_execution(
-        &self,
-        limit_order: &Order<S>,
-        market_update: &MarketUpdate<S>,
-    ) -> bool {
-        let limit_price = limit_order.limit_price().expect(EXPECT_LIMIT_PRICE);
+    /// `Error::RateLimitExceeded` if any budget is used up for the current window.
+    fn acquire_rate_limit(&mut self, actions: &[(OrderAction, u32)]) -> Result<()> {
+        self.rate_limiter
+            .acquire(actions, self.market_state.current_timestamp_ns())
+    }
 
-        match market_update {
-            MarketUpdate::Bba { .. } => {
-                // Updates to the best bid and ask prices do not trigger limit orders for simulation purposes.
-                false
-            }
-            MarketUpdate::Trade {
-                price,
-                quantity: _,
-                side,
-            } => {
-                // For now we ignore the filled quantity, which will change in future versions.
-                match limit_order.side() {
-                    Side::Buy => *price <= limit_price && matches!(side, Side::Sell),
-                    Side::Sell => *price >= limit_price && matches!(side, Side::Buy),
+    /// Make sure the account can have `num_new` more open orders.
+    fn check_max_num_open_orders(&self, num_new: usize) -> Result<()> {
+        if self.account.num_open_orders() + num_new > self.config.max_num_open_orders() {
+            return Err(Error::OrderError(OrderError::MaxActiveOrders));
+        }
+
+        Ok(())
+    }
+
+    /// Send an order action to the exchange, where it arrives after the configured latency.
+    fn send_action(&mut self, kind: OrderAction, action: PendingAction<S>) {
+        let latency = self.latency.sample(kind);
+        let arrival_ts_ns = self.market_state.current_timestamp_ns() + latency as i64;
+        trace!(
+            "send_action: arrival_ts_ns: {}, {:?}",
+            arrival_ts_ns,
+            action
+        );
+        self.pending_actions.push(arrival_ts_ns, action);
+    }
+
+    /// Apply the order actions, which have arrived at the exchange by `timestamp_ns`.
+    /// The orders of rejected submissions and the amended orders of rejected amendments
+    /// are returned tagged by `Order::rejection`.
+    /// Cancellations of orders, which are no longer open, have no effect.
+    ///
+    /// # Returns:
+    /// The orders, which got executed upon arrival, and those rejected.
+    fn apply_pending_actions(&mut self, timestamp_ns: i64) -> Vec<Order<S>> {
+        let mut executed = Vec::new();
+        for action in self.pending_actions.pop_arrived(timestamp_ns) {
+            match action {
+                PendingAction::Submit(mut order) => {
+                    match self
+                        .validate_new_order(&order)
+                        .and_then(|_| self.process_order_submission(order.as_ref().clone()))
+                    {
+                        Ok(order) => {
+                            if !order.filled_quantity().is_zero() {
+                                executed.push(order);
+                            }
+                        }
+                        Err(e) => {
+                            debug!("order {} got rejected upon arrival: {}", order.id(), e);
+                            // The children of a bracket can't become active without their entry.
+                            self.account.take_pending_child_orders(order.id());
+                            order.mark_rejected(e);
+                            executed.push(*order);
+                        }
+                    }
+                }
+                PendingAction::SubmitOco(mut orders) => {
+                    if let Err(e) = orders
+                        .iter()
+                        .try_for_each(|order| self.validate_new_order(order))
+                        .and_then(|_| self.place_order_group(&mut orders))
+                    {
+                        debug!("one-cancels-other group got rejected upon arrival: {}", e);
+                        for mut order in orders {
+                            order.mark_rejected(e.clone());
+                            executed.push(order);
+                        }
+                    }
+                }
+                PendingAction::Amend(mut amended) => {
+                    if let Err(e) = self.process_amend(
+                        amended.id(),
+                        amended.limit_price().expect(EXPECT_LIMIT_PRICE),
+                        amended.quantity(),
+                    ) {
+                        debug!(
+                            "amendment of order {} got rejected upon arrival: {}",
+                            amended.id(),
+                            e
+                        );
+                        amended.mark_rejected(e);
+                        executed.push(*amended);
+                    }
+                }
+                PendingAction::Cancel(order_id) => {
+                    // The order may have been filled before the cancellation arrived.
+                    if let Err(e) = self.process_cancel(order_id) {
+                        debug!("cancellation of order {} had no effect: {}", order_id, e);
+                    }
+                }
+                PendingAction::SubmitBatch {
+                    mut orders,
+                    cancellations,
+                } => {
+                    if let Err(e) = orders
+                        .iter()
+                        .try_for_each(|order| self.validate_new_order(order))
+                        .and_then(|_| self.process_batch(&mut orders, &cancellations))
+                    {
+                        debug!("batch got rejected upon arrival: {}", e);
+                        for mut order in orders {
+                            order.mark_rejected(e.clone());
+                            executed.push(order);
+                        }
+                    }
+                }
+                PendingAction::CancelMany(order_ids) => {
+                    self.process_cancel_many(&order_ids);
                 }
             }
-            MarketUpdate::Candle {
-                bid: _,
-                ask: _,
-                low,
-                high,
-            } => match limit_order.side() {
-                Side::Buy => *low < limit_price,
-                Side::Sell => *high > limit_price,
-            },
+        }
+
+        executed
+    }
+
+    /// Cancel all open orders, whose expiry timestamp has passed.
+    ///
+    /// # Returns:
+    /// The expired orders.
+    fn expire_orders(&mut self) -> Vec<Order<S>> {
+        let now = self.market_state.current_timestamp_ns();
+        let mut expired = Vec::from_iter(
+            self.account
+                .active_limit_orders
+                .values()
+                .chain(self.account.untriggered_orders.values())
+                .filter(|order| order.expiry_timestamp_ns().is_some_and(|ts| now > ts))
+                .map(|order| order.id()),
+        );
+        expired.sort_unstable();
+
+        Vec::from_iter(expired.into_iter().map(|order_id| {
+            self.matching_engine.remove_order(order_id);
+            self.account
+                .expire_order(order_id, &mut self.account_tracker)
+                .expect("The order is open; qed")
+        }))
+    }
+
+    /// Derive the best bid and ask of the `MarketState` from the order book, if it has both sides.
+    fn sync_bba_with_book(&mut self) {
+        if let (Some((bid, _)), Some((ask, _))) = (
+            self.matching_engine.best_bid(),
+            self.matching_engine.best_ask(),
+        ) {
+            let offset = self.price_impact.offset();
+            self.market_state.set_bba(bid + offset, ask + offset);
         }
     }
 
@@ -200,289 +836,1176 @@ where
     /// `order`: The order that is being submitted.
     ///
     /// # Returns:
-    /// If Ok, the order with timestamp and id filled in.
+    /// If Ok, the order with timestamp and id filled in and the fills it got immediately.
+    /// With an order entry latency, the order is only sent and takes effect once it arrives,
+    /// so an Ok just means it passed the order filters and its fills are returned by `update_state`,
+    /// just like the order itself, tagged by `Order::rejection`, should it be rejected once it arrives.
     /// Else its an error.
-    pub fn submit_order(&mut self, mut order: Order<S>) -> Result<Order<S>> {
+    pub fn submit_order(&mut self, order: Order<S>) -> Result<(Order<S>, Vec<Fill<S>>)> {
         trace!("submit_order: {:?}", order);
+        self.validate_new_order(&order)?;
+        // An invalid request is rejected before it uses up any of the budget.
+        self.acquire_rate_limit(&[(OrderAction::Submit, 1)])?;
 
-        // Basic checks
-        self.config
-            .contract_specification()
-            .quantity_filter
-            .validate_order(&order)?;
-        self.config
-            .contract_specification()
-            .price_filter
-            .validate_order(&order, self.market_state.mid_price())?;
+        self.send_order(order)
+    }
+
+    /// Submit a new order, which passed the order filters and whose request already used up its rate limit budget.
+    fn send_order(&mut self, mut order: Order<S>) -> Result<(Order<S>, Vec<Fill<S>>)> {
+        self.fills.clear();
 
         order.set_timestamp(self.market_state.current_timestamp_ns());
         order.set_id(self.next_order_id());
 
+        if !self.latency.is_immediate() {
+            self.send_action(
+                OrderAction::Submit,
+                PendingAction::Submit(Box::new(order.clone())),
+            );
+            return Ok((order, Vec::new()));
+        }
+        let order = self.process_order_submission(order)?;
+
+        Ok((order, std::mem::take(&mut self.fills)))
+    }
+
+    /// Process a new order, which passed the order filters and has its id assigned.
+    fn process_order_submission(&mut self, mut order: Order<S>) -> Result<Order<S>> {
         match order.order_type() {
             OrderType::Market => {
-                let fill_price = match order.side() {
-                    Side::Buy => self.market_state.ask(),
-                    Side::Sell => self.market_state.bid(),
-                };
-                self.risk_engine
-                    .check_market_order(&self.account, &order, fill_price)?;
-                let quantity = match order.side() {
-                    Side::Buy => order.quantity(),
-                    Side::Sell => order.quantity().into_negative(),
-                };
-                // From here on, everything is infallible
-                self.clearing_house.settle_filled_order(
-                    &mut self.account,
-                    &mut self.account_tracker,
-                    quantity,
-                    fill_price,
-                    self.config.contract_specification().fee_taker,
-                    self.market_state.current_timestamp_ns(),
-                );
-                order.mark_filled(fill_price);
-                self.account_tracker.log_market_order_fill();
+                if order.time_in_force() == TimeInForce::PostOnly {
+                    return Err(Error::OrderError(OrderError::PostOnlyWouldTake));
+                }
+                self.check_reduce_only(&order)?;
+                self.execute_market_order(&mut order)?
             }
             OrderType::Limit => {
-                let l_price = order.limit_price().expect(EXPECT_LIMIT_PRICE);
-                match order.side() {
-                    Side::Buy => {
-                        if l_price >= self.market_state.ask() {
-                            return Err(Error::OrderError(OrderError::LimitPriceAboveAsk));
-                        }
-                    }
-                    Side::Sell => {
-                        if l_price <= self.market_state.bid() {
-                            return Err(Error::OrderError(OrderError::LimitPriceBelowBid));
+                if order.time_in_force() == TimeInForce::GoodTillCancelled
+                    && !self.config.execute_marketable_limit_orders()
+                    && self.is_marketable(&order)
+                {
+                    match order.side() {
+                        Side::Buy => return Err(Error::OrderError(OrderError::LimitPriceAboveAsk)),
+                        Side::Sell => {
+                            return Err(Error::OrderError(OrderError::LimitPriceBelowBid))
                         }
                     }
                 }
-                self.risk_engine.check_limit_order(&self.account, &order)?;
-                self.account.append_limit_order(order.clone());
+                self.check_reduce_only(&order)?;
+                self.execute_limit_order(&mut order)?;
                 self.account_tracker.log_limit_order_submission();
             }
+            OrderType::StopMarket
+            | OrderType::StopLimit
+            | OrderType::TakeProfitMarket
+            | OrderType::TakeProfitLimit
+            | OrderType::TrailingStopMarket => {
+                self.validate_trigger_price(&mut order)?;
+                self.check_max_num_open_orders(1)?;
+                self.account.append_untriggered_order(order.clone());
+            }
         }
 
         Ok(order)
     }
 
-    #[inline(always)]
-    fn next_order_id(&mut self) -> u64 {
-        self.next_order_id += 1;
-        self.next_order_id - 1
+    /// Submit a one-cancels-other group of orders.
+    /// Once one of them gets (partially) filled or triggered, the others are cancelled.
+    /// The orders must be able to rest, so market, immediate or cancel, fill or kill
+    /// and marketable limit orders are rejected.
+    /// As only one of them can be executed, the group only requires the order margin of its largest order.
+    ///
+    /// # Arguments:
+    /// `orders`: At least two orders, which are linked.
+    ///
+    /// # Returns:
+    /// If Ok, the orders with timestamp and id filled in.
+    /// With an order entry latency, the group is only placed once it arrives,
+    /// and `update_state` returns its orders, tagged by `Order::rejection`, should it be rejected then.
+    /// Else its an error and none of the orders got submitted.
+    pub fn submit_oco(&mut self, mut orders: Vec<Order<S>>) -> Result<Vec<Order<S>>> {
+        trace!("submit_oco: {:?}", orders);
+
+        if orders.len() < 2 {
+            return Err(Error::OrderError(OrderError::InvalidOrderGroup));
+        }
+        for order in orders.iter() {
+            self.validate_new_order(order)?;
+        }
+        // An invalid request is rejected before it uses up any of the budget.
+        self.acquire_rate_limit(&[(OrderAction::Submit, orders.len() as u32)])?;
+        for order in orders.iter_mut() {
+            order.set_timestamp(self.market_state.current_timestamp_ns());
+            order.set_id(self.next_order_id());
+        }
+        if !self.latency.is_immediate() {
+            self.send_action(
+                OrderAction::Submit,
+                PendingAction::SubmitOco(orders.clone()),
+            );
+            return Ok(orders);
+        }
+        self.place_order_group(&mut orders)?;
+
+        Ok(orders)
     }
 
-    /// Cancel an active order based on the user_order_id of an Order
+    /// Submit a bracket, consisting of an entry order and its attached take profit and stop loss orders.
+    /// The children become active as a one-cancels-other group with the first fill of the entry order,
+    /// covering the filled quantity, and grow along with its further fills up to their own quantity.
+    /// They are marked as reduce only.
+    /// If the entry order is cancelled before any fill, the children are cancelled as well,
+    /// while the children covering a partial fill are kept.
+    /// Should the children not pass the checks once they become active, they are rejected,
+    /// tagged by `Order::rejection`.
     ///
     /// # Arguments:
-    /// `user_order_id`: The `user_order_id` of the order to cancel.
+    /// `entry`: The order opening the position, which can't be immediate or cancel.
+    /// `take_profit`: A `Limit`, `TakeProfitMarket` or `TakeProfitLimit` order on the opposite side.
+    /// `stop_loss`: A `StopMarket`, `StopLimit` or `TrailingStopMarket` order on the opposite side.
     ///
     /// # Returns:
-    /// the cancelled order if successfull, error when the `user_order_id` is
-    /// not found
-    pub fn cancel_order_by_user_id(&mut self, user_order_id: u64) -> Result<Order<S>> {
+    /// If Ok, the entry, take profit and stop loss orders with timestamp and id filled in
+    /// and the fills the entry got immediately.
+    /// Else its an error.
+    pub fn submit_bracket(
+        &mut self,
+        entry: Order<S>,
+        mut take_profit: Order<S>,
+        mut stop_loss: Order<S>,
+    ) -> Result<Executions<S>> {
+        trace!(
+            "submit_bracket: entry: {:?}, take_profit: {:?}, stop_loss: {:?}",
+            entry,
+            take_profit,
+            stop_loss
+        );
+        if entry.time_in_force() == TimeInForce::ImmediateOrCancel
+            || take_profit.side() == entry.side()
+            || stop_loss.side() == entry.side()
+            || !matches!(
+                take_profit.order_type(),
+                OrderType::Limit | OrderType::TakeProfitMarket | OrderType::TakeProfitLimit
+            )
+            || !matches!(
+                stop_loss.order_type(),
+                OrderType::StopMarket | OrderType::StopLimit | OrderType::TrailingStopMarket
+            )
+        {
+            return Err(Error::OrderError(OrderError::InvalidOrderGroup));
+        }
+        for order in [&entry, &take_profit, &stop_loss] {
+            self.validate_new_order(order)?;
+        }
+        // A market entry order never rests, but its children do.
+        let num_resting = if entry.order_type() == OrderType::Market {
+            2
+        } else {
+            3
+        };
+        self.check_max_num_open_orders(num_resting)?;
+        // An invalid request is rejected before it uses up any of the budget.
+        self.acquire_rate_limit(&[(OrderAction::Submit, 3)])?;
+
+        let (entry, fills) = self.send_order(entry)?;
+        for order in [&mut take_profit, &mut stop_loss] {
+            order.set_reduce_only(true);
+            order.set_timestamp(self.market_state.current_timestamp_ns());
+            order.set_id(self.next_order_id());
+        }
         self.account
-            .cancel_order_by_user_id(user_order_id, &mut self.account_tracker)
+            .append_pending_child_orders(entry.id(), vec![take_profit.clone(), stop_loss.clone()]);
+        let mut child_orders = vec![take_profit, stop_loss];
+        if !entry.filled_quantity().is_zero() {
+            let rejected = self.update_child_orders(entry.id(), entry.filled_quantity());
+            if !rejected.is_empty() {
+                child_orders = rejected;
+            }
+        }
+        let mut orders = vec![entry];
+        orders.append(&mut child_orders);
+
+        Ok((orders, fills))
     }
 
-    /// Cancel an active order.
+    /// Atomically submit new orders and cancel open ones, e.g. to requote a ladder.
+    /// The order margin is checked once for the whole batch, after the cancelled orders have been released,
+    /// so either all of it is applied or none of it.
+    /// The new orders must be able to rest, so market, immediate or cancel, fill or kill
+    /// and marketable limit orders are rejected.
     ///
     /// # Arguments:
-    /// `order_id`: The `id` (assigned by the exchange) of the order to cancel.
+    /// `orders`: The new orders.
+    /// `cancellations`: The ids (assigned by the exchange) of the open orders to cancel.
     ///
     /// # Returns:
-    /// An order if successful with the given order_id.
-    pub fn cancel_order(&mut self, order_id: u64) -> Result<Order<S>> {
-        self.account
-            .cancel_order(order_id, &mut self.account_tracker)
+    /// If Ok, the `BatchOrders::Applied` new orders with timestamp and id filled in and the cancelled orders.
+    /// With an order entry latency, the batch is only applied once it arrives,
+    /// so an Ok is `BatchOrders::Pending` and just means the new orders passed the order filters.
+    /// Else its an error and nothing changed.
+    pub fn submit_batch(
+        &mut self,
+        mut orders: Vec<Order<S>>,
+        cancellations: Vec<u64>,
+    ) -> Result<BatchOrders<S>> {
+        trace!(
+            "submit_batch: {:?}, cancellations: {:?}",
+            orders,
+            cancellations
+        );
+        for order in orders.iter() {
+            self.validate_new_order(order)?;
+        }
+        self.acquire_rate_limit(&[
+            (OrderAction::Submit, orders.len() as u32),
+            (OrderAction::Cancel, cancellations.len() as u32),
+        ])?;
+        if !self.latency.is_immediate() {
+            let to_be_cancelled = cancellations
+                .iter()
+                .map(|order_id| {
+                    self.account
+                        .open_order(*order_id)
+                        .or_else(|| self.pending_actions.submitted_order(*order_id))
+                        .cloned()
+                        .ok_or(Error::OrderIdNotFound)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for order in orders.iter_mut() {
+                order.set_timestamp(self.market_state.current_timestamp_ns());
+                order.set_id(self.next_order_id());
+            }
+            self.send_action(
+                OrderAction::Submit,
+                PendingAction::SubmitBatch {
+                    orders: orders.clone(),
+                    cancellations,
+                },
+            );
+            return Ok(BatchOrders::Pending {
+                orders,
+                to_be_cancelled,
+            });
+        }
+        for order in orders.iter_mut() {
+            order.set_timestamp(self.market_state.current_timestamp_ns());
+            order.set_id(self.next_order_id());
+        }
+        let cancelled = self.process_batch(&mut orders, &cancellations)?;
+
+        Ok(BatchOrders::Applied { orders, cancelled })
     }
-}
 
-#[cfg(test)]
-mod test {
-    use fpdec::Decimal;
+    /// Apply a batch of new orders, which already have their ids assigned, and cancellations.
+    ///
+    /// # Returns:
+    /// If Ok, the cancelled orders.
+    fn process_batch(
+        &mut self,
+        orders: &mut [Order<S>],
+        cancellations: &[u64],
+    ) -> Result<Vec<Order<S>>> {
+        if cancellations
+            .iter()
+            .any(|order_id| self.account.open_order(*order_id).is_none())
+        {
+            return Err(Error::OrderIdNotFound);
+        }
+        for order in orders.iter_mut() {
+            match order.order_type() {
+                OrderType::Market => return Err(Error::OrderError(OrderError::InvalidBatch)),
+                OrderType::Limit => {
+                    if matches!(
+                        order.time_in_force(),
+                        TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill
+                    ) {
+                        return Err(Error::OrderError(OrderError::InvalidBatch));
+                    }
+                    if self.is_marketable(order) {
+                        return Err(Error::OrderError(
+                            match (order.time_in_force(), order.side()) {
+                                (TimeInForce::PostOnly, _) => OrderError::PostOnlyWouldTake,
+                                (_, Side::Buy) => OrderError::LimitPriceAboveAsk,
+                                (_, Side::Sell) => OrderError::LimitPriceBelowBid,
+                            },
+                        ));
+                    }
+                    self.check_reduce_only(order)?;
+                }
+                _ => self.validate_trigger_price(order)?,
+            }
+        }
 
-    use crate::{mock_exchange_base, prelude::*};
+        // The combined risk check is done against a copy of the account, so nothing changes if it fails.
+        let mut account = self.account.clone();
+        let num_active_cancelled = cancellations
+            .iter()
+            .filter(|order_id| account.active_limit_orders.contains_key(*order_id))
+            .count();
+        let cancelled = account.cancel_orders(cancellations, &mut NoAccountTracker);
+        if account.num_open_orders() + orders.len() > self.config.max_num_open_orders() {
+            return Err(Error::OrderError(OrderError::MaxActiveOrders));
+        }
+        for order in orders.iter() {
+            if order.order_type() == OrderType::Limit {
+                account.append_limit_order(order.clone());
+            } else {
+                account.append_untriggered_order(order.clone());
+            }
+        }
+        self.risk_engine.check_order_margin(&account)?;
 
-    #[test]
-    fn check_limit_order_execution_buy_trade() {
-        let exchange = mock_exchange_base();
+        // From here on, everything is infallible
+        self.account = account;
+        for order in cancelled.iter() {
+            self.matching_engine.remove_order(order.id());
+        }
+        for _ in 0..num_active_cancelled {
+            self.account_tracker.log_limit_order_cancellation();
+        }
+        for order in orders
+            .iter()
+            .filter(|order| order.order_type() == OrderType::Limit)
+        {
+            self.matching_engine.insert_order(order);
+            self.account_tracker.log_limit_order_submission();
+        }
+
+        Ok(cancelled)
+    }
+
+    /// Make sure the order conforms to the `QuantityFilter` and `PriceFilter`,
+    /// and has not expired yet.
+    fn validate_new_order(&self, order: &Order<S>) -> Result<()> {
+        if order
+            .expiry_timestamp_ns()
+            .is_some_and(|ts| ts <= self.market_state.current_timestamp_ns())
+        {
+            return Err(Error::OrderError(OrderError::InvalidExpiry));
+        }
+        self.config
+            .contract_specification()
+            .quantity_filter
+            .validate_order(order)?;
+        self.config
+            .contract_specification()
+            .price_filter
+            .validate_order(order, self.market_state.mid_price())?;
+
+        Ok(())
+    }
+
+    /// Make sure a conditional order is not triggered right away.
+    /// The trigger price of trailing stop orders is initialized here.
+    fn validate_trigger_price(&self, order: &mut Order<S>) -> Result<()> {
+        let source = order.trigger_price_source().expect(EXPECT_TRIGGER_PRICE);
+        let price = self.trigger_reference_price(source);
+        order.update_trailing_stop(price);
+        let trigger_price = order.trigger_price().expect(EXPECT_TRIGGER_PRICE);
+        if triggers_on_rise(order) {
+            if trigger_price <= price {
+                return Err(Error::OrderError(OrderError::InvalidTriggerPrice));
+            }
+        } else if trigger_price >= price {
+            return Err(Error::OrderError(OrderError::InvalidTriggerPrice));
+        }
+
+        Ok(())
+    }
+
+    /// Place a one-cancels-other group of orders, which already have their ids assigned.
+    /// Either all of them are placed or none.
+    fn place_order_group(&mut self, orders: &mut [Order<S>]) -> Result<()> {
+        for order in orders.iter_mut() {
+            match order.order_type() {
+                OrderType::Market => return Err(Error::OrderError(OrderError::InvalidOrderGroup)),
+                OrderType::Limit => {
+                    if matches!(
+                        order.time_in_force(),
+                        TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill
+                    ) {
+                        return Err(Error::OrderError(OrderError::InvalidOrderGroup));
+                    }
+                    // Linked orders only ever rest in the book.
+                    if self.is_marketable(order) {
+                        return Err(Error::OrderError(
+                            match (order.time_in_force(), order.side()) {
+                                (TimeInForce::PostOnly, _) => OrderError::PostOnlyWouldTake,
+                                (_, Side::Buy) => OrderError::LimitPriceAboveAsk,
+                                (_, Side::Sell) => OrderError::LimitPriceBelowBid,
+                            },
+                        ));
+                    }
+                    self.check_reduce_only(order)?;
+                }
+                _ => self.validate_trigger_price(order)?,
+            }
+        }
+
+        self.check_max_num_open_orders(orders.len())?;
+
+        // The risk checks are done against a copy of the account, so nothing changes if one fails.
+        let mut account = self.account.clone();
+        account.link_orders(Vec::from_iter(orders.iter().map(|order| order.id())));
+        for order in orders.iter() {
+            if order.order_type() == OrderType::Limit {
+                self.risk_engine.check_limit_order(&account, order)?;
+                account.append_limit_order(order.clone());
+            } else {
+                account.append_untriggered_order(order.clone());
+            }
+        }
+        self.account = account;
+        for order in orders
+            .iter()
+            .filter(|order| order.order_type() == OrderType::Limit)
+        {
+            self.matching_engine.insert_order(order);
+            self.account_tracker.log_limit_order_submission();
+        }
+
+        Ok(())
+    }
 
-        let market_update = MarketUpdate::Trade {
-            price: quote!(100.0),
-            quantity: base!(1.0),
-            side: Side::Buy,
+    /// Let the child orders of a bracket cover the filled quantity of its entry order.
+    /// With the first fill, they are placed as one-cancels-other group,
+    /// and with every further fill, those still open grow up to their own quantity.
+    ///
+    /// # Arguments:
+    /// `entry_order_id`: The id of the entry order, which may not be a bracket entry at all.
+    /// `filled_quantity`: The total filled quantity of the entry order.
+    ///
+    /// # Returns:
+    /// The child orders, which got rejected upon activation, tagged by `Order::rejection`.
+    fn update_child_orders(&mut self, entry_order_id: u64, filled_quantity: S) -> Vec<Order<S>> {
+        let mut rejected = Vec::new();
+        let mut child_orders = self.account.take_pending_child_orders(entry_order_id);
+        if !child_orders.is_empty() {
+            debug!(
+                "activating child orders of entry order {}: {:?}",
+                entry_order_id, child_orders
+            );
+            let child_quantities = Vec::from_iter(
+                child_orders
+                    .iter()
+                    .map(|order| (order.id(), order.quantity())),
+            );
+            for order in child_orders.iter_mut() {
+                order.set_quantity(min(order.quantity(), filled_quantity));
+            }
+            match self.place_order_group(&mut child_orders) {
+                Ok(()) => {
+                    self.account
+                        .bracket_child_orders
+                        .insert(entry_order_id, child_quantities);
+                }
+                Err(e) => {
+                    debug!(
+                        "child orders of entry order {} got rejected: {}",
+                        entry_order_id, e
+                    );
+                    for mut order in child_orders {
+                        order.mark_rejected(e.clone());
+                        rejected.push(order);
+                    }
+                }
+            }
+        } else if let Some(child_quantities) = self
+            .account
+            .bracket_child_orders
+            .get(&entry_order_id)
+            .cloned()
+        {
+            for (order_id, quantity) in child_quantities {
+                if let Some(order) = self
+                    .account
+                    .resize_child_order(order_id, min(quantity, filled_quantity))
+                {
+                    // Like an amendment to a larger quantity, the order loses its queue priority.
+                    self.matching_engine.remove_order(order_id);
+                    self.matching_engine.insert_order(&order);
+                }
+            }
+        }
+        // Once the entry is no longer open, its children don't grow anymore.
+        if self.account.open_order(entry_order_id).is_none() {
+            self.account.bracket_child_orders.remove(&entry_order_id);
+        }
+
+        rejected
+    }
+
+    /// Cancel the one-cancels-other siblings of an order, which got executed.
+    fn cancel_linked_orders(&mut self, order_id: u64) {
+        for sibling_id in self.account.unlink_order_group(order_id) {
+            debug!("cancelling order {} linked to {}", sibling_id, order_id);
+            self.account
+                .cancel_order(sibling_id, &mut self.account_tracker)
+                .expect("Linked orders are open; qed");
+            self.matching_engine.remove_order(sibling_id);
+        }
+    }
+
+    /// Execute a market order against the book, or the best bid and ask if there is no depth information.
+    fn execute_market_order(&mut self, order: &mut Order<S>) -> Result<()> {
+        let fill_price = match self
+            .matching_engine
+            .market_order_fill_price(order.side(), order.quantity())
+        {
+            Some(price) => price + self.price_impact.offset(),
+            None => match order.side() {
+                Side::Buy => self.market_state.ask(),
+                Side::Sell => self.market_state.bid(),
+            },
         };
-        // Buys
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(90), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(99), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(100), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(101), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
+        self.execute_market_fill(order, fill_price)
+    }
 
-        // Sells
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(110), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(101), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(100), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            true
+    /// Execute the whole `order` as taker at the `fill_price`, shifted by its market impact.
+    /// The impact then persists in the best bid and ask until it wears off.
+    fn execute_market_fill(
+        &mut self,
+        order: &mut Order<S>,
+        fill_price: QuoteCurrency,
+    ) -> Result<()> {
+        let quantity = order.quantity();
+        let impact = self.price_impact.fill_impact(
+            order.side(),
+            quantity,
+            fill_price,
+            self.config.contract_specification().price_filter.tick_size,
         );
+        self.execute_taker_fill(order, quantity, fill_price + impact)?;
+        self.account_tracker.log_market_order_fill();
+        if !impact.is_zero() {
+            self.price_impact
+                .add(impact, self.market_state.current_timestamp_ns());
+            self.shift_bba(impact);
+        }
+
+        Ok(())
+    }
+
+    /// Execute a limit order according to its `TimeInForce`.
+    /// Immediate or cancel and fill or kill orders are executed as taker and never rest in the book,
+    /// while all others are placed into the book.
+    /// If configured, the marketable part of a good till cancelled order is executed as taker first.
+    fn execute_limit_order(&mut self, order: &mut Order<S>) -> Result<()> {
+        match order.time_in_force() {
+            TimeInForce::GoodTillCancelled => {
+                if !self.config.execute_marketable_limit_orders() {
+                    return self.place_limit_order(order);
+                }
+                let (quantity, fill_price) = self.marketable_quantity(order);
+                if quantity.is_zero() {
+                    return self.place_limit_order(order);
+                }
+                self.execute_taker_fill(order, quantity, fill_price)?;
+                if order.remaining_quantity().is_zero() {
+                    return Ok(());
+                }
+                // The taker fill is already settled, so a remainder the account can't afford is dropped.
+                if let Err(e) = self.place_limit_order(order) {
+                    warn!(
+                        "remainder of marketable limit order {} got rejected: {}",
+                        order.id(),
+                        e
+                    );
+                }
+                Ok(())
+            }
+            TimeInForce::PostOnly => {
+                if self.is_marketable(order) {
+                    return Err(Error::OrderError(OrderError::PostOnlyWouldTake));
+                }
+                self.place_limit_order(order)
+            }
+            TimeInForce::ImmediateOrCancel => {
+                let (quantity, fill_price) = self.marketable_quantity(order);
+                if quantity.is_zero() {
+                    return Err(Error::OrderError(
+                        OrderError::ImmediateOrCancelNotExecutable,
+                    ));
+                }
+                self.execute_taker_fill(order, quantity, fill_price)
+            }
+            TimeInForce::FillOrKill => {
+                let (quantity, fill_price) = self.marketable_quantity(order);
+                if quantity < order.remaining_quantity() {
+                    return Err(Error::OrderError(OrderError::FillOrKillNotExecutable));
+                }
+                self.execute_taker_fill(order, quantity, fill_price)
+            }
+        }
     }
 
-    #[test]
-    fn check_limit_order_execution_sell_trade() {
-        let exchange = mock_exchange_base();
+    /// Whether a limit order would be executed immediately against the best bid or ask.
+    fn is_marketable(&self, order: &Order<S>) -> bool {
+        let l_price = order.limit_price().expect(EXPECT_LIMIT_PRICE);
+        match order.side() {
+            Side::Buy => l_price >= self.market_state.ask(),
+            Side::Sell => l_price <= self.market_state.bid(),
+        }
+    }
 
-        let market_update = MarketUpdate::Trade {
-            price: quote!(100.0),
-            quantity: base!(1.0),
-            side: Side::Sell,
+    /// The quantity of a limit order which is immediately executable and its average fill price.
+    /// Without depth information, the best bid and ask are assumed to have unlimited liquidity.
+    fn marketable_quantity(&self, order: &Order<S>) -> (S, QuoteCurrency) {
+        let l_price = order.limit_price().expect(EXPECT_LIMIT_PRICE);
+        if let Some(fill) =
+            self.matching_engine
+                .limit_order_fill(order.side(), order.remaining_quantity(), l_price)
+        {
+            return fill;
+        }
+        let touch = match order.side() {
+            Side::Buy => self.market_state.ask(),
+            Side::Sell => self.market_state.bid(),
         };
-        // Buys
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(90), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(99), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(100), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            true
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(101), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            true
-        );
+        if self.is_marketable(order) {
+            (order.remaining_quantity(), touch)
+        } else {
+            (S::new_zero(), l_price)
+        }
+    }
 
-        // Sells
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(110), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
+    /// Execute `quantity` of the `order` as taker at the `fill_price`.
+    fn execute_taker_fill(
+        &mut self,
+        order: &mut Order<S>,
+        quantity: S,
+        fill_price: QuoteCurrency,
+    ) -> Result<()> {
+        // The fill has to pass the same risk checks as a market order of the same size.
+        let taker_order = Order::market(order.side(), quantity)?;
+        self.risk_engine
+            .check_market_order(&self.account, &taker_order, fill_price)?;
+        // From here on, everything is infallible
+        let mut fill = Fill::new(
+            order,
+            quantity,
+            fill_price,
+            false,
+            self.market_state.current_timestamp_ns(),
         );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(101), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
+        self.clearing_house.settle_filled_order(
+            &mut self.account,
+            &mut self.account_tracker,
+            &mut fill,
+            self.config.contract_specification().fee_taker,
         );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(100), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
+        self.fills.push(fill);
+        self.matching_engine
+            .consume_liquidity(order.side(), quantity);
+        self.sync_bba_with_book();
+        order.fill(quantity, fill_price);
+
+        Ok(())
+    }
+
+    /// The quantity an order of the given `side` can fill without increasing the position.
+    fn reducible_quantity(&self, side: Side) -> S {
+        let position_size = self.account.position.size();
+        match side {
+            Side::Buy if position_size < S::new_zero() => position_size.abs(),
+            Side::Sell if position_size > S::new_zero() => position_size,
+            _ => S::new_zero(),
+        }
+    }
+
+    /// Make sure a reduce only order does not increase the position.
+    fn check_reduce_only(&self, order: &Order<S>) -> Result<()> {
+        if order.reduce_only() && order.remaining_quantity() > self.reducible_quantity(order.side())
+        {
+            return Err(Error::OrderError(
+                OrderError::ReduceOnlyWouldIncreasePosition,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Cancel the resting reduce only orders, which can no longer reduce the position.
+    fn cancel_obsolete_reduce_only_orders(&mut self) {
+        let mut obsolete = Vec::from_iter(
+            self.account
+                .active_limit_orders
+                .values()
+                .filter(|order| {
+                    order.reduce_only() && self.reducible_quantity(order.side()).is_zero()
+                })
+                .map(|order| order.id()),
         );
+        obsolete.sort_unstable();
+        for order_id in obsolete {
+            debug!("cancelling obsolete reduce only order {}", order_id);
+            self.account
+                .cancel_order(order_id, &mut self.account_tracker)
+                .expect("The order is active; qed");
+            self.matching_engine.remove_order(order_id);
+        }
     }
 
-    #[test]
-    fn check_limit_order_execution_candle() {
-        let exchange = mock_exchange_base();
+    /// Place a limit order into the book, if the account can provide the order margin.
+    fn place_limit_order(&mut self, order: &Order<S>) -> Result<()> {
+        self.check_max_num_open_orders(1)?;
+        self.risk_engine.check_limit_order(&self.account, order)?;
+        self.matching_engine.insert_order(order);
+        self.account.append_limit_order(order.clone());
 
-        let market_update = MarketUpdate::Candle {
-            bid: quote!(100),
-            ask: quote!(101),
-            low: quote!(98),
-            high: quote!(102),
+        Ok(())
+    }
+
+    /// The current price which is compared against the trigger price of conditional orders.
+    fn trigger_reference_price(&self, source: TriggerPriceSource) -> QuoteCurrency {
+        match source {
+            TriggerPriceSource::LastTradePrice => {
+                if self.market_state.last_trade_price().is_zero() {
+                    // No trade has been observed yet.
+                    self.market_state.mid_price()
+                } else {
+                    self.market_state.last_trade_price()
+                }
+            }
+            TriggerPriceSource::MidPrice => self.market_state.mid_price(),
+            TriggerPriceSource::MarkPrice => match self.config.contract_specification().mark_method
+            {
+                MarkMethod::MidPrice => self.market_state.mid_price(),
+                // Fair price marking requires an index price, which is not available.
+                MarkMethod::FairPrice => self.market_state.mid_price(),
+            },
+        }
+    }
+
+    /// Check if a conditional order is triggered by the `market_update`.
+    fn is_triggered(&self, order: &Order<S>, market_update: &MarketUpdate<S>) -> bool {
+        let trigger_price = order.trigger_price().expect(EXPECT_TRIGGER_PRICE);
+        let source = order.trigger_price_source().expect(EXPECT_TRIGGER_PRICE);
+        let (low, high) = match (source, market_update) {
+            (TriggerPriceSource::LastTradePrice, MarketUpdate::Trade { price, .. }) => {
+                (*price, *price)
+            }
+            // Trades have occured over the whole range of the candle.
+            (TriggerPriceSource::LastTradePrice, MarketUpdate::Candle { low, high, .. }) => {
+                (*low, *high)
+            }
+            // Without trades, the last trade price does not change.
+            (TriggerPriceSource::LastTradePrice, _) => return false,
+            (source, _) => {
+                let price = self.trigger_reference_price(source);
+                (price, price)
+            }
         };
-        // Buys
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(90), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(98), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
-        );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Buy, quote!(99), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            true
-        );
+        if triggers_on_rise(order) {
+            high >= trigger_price
+        } else {
+            low <= trigger_price
+        }
+    }
 
-        // Sells
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(110), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
+    /// Let the trigger price of trailing stop orders follow the best price of the `market_update`.
+    fn update_trailing_stops(&mut self, market_update: &MarketUpdate<S>) {
+        let mid_price = self.market_state.mid_price();
+        let mark_price = self.trigger_reference_price(TriggerPriceSource::MarkPrice);
+        for order in self
+            .account
+            .untriggered_orders
+            .values_mut()
+            .filter(|order| order.trailing_offset().is_some())
+        {
+            let source = order.trigger_price_source().expect(EXPECT_TRIGGER_PRICE);
+            let price = match (source, market_update) {
+                (TriggerPriceSource::LastTradePrice, MarketUpdate::Trade { price, .. }) => *price,
+                // Assume the favourable extreme of the candle is reached first.
+                (TriggerPriceSource::LastTradePrice, MarketUpdate::Candle { low, high, .. }) => {
+                    match order.side() {
+                        Side::Buy => *low,
+                        Side::Sell => *high,
+                    }
+                }
+                (TriggerPriceSource::LastTradePrice, _) => continue,
+                (TriggerPriceSource::MidPrice, _) => mid_price,
+                (TriggerPriceSource::MarkPrice, _) => mark_price,
+            };
+            order.update_trailing_stop(price);
+        }
+    }
+
+    /// Check if any conditional orders have been triggered and execute or place them.
+    ///
+    /// # Returns:
+    /// The executed orders and those rejected once triggered, tagged by `Order::rejection`.
+    fn check_untriggered_orders(&mut self, market_update: &MarketUpdate<S>) -> Vec<Order<S>> {
+        let mut triggered = Vec::from_iter(
+            self.account
+                .untriggered_orders
+                .values()
+                .filter(|order| self.is_triggered(order, market_update))
+                .map(|order| order.id()),
         );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(102), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            false
+        triggered.sort_unstable();
+
+        let mut executed = Vec::new();
+        for order_id in triggered {
+            // The order may have been cancelled by a linked order in the meantime.
+            if !self.account.untriggered_orders.contains_key(&order_id) {
+                continue;
+            }
+            let mut order = self.account.remove_triggered_order(order_id);
+            self.cancel_linked_orders(order_id);
+            debug!("triggered order: {:?}", order);
+            // Once triggered, the order has to pass the same checks as any new order.
+            let res = self
+                .check_reduce_only(&order)
+                .and_then(|_| match order.order_type() {
+                    OrderType::StopMarket
+                    | OrderType::TakeProfitMarket
+                    | OrderType::TrailingStopMarket => {
+                        match candle_fill_price(&order, market_update) {
+                            Some(fill_price) => self.execute_market_fill(&mut order, fill_price),
+                            None => self.execute_market_order(&mut order),
+                        }
+                    }
+                    OrderType::StopLimit | OrderType::TakeProfitLimit => {
+                        self.execute_limit_order(&mut order)
+                    }
+                    _ => unreachable!("Only conditional orders can be triggered"),
+                });
+            match res {
+                Ok(()) => {
+                    if !order.filled_quantity().is_zero() {
+                        executed.push(order);
+                    }
+                }
+                Err(e) => {
+                    debug!("triggered order {} got rejected: {}", order_id, e);
+                    // The children of a bracket can't become active without their entry.
+                    self.account.take_pending_child_orders(order_id);
+                    order.mark_rejected(e);
+                    executed.push(order);
+                }
+            }
+        }
+
+        executed
+    }
+
+    #[inline(always)]
+    fn next_order_id(&mut self) -> u64 {
+        self.next_order_id += 1;
+        self.next_order_id - 1
+    }
+
+    /// Amend the limit price and quantity of an active limit order, keeping its id.
+    /// The amended order has to pass the `PriceFilter`, `QuantityFilter` and the risk checks again.
+    /// Like on most exchanges, it keeps its queue priority if only its quantity is reduced,
+    /// while a price change or quantity increase moves it to the back of the queue.
+    ///
+    /// # Arguments:
+    /// `order_id`: The id of the active limit order.
+    /// `new_price`: The new limit price.
+    /// `new_qty`: The new total quantity of the order, including what has already been filled.
+    ///
+    /// # Returns:
+    /// If Ok, the amended order, otherwise the order remains unchanged.
+    /// With an order entry latency, the amendment is only sent and checked once it arrives.
+    /// Should it be rejected then, `update_state` returns the amended order, tagged by `Order::rejection`.
+    pub fn amend_order(
+        &mut self,
+        order_id: u64,
+        new_price: QuoteCurrency,
+        new_qty: S,
+    ) -> Result<Order<S>> {
+        trace!(
+            "amend_order: order_id: {}, new_price: {}, new_qty: {}",
+            order_id,
+            new_price,
+            new_qty
         );
-        assert_eq!(
-            exchange.check_limit_order_execution(
-                &Order::limit(Side::Sell, quote!(101), base!(0.1)).unwrap(),
-                &market_update
-            ),
-            true
+        let order = if self.latency.is_immediate() {
+            self.account.active_limit_orders.get(&order_id)
+        } else {
+            // The order may still be on its way to the exchange.
+            self.account
+                .active_limit_orders
+                .get(&order_id)
+                .or_else(|| self.pending_actions.submitted_order(order_id))
+                .filter(|order| order.order_type() == OrderType::Limit)
+        }
+        .ok_or(Error::OrderIdNotFound)?;
+        let amended = self.validate_amend(order, new_price, new_qty)?;
+        // An invalid request is rejected before it uses up any of the budget.
+        self.acquire_rate_limit(&[(OrderAction::Amend, 1)])?;
+        if !self.latency.is_immediate() {
+            self.send_action(
+                OrderAction::Amend,
+                PendingAction::Amend(Box::new(amended.clone())),
+            );
+            return Ok(amended);
+        }
+        self.process_amend(order_id, new_price, new_qty)
+    }
+
+    /// Amend an active limit order, once the amendment arrives at the exchange.
+    fn process_amend(
+        &mut self,
+        order_id: u64,
+        new_price: QuoteCurrency,
+        new_qty: S,
+    ) -> Result<Order<S>> {
+        let order = self
+            .account
+            .active_limit_orders
+            .get(&order_id)
+            .ok_or(Error::OrderIdNotFound)?;
+        let amended = self.validate_amend(order, new_price, new_qty)?;
+        // An amend never executes immediately.
+        if self.is_marketable(&amended) {
+            match amended.side() {
+                Side::Buy => return Err(Error::OrderError(OrderError::LimitPriceAboveAsk)),
+                Side::Sell => return Err(Error::OrderError(OrderError::LimitPriceBelowBid)),
+            }
+        }
+        self.check_reduce_only(&amended)?;
+        self.risk_engine
+            .check_limit_order(&self.account, &amended)?;
+
+        let loses_priority = new_price != order.limit_price().expect(EXPECT_LIMIT_PRICE)
+            || new_qty > order.quantity();
+        if loses_priority {
+            self.matching_engine.remove_order(order_id);
+            self.matching_engine.insert_order(&amended);
+        }
+        self.account.amend_limit_order(amended.clone());
+        self.account_tracker.log_limit_order_amend();
+
+        Ok(amended)
+    }
+
+    /// Make sure the amended limit `order` has a valid price and quantity,
+    /// which conform to the `QuantityFilter` and `PriceFilter`.
+    ///
+    /// # Returns:
+    /// If Ok, the amended order.
+    fn validate_amend(
+        &self,
+        order: &Order<S>,
+        new_price: QuoteCurrency,
+        new_qty: S,
+    ) -> Result<Order<S>> {
+        if new_price <= QuoteCurrency::new_zero() {
+            return Err(Error::OrderError(OrderError::LimitPriceBelowZero));
+        }
+        if new_qty <= order.filled_quantity() {
+            return Err(Error::OrderError(OrderError::AmendedQuantityBelowFilled));
+        }
+        let mut amended = order.clone();
+        amended.amend(new_price, new_qty);
+
+        self.config
+            .contract_specification()
+            .quantity_filter
+            .validate_order(&amended)?;
+        self.config
+            .contract_specification()
+            .price_filter
+            .validate_order(&amended, self.market_state.mid_price())?;
+
+        Ok(amended)
+    }
+
+    /// Cancel an active order based on the user_order_id of an Order
+    ///
+    /// # Arguments:
+    /// `user_order_id`: The `user_order_id` of the order to cancel.
+    ///
+    /// # Returns:
+    /// the cancelled order if successfull, error when the `user_order_id` is
+    /// not found.
+    /// With an order entry latency, the order is only cancelled once the cancellation arrives,
+    /// so it may still get filled in the meantime.
+    pub fn cancel_order_by_user_id(&mut self, user_order_id: u64) -> Result<Order<S>> {
+        self.acquire_rate_limit(&[(OrderAction::Cancel, 1)])?;
+        if !self.latency.is_immediate() {
+            let order_id = match self
+                .account
+                .lookup_order_nonce_from_user_order_id
+                .get(&user_order_id)
+            {
+                Some(order_id) => *order_id,
+                None => self
+                    .pending_actions
+                    .submitted_order_by_user_id(user_order_id)
+                    .ok_or(Error::UserOrderIdNotFound)?
+                    .id(),
+            };
+            return self.send_cancel(order_id);
+        }
+        let order = self
+            .account
+            .cancel_order_by_user_id(user_order_id, &mut self.account_tracker)?;
+        self.matching_engine.remove_order(order.id());
+
+        Ok(order)
+    }
+
+    /// Cancel an active order.
+    ///
+    /// # Arguments:
+    /// `order_id`: The `id` (assigned by the exchange) of the order to cancel.
+    ///
+    /// # Returns:
+    /// An order if successful with the given order_id.
+    /// With an order entry latency, the order is only cancelled once the cancellation arrives,
+    /// so it may still get filled in the meantime.
+    pub fn cancel_order(&mut self, order_id: u64) -> Result<Order<S>> {
+        self.acquire_rate_limit(&[(OrderAction::Cancel, 1)])?;
+        if !self.latency.is_immediate() {
+            return self.send_cancel(order_id);
+        }
+        self.process_cancel(order_id)
+    }
+
+    /// Send the cancellation of an open or submitted order, which arrives after the order entry latency.
+    fn send_cancel(&mut self, order_id: u64) -> Result<Order<S>> {
+        let order = self
+            .account
+            .open_order(order_id)
+            .or_else(|| self.pending_actions.submitted_order(order_id))
+            .ok_or(Error::OrderIdNotFound)?
+            .clone();
+        self.send_action(OrderAction::Cancel, PendingAction::Cancel(order_id));
+
+        Ok(order)
+    }
+
+    /// Cancel an open order, once the cancellation arrives at the exchange.
+    fn process_cancel(&mut self, order_id: u64) -> Result<Order<S>> {
+        let order = self
+            .account
+            .cancel_order(order_id, &mut self.account_tracker)?;
+        self.matching_engine.remove_order(order_id);
+
+        Ok(order)
+    }
+
+    /// Cancel all open orders.
+    ///
+    /// # Returns:
+    /// If Ok, the cancelled orders, ordered by their id.
+    /// With an order entry latency, the orders are only cancelled once the cancellation arrives,
+    /// so some may still get filled in the meantime.
+    pub fn cancel_all_orders(&mut self) -> Result<Vec<Order<S>>> {
+        self.cancel_orders_matching(|_| true)
+    }
+
+    /// Cancel all open orders of the given `side`.
+    ///
+    /// # Returns:
+    /// If Ok, the cancelled orders, ordered by their id.
+    /// With an order entry latency, the orders are only cancelled once the cancellation arrives,
+    /// so some may still get filled in the meantime.
+    pub fn cancel_orders_by_side(&mut self, side: Side) -> Result<Vec<Order<S>>> {
+        self.cancel_orders_matching(|order| order.side() == side)
+    }
+
+    /// Cancel all open orders, for which the `predicate` returns true.
+    /// Open orders are the active limit orders, the untriggered conditional orders
+    /// and the children of bracket orders, whose entry order has not been filled yet.
+    /// The order margin is computed only once, after all orders are cancelled.
+    /// Every cancelled order uses up the cancel weight of the rate limits,
+    /// while a request cancelling no order at all still uses it up once.
+    ///
+    /// # Arguments:
+    /// `predicate`: Decides which orders get cancelled.
+    ///
+    /// # Returns:
+    /// If Ok, the cancelled orders, ordered by their id.
+    /// With an order entry latency, the orders are only cancelled once the cancellation arrives,
+    /// so some may still get filled in the meantime.
+    pub fn cancel_orders_matching<P>(&mut self, mut predicate: P) -> Result<Vec<Order<S>>>
+    where
+        P: FnMut(&Order<S>) -> bool,
+    {
+        let mut orders = Vec::from_iter(
+            self.account
+                .active_limit_orders
+                .values()
+                .chain(self.account.untriggered_orders.values())
+                .chain(self.account.pending_child_orders.values().flatten())
+                .filter(|order| predicate(order))
+                .cloned(),
         );
+        if !self.latency.is_immediate() {
+            orders.extend(
+                self.pending_actions
+                    .submitted_orders()
+                    .filter(|order| predicate(order))
+                    .cloned(),
+            );
+            self.acquire_rate_limit(&[(OrderAction::Cancel, max(orders.len(), 1) as u32)])?;
+            orders.sort_unstable_by_key(|order| order.id());
+            let order_ids = Vec::from_iter(orders.iter().map(|order| order.id()));
+            self.send_action(OrderAction::Cancel, PendingAction::CancelMany(order_ids));
+            return Ok(orders);
+        }
+        self.acquire_rate_limit(&[(OrderAction::Cancel, max(orders.len(), 1) as u32)])?;
+        orders.sort_unstable_by_key(|order| order.id());
+        let order_ids = Vec::from_iter(orders.iter().map(|order| order.id()));
+
+        Ok(self.process_cancel_many(&order_ids))
+    }
+
+    /// Cancel several open orders, once the cancellation arrives at the exchange.
+    fn process_cancel_many(&mut self, order_ids: &[u64]) -> Vec<Order<S>> {
+        let cancelled = self
+            .account
+            .cancel_orders(order_ids, &mut self.account_tracker);
+        for order in cancelled.iter() {
+            self.matching_engine.remove_order(order.id());
+        }
+
+        cancelled
+    }
+}
+
+/// The price at which a triggered market order is filled, if it was triggered by the range of a candle.
+/// The price passes through the trigger price, unless the candle already opened beyond it.
+fn candle_fill_price<S>(order: &Order<S>, market_update: &MarketUpdate<S>) -> Option<QuoteCurrency>
+where
+    S: Currency,
+{
+    let MarketUpdate::Candle { open, .. } = market_update else {
+        return None;
+    };
+    if order.trigger_price_source() != Some(TriggerPriceSource::LastTradePrice) {
+        return None;
+    }
+    let trigger_price = order.trigger_price().expect(EXPECT_TRIGGER_PRICE);
+    let gapped = if triggers_on_rise(order) {
+        *open >= trigger_price
+    } else {
+        *open <= trigger_price
+    };
+    if gapped {
+        Some(*open)
+    } else {
+        Some(trigger_price)
+    }
+}
+
+/// Whether a conditional order is triggered once the price rises to its trigger price,
+/// as opposed to falling to it.
+fn triggers_on_rise<S>(order: &Order<S>) -> bool
+where
+    S: Currency,
+{
+    match order.order_type() {
+        OrderType::StopMarket | OrderType::StopLimit | OrderType::TrailingStopMarket => {
+            order.side() == Side::Buy
+        }
+        OrderType::TakeProfitMarket | OrderType::TakeProfitLimit => order.side() == Side::Sell,
+        OrderType::Market | OrderType::Limit => {
+            unreachable!("Only conditional orders have a trigger price")
+        }
     }
 }
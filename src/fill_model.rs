@@ -0,0 +1,471 @@
+//! Models how resting limit orders are filled by candles,
+//! which only tell the range of traded prices, but not the traded quantities.
+
+use fpdec::Decimal;
+use hashbrown::HashMap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::types::{Currency, Error, MarketUpdate, QuoteCurrency, Result, Side};
+
+/// The assumed path of the price within a candle.
+/// It decides which orders are executed first, if limit and conditional orders are hit in the same candle.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum IntrabarPath {
+    /// The price first moves to the high and then to the low of the candle.
+    #[default]
+    OpenHighLowClose,
+    /// The price first moves to the low and then to the high of the candle.
+    OpenLowHighClose,
+}
+
+impl IntrabarPath {
+    /// Split a candle into the legs of the path, starting with the open.
+    /// The `low` and `high` of each leg are the single price it reaches,
+    /// while the other fields are those of the whole candle.
+    /// The close is never reached outside of the range of the previous legs, so it has no leg.
+    pub(crate) fn legs<S>(&self, candle: &MarketUpdate<S>) -> [MarketUpdate<S>; 3]
+    where
+        S: Currency,
+    {
+        let MarketUpdate::Candle {
+            bid,
+            ask,
+            open,
+            high,
+            low,
+            close,
+            volume,
+        } = candle
+        else {
+            panic!("Only candles have an intrabar path");
+        };
+        let leg = |price: QuoteCurrency| MarketUpdate::Candle {
+            bid: *bid,
+            ask: *ask,
+            open: *open,
+            high: price,
+            low: price,
+            close: *close,
+            volume: *volume,
+        };
+        match self {
+            IntrabarPath::OpenHighLowClose => [leg(*open), leg(*high), leg(*low)],
+            IntrabarPath::OpenLowHighClose => [leg(*open), leg(*low), leg(*high)],
+        }
+    }
+}
+
+/// The state of matching a `MarketUpdate::Candle` along the legs of its `IntrabarPath`.
+/// The legs only decide the order in which resting limit orders and conditional orders are executed,
+/// while the `FillModel` decides only once per order, whether the whole candle fills it.
+#[derive(Debug, Clone)]
+pub(crate) struct CandleFills<S> {
+    low: QuoteCurrency,
+    high: QuoteCurrency,
+    /// The quantity, which the resting limit orders can still get filled, if limited.
+    fill_capacity: Option<S>,
+    /// Whether the candle fills an order, decided when a leg first reaches its limit price.
+    decisions: HashMap<u64, bool>,
+}
+
+impl<S> CandleFills<S>
+where
+    S: Currency,
+{
+    /// Start matching the `candle`, of whose volume the resting limit orders can get filled `max_volume_fraction` at most.
+    pub(crate) fn new(candle: &MarketUpdate<S>, max_volume_fraction: Option<Decimal>) -> Self {
+        let MarketUpdate::Candle {
+            low, high, volume, ..
+        } = candle
+        else {
+            panic!("Only candles are matched along an intrabar path");
+        };
+        Self {
+            low: *low,
+            high: *high,
+            fill_capacity: max_volume_fraction.map(|fraction| *volume * fraction),
+            decisions: HashMap::new(),
+        }
+    }
+
+    /// Check if the `leg` reaches the limit price of an order and whether the candle fills it.
+    /// The `fill_model` is asked only the first time a leg reaches the order.
+    ///
+    /// # Arguments:
+    /// `leg`: The leg of the intrabar path, whose `low` and `high` are the price it reaches.
+    /// `order_id`: The id of the resting limit order.
+    /// `side`: The side of the limit order.
+    /// `limit_price`: The limit price of the order.
+    /// `fill_model`: Decides whether the order is filled by the whole candle.
+    /// `tick_size`: The tick size of the `PriceFilter`.
+    pub(crate) fn is_filled<F>(
+        &mut self,
+        leg: &MarketUpdate<S>,
+        order_id: u64,
+        side: Side,
+        limit_price: QuoteCurrency,
+        fill_model: &mut F,
+        tick_size: QuoteCurrency,
+    ) -> bool
+    where
+        F: FillModel,
+    {
+        let MarketUpdate::Candle { low, high, .. } = leg else {
+            panic!("The legs of a candle are candles");
+        };
+        if penetration_depth(side, limit_price, *low, *high) < QuoteCurrency::new_zero() {
+            return false;
+        }
+        let (candle_low, candle_high) = (self.low, self.high);
+        *self.decisions.entry(order_id).or_insert_with(|| {
+            fill_model.is_filled(side, limit_price, candle_low, candle_high, tick_size)
+        })
+    }
+
+    /// The quantity, which the resting limit orders can still get filled, if limited.
+    pub(crate) fn fill_capacity(&mut self) -> Option<&mut S> {
+        self.fill_capacity.as_mut()
+    }
+}
+
+/// Decides whether a resting limit order is filled by a `MarketUpdate::Candle`.
+/// The model is selected per `Exchange` with `Exchange::with_fill_model`.
+pub trait FillModel {
+    /// Check if a resting limit order is filled by a candle.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the limit order.
+    /// `limit_price`: The limit price of the order.
+    /// `low`: The low price of the candle.
+    /// `high`: The high price of the candle.
+    /// `tick_size`: The tick size of the `PriceFilter`.
+    ///
+    /// # Returns:
+    /// true if the order is filled completely.
+    fn is_filled(
+        &mut self,
+        side: Side,
+        limit_price: QuoteCurrency,
+        low: QuoteCurrency,
+        high: QuoteCurrency,
+        tick_size: QuoteCurrency,
+    ) -> bool;
+
+    /// The assumed path of the price within a candle.
+    fn intrabar_path(&self) -> IntrabarPath {
+        IntrabarPath::default()
+    }
+
+    /// The fraction of the volume of a candle, which the resting limit orders can get filled at most.
+    /// None means the fills are not limited by the volume.
+    fn max_volume_fraction(&self) -> Option<Decimal> {
+        None
+    }
+}
+
+/// Make sure the fraction of the candle volume is in the range (0, 1].
+fn validate_volume_fraction(fraction: Decimal) -> Result<Decimal> {
+    if fraction <= Decimal::ZERO || fraction > Decimal::ONE {
+        return Err(Error::InvalidVolumeFraction);
+    }
+    Ok(fraction)
+}
+
+/// How far the price of the candle has moved through the limit price, which is negative if it has not reached it.
+fn penetration_depth(
+    side: Side,
+    limit_price: QuoteCurrency,
+    low: QuoteCurrency,
+    high: QuoteCurrency,
+) -> QuoteCurrency {
+    match side {
+        Side::Buy => limit_price - low,
+        Side::Sell => high - limit_price,
+    }
+}
+
+/// Only fills an order once the price has traded through its limit price by some ticks,
+/// as touching the limit price does not mean the queue ahead has been consumed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PessimisticFillModel {
+    trade_through_ticks: u32,
+    intrabar_path: IntrabarPath,
+    max_volume_fraction: Option<Decimal>,
+}
+
+impl PessimisticFillModel {
+    /// Create a new instance.
+    ///
+    /// # Arguments:
+    /// `trade_through_ticks`: The number of ticks the price has to trade through the limit price.
+    /// `intrabar_path`: The assumed path of the price within a candle.
+    pub fn new(trade_through_ticks: u32, intrabar_path: IntrabarPath) -> Self {
+        Self {
+            trade_through_ticks,
+            intrabar_path,
+            max_volume_fraction: None,
+        }
+    }
+
+    /// Limit the quantity filled by a candle to a `fraction` of its volume, which must be in the range (0, 1].
+    pub fn set_max_volume_fraction(&mut self, fraction: Decimal) -> Result<()> {
+        self.max_volume_fraction = Some(validate_volume_fraction(fraction)?);
+        Ok(())
+    }
+}
+
+/// Requires a trade through of a single tick, which is the default.
+impl Default for PessimisticFillModel {
+    fn default() -> Self {
+        Self::new(1, IntrabarPath::default())
+    }
+}
+
+impl FillModel for PessimisticFillModel {
+    fn is_filled(
+        &mut self,
+        side: Side,
+        limit_price: QuoteCurrency,
+        low: QuoteCurrency,
+        high: QuoteCurrency,
+        tick_size: QuoteCurrency,
+    ) -> bool {
+        let depth = penetration_depth(side, limit_price, low, high);
+        depth > QuoteCurrency::new_zero()
+            && depth >= tick_size * Decimal::from(self.trade_through_ticks)
+    }
+
+    fn intrabar_path(&self) -> IntrabarPath {
+        self.intrabar_path
+    }
+
+    fn max_volume_fraction(&self) -> Option<Decimal> {
+        self.max_volume_fraction
+    }
+}
+
+/// Fills an order as soon as the price touches its limit price.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct OptimisticFillModel {
+    intrabar_path: IntrabarPath,
+    max_volume_fraction: Option<Decimal>,
+}
+
+impl OptimisticFillModel {
+    /// Create a new instance.
+    ///
+    /// # Arguments:
+    /// `intrabar_path`: The assumed path of the price within a candle.
+    pub fn new(intrabar_path: IntrabarPath) -> Self {
+        Self {
+            intrabar_path,
+            max_volume_fraction: None,
+        }
+    }
+
+    /// Limit the quantity filled by a candle to a `fraction` of its volume, which must be in the range (0, 1].
+    pub fn set_max_volume_fraction(&mut self, fraction: Decimal) -> Result<()> {
+        self.max_volume_fraction = Some(validate_volume_fraction(fraction)?);
+        Ok(())
+    }
+}
+
+impl FillModel for OptimisticFillModel {
+    fn is_filled(
+        &mut self,
+        side: Side,
+        limit_price: QuoteCurrency,
+        low: QuoteCurrency,
+        high: QuoteCurrency,
+        _tick_size: QuoteCurrency,
+    ) -> bool {
+        penetration_depth(side, limit_price, low, high) >= QuoteCurrency::new_zero()
+    }
+
+    fn intrabar_path(&self) -> IntrabarPath {
+        self.intrabar_path
+    }
+
+    fn max_volume_fraction(&self) -> Option<Decimal> {
+        self.max_volume_fraction
+    }
+}
+
+/// Fills an order with a probability, which rises linearly with the penetration depth of the price.
+/// Touching the limit price fills with a probability of `1 / (certain_fill_ticks + 1)`,
+/// while trading through it by `certain_fill_ticks` always fills.
+#[derive(Debug, Clone)]
+pub struct ProbabilisticFillModel {
+    certain_fill_ticks: u32,
+    intrabar_path: IntrabarPath,
+    max_volume_fraction: Option<Decimal>,
+    rng: StdRng,
+}
+
+impl ProbabilisticFillModel {
+    /// Create a new instance.
+    ///
+    /// # Arguments:
+    /// `certain_fill_ticks`: The penetration depth in ticks, from which on an order is always filled.
+    /// `intrabar_path`: The assumed path of the price within a candle.
+    /// `seed`: The seed of the random number generator, making a backtest reproducible.
+    pub fn new(certain_fill_ticks: u32, intrabar_path: IntrabarPath, seed: u64) -> Self {
+        Self {
+            certain_fill_ticks,
+            intrabar_path,
+            max_volume_fraction: None,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Limit the quantity filled by a candle to a `fraction` of its volume, which must be in the range (0, 1].
+    pub fn set_max_volume_fraction(&mut self, fraction: Decimal) -> Result<()> {
+        self.max_volume_fraction = Some(validate_volume_fraction(fraction)?);
+        Ok(())
+    }
+
+    /// The probability of a fill given the penetration depth in ticks.
+    fn fill_probability(&self, depth_ticks: Decimal) -> f64 {
+        let p = (depth_ticks + Decimal::ONE) / Decimal::from(self.certain_fill_ticks + 1);
+        if p >= Decimal::ONE {
+            1.0
+        } else {
+            f64::from(p)
+        }
+    }
+}
+
+impl FillModel for ProbabilisticFillModel {
+    fn is_filled(
+        &mut self,
+        side: Side,
+        limit_price: QuoteCurrency,
+        low: QuoteCurrency,
+        high: QuoteCurrency,
+        tick_size: QuoteCurrency,
+    ) -> bool {
+        let depth = penetration_depth(side, limit_price, low, high);
+        if depth < QuoteCurrency::new_zero() {
+            return false;
+        }
+        let p = self.fill_probability((depth / tick_size).inner());
+        self.rng.gen_bool(p)
+    }
+
+    fn intrabar_path(&self) -> IntrabarPath {
+        self.intrabar_path
+    }
+
+    fn max_volume_fraction(&self) -> Option<Decimal> {
+        self.max_volume_fraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{candle, prelude::*};
+
+    #[test]
+    fn pessimistic_fill_model() {
+        let mut model = PessimisticFillModel::default();
+        let tick = quote!(1);
+        // Buys
+        assert!(!model.is_filled(Side::Buy, quote!(90), quote!(98), quote!(102), tick));
+        assert!(!model.is_filled(Side::Buy, quote!(98), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Buy, quote!(99), quote!(98), quote!(102), tick));
+        // Sells
+        assert!(!model.is_filled(Side::Sell, quote!(110), quote!(98), quote!(102), tick));
+        assert!(!model.is_filled(Side::Sell, quote!(102), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Sell, quote!(101), quote!(98), quote!(102), tick));
+
+        let mut model = PessimisticFillModel::new(3, IntrabarPath::OpenHighLowClose);
+        assert!(!model.is_filled(Side::Buy, quote!(100), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Buy, quote!(101), quote!(98), quote!(102), tick));
+        assert!(!model.is_filled(Side::Sell, quote!(100), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Sell, quote!(99), quote!(98), quote!(102), tick));
+    }
+
+    #[test]
+    fn optimistic_fill_model() {
+        let mut model = OptimisticFillModel::default();
+        let tick = quote!(1);
+        assert!(!model.is_filled(Side::Buy, quote!(97), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Buy, quote!(98), quote!(98), quote!(102), tick));
+        assert!(!model.is_filled(Side::Sell, quote!(103), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Sell, quote!(102), quote!(98), quote!(102), tick));
+    }
+
+    #[test]
+    fn probabilistic_fill_model() {
+        let mut model = ProbabilisticFillModel::new(4, IntrabarPath::OpenHighLowClose, 0);
+        let tick = quote!(1);
+        assert_eq!(model.fill_probability(Decimal::ZERO), 0.2);
+        assert_eq!(model.fill_probability(Decimal::TWO), 0.6);
+        assert_eq!(model.fill_probability(Decimal::from(4)), 1.0);
+        assert_eq!(model.fill_probability(Decimal::from(10)), 1.0);
+
+        assert!(!model.is_filled(Side::Buy, quote!(97), quote!(98), quote!(102), tick));
+        assert!(model.is_filled(Side::Buy, quote!(102), quote!(98), quote!(102), tick));
+
+        let fills = |seed| {
+            let mut model = ProbabilisticFillModel::new(4, IntrabarPath::OpenHighLowClose, seed);
+            Vec::from_iter(
+                (0..100)
+                    .map(|_| model.is_filled(Side::Buy, quote!(98), quote!(98), quote!(102), tick)),
+            )
+        };
+        // Reproducible with the same seed.
+        assert_eq!(fills(42), fills(42));
+        let num_fills = fills(42).into_iter().filter(|filled| *filled).count();
+        assert!(num_fills > 0 && num_fills < 100);
+    }
+
+    #[test]
+    fn intrabar_path_legs() {
+        let candle: MarketUpdate<BaseCurrency> = candle!(
+            quote!(100),
+            quote!(101),
+            quote!(99),
+            quote!(102),
+            quote!(98),
+            quote!(100),
+            base!(5)
+        );
+        let leg = |price| {
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(99),
+                price,
+                price,
+                quote!(100),
+                base!(5)
+            )
+        };
+        assert_eq!(
+            IntrabarPath::OpenHighLowClose.legs(&candle),
+            [leg(quote!(99)), leg(quote!(102)), leg(quote!(98))]
+        );
+        assert_eq!(
+            IntrabarPath::OpenLowHighClose.legs(&candle),
+            [leg(quote!(99)), leg(quote!(98)), leg(quote!(102))]
+        );
+    }
+
+    #[test]
+    fn fill_model_max_volume_fraction() {
+        let mut model = PessimisticFillModel::default();
+        assert_eq!(model.max_volume_fraction(), None);
+        assert_eq!(
+            model.set_max_volume_fraction(Decimal::ZERO),
+            Err(Error::InvalidVolumeFraction)
+        );
+        assert_eq!(
+            model.set_max_volume_fraction(Dec!(1.1)),
+            Err(Error::InvalidVolumeFraction)
+        );
+        model.set_max_volume_fraction(Dec!(0.1)).unwrap();
+        assert_eq!(model.max_volume_fraction(), Some(Dec!(0.1)));
+    }
+}
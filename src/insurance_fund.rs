@@ -0,0 +1,82 @@
+//! The insurance fund of the exchange, which covers the losses of liquidations beyond the bankruptcy price.
+
+use crate::types::{Currency, MarginCurrency};
+
+/// The insurance fund of the exchange.
+/// It receives the surplus of liquidations, which could be closed in the market at a better price than the account,
+/// including the liquidation fee, and covers the shortfall of liquidations which could only be closed at a worse price.
+#[derive(Debug, Clone)]
+pub struct InsuranceFund<M>
+where
+    M: Currency + MarginCurrency,
+{
+    balance: M,
+    /// The timestamp and balance after every change.
+    history: Vec<(i64, M)>,
+}
+
+impl<M> InsuranceFund<M>
+where
+    M: Currency + MarginCurrency,
+{
+    pub(crate) fn new(balance: M) -> Self {
+        Self {
+            balance,
+            history: Vec::new(),
+        }
+    }
+
+    /// Return the current balance of the insurance fund, which never becomes negative.
+    #[inline(always)]
+    pub fn balance(&self) -> M {
+        self.balance
+    }
+
+    /// Return the timestamp in nanoseconds and the balance of the insurance fund after every change.
+    #[inline(always)]
+    pub fn history(&self) -> &[(i64, M)] {
+        &self.history
+    }
+
+    /// Receive the surplus of a liquidation, or cover its shortfall as far as possible.
+    ///
+    /// # Arguments:
+    /// `amount`: The surplus if positive, or the shortfall if negative.
+    /// `timestamp_ns`: The timestamp of the liquidation.
+    ///
+    /// # Returns:
+    /// The part of the shortfall, which the balance could not cover.
+    pub(crate) fn settle(&mut self, amount: M, timestamp_ns: i64) -> M {
+        let new_balance = self.balance + amount;
+        let uncovered = if new_balance < M::new_zero() {
+            new_balance.abs()
+        } else {
+            M::new_zero()
+        };
+        self.balance = new_balance + uncovered;
+        self.history.push((timestamp_ns, self.balance));
+
+        uncovered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn insurance_fund_settle() {
+        let mut fund = InsuranceFund::new(quote!(10));
+        assert_eq!(fund.settle(quote!(5), 1), quote!(0));
+        assert_eq!(fund.balance(), quote!(15));
+        assert_eq!(fund.settle(quote!(-12), 2), quote!(0));
+        assert_eq!(fund.balance(), quote!(3));
+        assert_eq!(fund.settle(quote!(-5), 3), quote!(2));
+        assert_eq!(fund.balance(), quote!(0));
+        assert_eq!(
+            fund.history(),
+            &[(1, quote!(15)), (2, quote!(3)), (3, quote!(0))]
+        );
+    }
+}
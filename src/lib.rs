@@ -16,10 +16,14 @@ mod config;
 mod contract_specification;
 mod cornish_fisher;
 mod exchange;
+mod fill_model;
+mod insurance_fund;
 mod market_state;
+mod matching_engine;
 mod mock_exchange;
 mod order_filters;
 mod order_margin;
+mod pending_actions;
 mod position;
 mod risk_engine;
 #[cfg(test)]
@@ -40,9 +44,13 @@ pub mod prelude {
         base, bba,
         config::Config,
         contract_specification::*,
-        exchange::Exchange,
-        fee, leverage,
+        exchange::{Exchange, Executions},
+        fee,
+        fill_model::*,
+        insurance_fund::InsuranceFund,
+        leverage,
         market_state::MarketState,
+        matching_engine::MatchingEngine,
         order_filters::{PriceFilter, QuantityFilter},
         position::Position,
         quote,
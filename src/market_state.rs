@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use fpdec::Decimal;
 
 use crate::{
@@ -16,6 +18,8 @@ pub struct MarketState {
     bid: QuoteCurrency,
     /// The current ask
     ask: QuoteCurrency,
+    /// The price of the last observed trade
+    last_trade_price: QuoteCurrency,
     /// The current timestamp in nanoseconds
     current_ts_ns: i64,
     /// Used for synchronizing orders
@@ -28,6 +32,7 @@ impl MarketState {
             price_filter,
             bid: quote!(0),
             ask: quote!(0),
+            last_trade_price: quote!(0),
             current_ts_ns: 0,
             step: 0,
         }
@@ -55,10 +60,18 @@ impl MarketState {
                 self.bid = *bid;
                 self.ask = *ask;
             }
-            MarketUpdate::Trade { .. } => {}
-            MarketUpdate::Candle { bid, ask, .. } => {
+            MarketUpdate::Trade { price, .. } => {
+                self.last_trade_price = *price;
+            }
+            // The best bid and ask are derived from the order book by the `Exchange`.
+            MarketUpdate::L2Update { .. } | MarketUpdate::L2Snapshot { .. } => {}
+            MarketUpdate::Liquidation { .. } | MarketUpdate::Funding { .. } => {}
+            MarketUpdate::Candle {
+                bid, ask, close, ..
+            } => {
                 self.bid = *bid;
                 self.ask = *ask;
+                self.last_trade_price = *close;
             }
         }
         self.current_ts_ns = timestamp_ns as i64;
@@ -67,6 +80,13 @@ impl MarketState {
         Ok(())
     }
 
+    /// Set the best bid and ask, e.g.: when they are derived from the level 2 order book.
+    #[inline]
+    pub(crate) fn set_bba(&mut self, bid: QuoteCurrency, ask: QuoteCurrency) {
+        self.bid = bid;
+        self.ask = ask;
+    }
+
     /// Get the mid price
     #[inline]
     pub fn mid_price(&self) -> QuoteCurrency {
@@ -90,4 +110,97 @@ impl MarketState {
     pub fn ask(&self) -> QuoteCurrency {
         self.ask
     }
+
+    /// Get the price of the last observed `Trade` or the close of the last `Candle`,
+    /// which is zero if none has been observed yet.
+    #[inline]
+    pub fn last_trade_price(&self) -> QuoteCurrency {
+        self.last_trade_price
+    }
+}
+
+/// The view of the market, which a strategy gets to see through a delayed market data feed.
+#[derive(Debug, Clone)]
+pub(crate) struct MarketDataFeed {
+    /// The delay of the feed in nanoseconds.
+    delay_ns: i64,
+    /// The observed market states, which have not reached the strategy yet.
+    in_flight: VecDeque<MarketState>,
+    /// The latest market state which has reached the strategy.
+    view: MarketState,
+}
+
+impl MarketDataFeed {
+    pub(crate) fn new(delay_ns: u64, price_filter: PriceFilter) -> Self {
+        Self {
+            delay_ns: delay_ns as i64,
+            in_flight: VecDeque::new(),
+            view: MarketState::new(price_filter),
+        }
+    }
+
+    /// Whether the strategy sees the true market state without delay.
+    #[inline(always)]
+    pub(crate) fn is_immediate(&self) -> bool {
+        self.delay_ns == 0
+    }
+
+    /// Publish the latest true market state,
+    /// which reaches the strategy once the market clock has advanced by the delay.
+    pub(crate) fn publish(&mut self, market_state: &MarketState) {
+        let now = market_state.current_timestamp_ns();
+        self.in_flight.push_back(market_state.clone());
+        while self
+            .in_flight
+            .front()
+            .is_some_and(|state| state.current_timestamp_ns() + self.delay_ns <= now)
+        {
+            self.view = self.in_flight.pop_front().expect("Is some; qed");
+        }
+    }
+
+    /// The delayed market state.
+    #[inline(always)]
+    pub(crate) fn view(&self) -> &MarketState {
+        &self.view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn market_data_feed_delay() {
+        let mut market_state = MarketState::new(PriceFilter::default());
+        let mut feed = MarketDataFeed::new(100, PriceFilter::default());
+        assert!(!feed.is_immediate());
+
+        market_state
+            .update_state::<BaseCurrency>(0, &bba!(quote!(100), quote!(101)))
+            .unwrap();
+        feed.publish(&market_state);
+        assert_eq!(feed.view().bid(), quote!(0));
+
+        market_state
+            .update_state::<BaseCurrency>(50, &bba!(quote!(102), quote!(103)))
+            .unwrap();
+        feed.publish(&market_state);
+        assert_eq!(feed.view().bid(), quote!(0));
+
+        market_state
+            .update_state::<BaseCurrency>(120, &bba!(quote!(104), quote!(105)))
+            .unwrap();
+        feed.publish(&market_state);
+        assert_eq!(feed.view().bid(), quote!(100));
+        assert_eq!(feed.view().current_timestamp_ns(), 0);
+
+        market_state
+            .update_state::<BaseCurrency>(300, &bba!(quote!(106), quote!(107)))
+            .unwrap();
+        feed.publish(&market_state);
+        assert_eq!(feed.view().bid(), quote!(104));
+        assert_eq!(feed.view().current_timestamp_ns(), 120);
+    }
 }
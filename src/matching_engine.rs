@@ -0,0 +1,1000 @@
+//! The `MatchingEngine` simulates how the resting limit orders of the user
+//! are matched against the observed market data.
+
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use hashbrown::HashMap;
+
+use crate::{
+    exchange::EXPECT_LIMIT_PRICE,
+    fill_model::{CandleFills, FillModel},
+    types::{Currency, MarketUpdate, Order, QuoteCurrency, Side},
+    utils::min,
+};
+
+/// The position of a resting limit order in the queue of its price level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueuePosition<S> {
+    side: Side,
+    price: QuoteCurrency,
+    /// The quantity of other market participants resting ahead of the order.
+    quantity_ahead: S,
+    /// The remaining quantity of the visible slice of an iceberg order.
+    visible_quantity: Option<S>,
+}
+
+/// The `MatchingEngine` maintains a level 2 (price aggregated) order book
+/// which is built from `MarketUpdate::L2Update`s.
+/// For every resting limit order of the user it tracks the quantity resting ahead of it
+/// at the same price level, so that the order only gets filled once the volume ahead of it has traded.
+///
+/// The orders of the user are not part of the book itself,
+/// as the book represents the liquidity of all other market participants.
+///
+/// Generic over:
+/// `S`: The `Currency` representing the order quantity.
+#[derive(Debug, Clone)]
+pub struct MatchingEngine<S>
+where
+    S: Currency,
+{
+    /// Maps the price level to the resting quantity of the bids.
+    bids: BTreeMap<QuoteCurrency, S>,
+    /// Maps the price level to the resting quantity of the asks.
+    asks: BTreeMap<QuoteCurrency, S>,
+    /// Maps the order `id` to its position in the queue.
+    queue_positions: HashMap<u64, QueuePosition<S>>,
+}
+
+impl<S> MatchingEngine<S>
+where
+    S: Currency,
+{
+    /// Create a new instance with an empty order book.
+    pub(crate) fn new() -> Self {
+        Self {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            queue_positions: HashMap::new(),
+        }
+    }
+
+    /// Return the best bid price level and its resting quantity, if any.
+    #[inline]
+    pub fn best_bid(&self) -> Option<(QuoteCurrency, S)> {
+        self.bids.iter().next_back().map(|(p, q)| (*p, *q))
+    }
+
+    /// Return the best ask price level and its resting quantity, if any.
+    #[inline]
+    pub fn best_ask(&self) -> Option<(QuoteCurrency, S)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
+    }
+
+    /// Return the quantity resting at a price level of the book.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the book, where `Buy` denotes the bids and `Sell` the asks.
+    /// `price`: The price of the level.
+    pub fn level_quantity(&self, side: Side, price: QuoteCurrency) -> S {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.get(&price).copied().unwrap_or_else(S::new_zero)
+    }
+
+    /// Return the quantity resting ahead of an order in the queue of its price level.
+    ///
+    /// # Returns:
+    /// `None` if there is no resting order with the `order_id`.
+    #[inline]
+    pub fn queue_position(&self, order_id: u64) -> Option<S> {
+        self.queue_positions
+            .get(&order_id)
+            .map(|pos| pos.quantity_ahead)
+    }
+
+    /// Place a new resting limit order at the back of the queue of its price level.
+    pub(crate) fn insert_order(&mut self, order: &Order<S>) {
+        let price = order.limit_price().expect(EXPECT_LIMIT_PRICE);
+        let quantity_ahead = self.level_quantity(order.side(), price);
+        trace!(
+            "insert_order: id: {}, quantity_ahead: {}",
+            order.id(),
+            quantity_ahead
+        );
+        self.queue_positions.insert(
+            order.id(),
+            QueuePosition {
+                side: order.side(),
+                price,
+                quantity_ahead,
+                visible_quantity: order
+                    .display_quantity()
+                    .map(|display_qty| min(display_qty, order.remaining_quantity())),
+            },
+        );
+    }
+
+    /// Remove a resting order, e.g.: when it got cancelled or filled.
+    pub(crate) fn remove_order(&mut self, order_id: u64) {
+        self.queue_positions.remove(&order_id);
+    }
+
+    /// Update the order book with new market information.
+    /// This must be called after checking the resting orders for execution,
+    /// so that a `Trade` does not consume the volume ahead of an order twice.
+    pub(crate) fn update_book(&mut self, market_update: &MarketUpdate<S>) {
+        match market_update {
+            MarketUpdate::Bba { bid, ask } => {
+                // Any level better than the new best bid and ask has been removed.
+                self.bids.retain(|price, _| price <= bid);
+                self.asks.retain(|price, _| price >= ask);
+            }
+            MarketUpdate::Trade {
+                price,
+                quantity,
+                side,
+            } => {
+                // The taker consumes all levels up to the trade price.
+                let levels = match side {
+                    Side::Buy => {
+                        self.asks.retain(|p, _| p >= price);
+                        &mut self.asks
+                    }
+                    Side::Sell => {
+                        self.bids.retain(|p, _| p <= price);
+                        &mut self.bids
+                    }
+                };
+                if let Some(level_qty) = levels.get_mut(price) {
+                    *level_qty -= min(*level_qty, *quantity);
+                    if level_qty.is_zero() {
+                        levels.remove(price);
+                    }
+                }
+            }
+            MarketUpdate::Candle { .. }
+            | MarketUpdate::Liquidation { .. }
+            | MarketUpdate::Funding { .. } => {}
+            MarketUpdate::L2Update {
+                side,
+                price,
+                quantity,
+            } => {
+                let levels = match side {
+                    Side::Buy => &mut self.bids,
+                    Side::Sell => &mut self.asks,
+                };
+                if quantity.is_zero() {
+                    levels.remove(price);
+                } else {
+                    levels.insert(*price, *quantity);
+                }
+                self.clamp_queue_positions();
+            }
+            MarketUpdate::L2Snapshot { bids, asks } => {
+                self.bids =
+                    BTreeMap::from_iter(bids.iter().copied().filter(|(_, qty)| !qty.is_zero()));
+                self.asks =
+                    BTreeMap::from_iter(asks.iter().copied().filter(|(_, qty)| !qty.is_zero()));
+                self.clamp_queue_positions();
+            }
+        }
+    }
+
+    /// Quantity that got cancelled may have been ahead of an order,
+    /// but the queue ahead can never be larger than the price level itself.
+    fn clamp_queue_positions(&mut self) {
+        for pos in self.queue_positions.values_mut() {
+            let levels = match pos.side {
+                Side::Buy => &self.bids,
+                Side::Sell => &self.asks,
+            };
+            let level_qty = levels.get(&pos.price).copied().unwrap_or_else(S::new_zero);
+            pos.quantity_ahead = min(pos.quantity_ahead, level_qty);
+        }
+    }
+
+    /// Compute the volume weighted average price at which a market order would be filled,
+    /// by walking the visible levels of the book.
+    /// Any quantity exceeding the visible liquidity is assumed to be filled at the worst visible level.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the market order.
+    /// `quantity`: The quantity of the market order.
+    ///
+    /// # Returns:
+    /// `None` if there is no visible liquidity on the opposite side of the book.
+    pub fn market_order_fill_price(&self, side: Side, quantity: S) -> Option<QuoteCurrency> {
+        let levels = match side {
+            Side::Buy => Vec::from_iter(self.asks.iter()),
+            Side::Sell => Vec::from_iter(self.bids.iter().rev()),
+        };
+
+        let mut remaining = quantity;
+        let mut notional = QuoteCurrency::new_zero();
+        let mut worst_price = None;
+        for (price, level_qty) in levels {
+            if remaining.is_zero() {
+                break;
+            }
+            let fill_qty = min(remaining, *level_qty);
+            notional += *price * fill_qty.inner();
+            remaining -= fill_qty;
+            worst_price = Some(*price);
+        }
+        let worst_price = worst_price?;
+        notional += worst_price * remaining.inner();
+
+        Some(notional / quantity.inner())
+    }
+
+    /// Compute how much of a limit order could be executed immediately against the visible levels,
+    /// which are priced at or better than its `limit_price`.
+    ///
+    /// # Returns:
+    /// The executable quantity and its volume weighted average price,
+    /// or None if there is no visible liquidity on the opposite side of the book.
+    /// If nothing is executable, the quantity is zero and the price is the `limit_price`.
+    pub fn limit_order_fill(
+        &self,
+        side: Side,
+        quantity: S,
+        limit_price: QuoteCurrency,
+    ) -> Option<(S, QuoteCurrency)> {
+        let levels = match side {
+            Side::Buy => Vec::from_iter(self.asks.iter().take_while(|(p, _)| **p <= limit_price)),
+            Side::Sell => Vec::from_iter(
+                self.bids
+                    .iter()
+                    .rev()
+                    .take_while(|(p, _)| **p >= limit_price),
+            ),
+        };
+        let has_liquidity = match side {
+            Side::Buy => !self.asks.is_empty(),
+            Side::Sell => !self.bids.is_empty(),
+        };
+        if !has_liquidity {
+            return None;
+        }
+
+        let mut filled = S::new_zero();
+        let mut notional = QuoteCurrency::new_zero();
+        for (price, level_qty) in levels {
+            let fill_qty = min(quantity - filled, *level_qty);
+            if fill_qty.is_zero() {
+                break;
+            }
+            notional += *price * fill_qty.inner();
+            filled += fill_qty;
+        }
+        if filled.is_zero() {
+            return Some((filled, limit_price));
+        }
+
+        Some((filled, notional / filled.inner()))
+    }
+
+    /// Remove the liquidity consumed by a market order of the user from the book.
+    pub(crate) fn consume_liquidity(&mut self, side: Side, quantity: S) {
+        let levels = match side {
+            Side::Buy => &mut self.asks,
+            Side::Sell => &mut self.bids,
+        };
+        let mut remaining = quantity;
+        while remaining > S::new_zero() {
+            let price = match side {
+                Side::Buy => levels.keys().next(),
+                Side::Sell => levels.keys().next_back(),
+            };
+            // The rest is filled beyond the visible liquidity.
+            let Some(price) = price.copied() else {
+                break;
+            };
+            let level_qty = levels.get_mut(&price).expect("The level exists; qed");
+            let fill_qty = min(remaining, *level_qty);
+            *level_qty -= fill_qty;
+            remaining -= fill_qty;
+            if level_qty.is_zero() {
+                levels.remove(&price);
+            }
+        }
+        self.clamp_queue_positions();
+    }
+
+    /// Check which of the resting limit orders have been executed by the `market_update`.
+    ///
+    /// # Arguments:
+    /// `active_limit_orders`: The resting limit orders of the user.
+    /// `market_update`: The newest market information.
+    /// `candle_fills`: The state of matching the candle, of which the `market_update` is a leg.
+    /// `fill_model`: Decides which orders are filled by a `MarketUpdate::Candle`.
+    /// `tick_size`: The tick size of the `PriceFilter`.
+    ///
+    /// # Returns:
+    /// The ids of the executed orders with their filled quantity, sorted by ascending order id.
+    pub(crate) fn check_resting_orders<F>(
+        &mut self,
+        active_limit_orders: &HashMap<u64, Order<S>>,
+        market_update: &MarketUpdate<S>,
+        candle_fills: Option<&mut CandleFills<S>>,
+        fill_model: &mut F,
+        tick_size: QuoteCurrency,
+    ) -> Vec<(u64, S)>
+    where
+        F: FillModel,
+    {
+        let mut fills = match market_update {
+            MarketUpdate::Trade { quantity, side, .. } => {
+                self.check_trade_execution(active_limit_orders, market_update, *quantity, *side)
+            }
+            // Without any information about the traded quantity, the orders are filled completely.
+            MarketUpdate::Candle { .. } => {
+                let candle_fills =
+                    candle_fills.expect("Candles are matched along their intrabar path; qed");
+                // Sorted, so a random fill model is reproducible.
+                let mut orders = Vec::from_iter(active_limit_orders.values());
+                orders.sort_unstable_by_key(|order| order.id());
+                Vec::from_iter(
+                    orders
+                        .into_iter()
+                        .filter(|order| {
+                            candle_fills.is_filled(
+                                market_update,
+                                order.id(),
+                                order.side(),
+                                order.limit_price().expect(EXPECT_LIMIT_PRICE),
+                                fill_model,
+                                tick_size,
+                            )
+                        })
+                        .map(|order| (order.id(), order.remaining_quantity())),
+                )
+            }
+            _ => Vec::from_iter(
+                active_limit_orders
+                    .values()
+                    .filter(|order| check_limit_order_execution(order, market_update))
+                    .map(|order| (order.id(), order.remaining_quantity())),
+            ),
+        };
+        fills.sort_unstable_by_key(|(id, _)| *id);
+
+        fills
+    }
+
+    /// Check which resting orders have been executed by a taker `Trade`,
+    /// taking their queue position into account.
+    /// The traded `quantity` is the volume of the taker, which sweeps the price levels of the book
+    /// from the best price up to the trade price. At every level it first consumes the volume of other
+    /// participants resting ahead of the users orders, then the orders themselves in time priority
+    /// and then the rest of the level, before moving on to the next level.
+    fn check_trade_execution(
+        &mut self,
+        active_limit_orders: &HashMap<u64, Order<S>>,
+        market_update: &MarketUpdate<S>,
+        quantity: S,
+        side: Side,
+    ) -> Vec<(u64, S)> {
+        let mut orders = Vec::from_iter(
+            active_limit_orders
+                .values()
+                .filter(|order| check_limit_order_execution(order, market_update)),
+        );
+        // The taker consumes the best prices first.
+        match side {
+            Side::Buy => orders.sort_by_key(|order| (order.limit_price(), order.id())),
+            Side::Sell => orders.sort_by_key(|order| (Reverse(order.limit_price()), order.id())),
+        }
+
+        let mut fills = Vec::new();
+        // The volume of the taker, which has not been consumed yet.
+        let mut available = quantity;
+        // The price level swept at the moment and the volume of other participants consumed there.
+        let mut level: Option<(QuoteCurrency, S)> = None;
+        // The iceberg orders, whose next slice joined the back of the current level.
+        let mut replenished = Vec::new();
+        for order in orders {
+            let limit_price = order.limit_price().expect(EXPECT_LIMIT_PRICE);
+            if level.map(|(price, _)| price) != Some(limit_price) {
+                if let Some(swept) = level {
+                    self.finish_level(side, swept, &mut available, &replenished);
+                    replenished.clear();
+                }
+                self.sweep_levels_between(side, level, limit_price, &mut available);
+                level = Some((limit_price, S::new_zero()));
+            }
+            let level_consumed = &mut level.as_mut().expect("The level is set; qed").1;
+            let level_qty = self.level_quantity(side.inverted(), limit_price);
+
+            let Some(pos) = self.queue_positions.get_mut(&order.id()) else {
+                let fill_qty = min(available, order.remaining_quantity());
+                if fill_qty > S::new_zero() {
+                    available -= fill_qty;
+                    fills.push((order.id(), fill_qty));
+                }
+                continue;
+            };
+            // The volume resting ahead of the order, which an earlier order at this level did not consume yet.
+            let ahead = pos.quantity_ahead - min(pos.quantity_ahead, *level_consumed);
+            let consumed = min(ahead, available);
+            available -= consumed;
+            *level_consumed += consumed;
+            pos.quantity_ahead -= min(pos.quantity_ahead, *level_consumed);
+
+            let mut fill_qty = min(available, order.remaining_quantity());
+            if let Some(display_qty) = order.display_quantity() {
+                // Only the visible slice of an iceberg order can be filled.
+                let visible_qty = pos
+                    .visible_quantity
+                    .as_mut()
+                    .expect("Iceberg orders have a visible slice; qed");
+                fill_qty = min(fill_qty, *visible_qty);
+                *visible_qty -= fill_qty;
+                if visible_qty.is_zero() {
+                    // The next slice is replenished at the back of the queue,
+                    // behind what remains of the price level.
+                    *visible_qty = min(display_qty, order.remaining_quantity() - fill_qty);
+                    pos.quantity_ahead = level_qty - min(level_qty, *level_consumed);
+                    replenished.push(order.id());
+                }
+            }
+            if fill_qty > S::new_zero() {
+                available -= fill_qty;
+                fills.push((order.id(), fill_qty));
+            }
+        }
+        if let Some(swept) = level {
+            self.finish_level(side, swept, &mut available, &replenished);
+        }
+
+        fills
+    }
+
+    /// Let the taker volume consume the rest of the `swept` level behind the users orders.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the taker.
+    /// `swept`: The price level of the users orders, with the volume of other participants consumed there.
+    /// `available`: The volume of the taker, which has not been consumed yet.
+    /// `replenished`: The iceberg orders, whose next slice joined the back of the `swept` level.
+    fn finish_level(
+        &mut self,
+        side: Side,
+        (price, level_consumed): (QuoteCurrency, S),
+        available: &mut S,
+        replenished: &[u64],
+    ) {
+        let level_qty = self.level_quantity(side.inverted(), price);
+        let consumed = min(*available, level_qty - min(level_qty, level_consumed));
+        *available -= consumed;
+        for order_id in replenished {
+            let pos = self
+                .queue_positions
+                .get_mut(order_id)
+                .expect("A replenished order is resting; qed");
+            pos.quantity_ahead -= min(pos.quantity_ahead, consumed);
+        }
+    }
+
+    /// Let the taker volume consume the levels without any user order,
+    /// which lie between the `swept` level, or the best price, and the `next_price`.
+    fn sweep_levels_between(
+        &self,
+        side: Side,
+        swept: Option<(QuoteCurrency, S)>,
+        next_price: QuoteCurrency,
+        available: &mut S,
+    ) {
+        let between = match side {
+            Side::Buy => self
+                .asks
+                .iter()
+                .filter(|(price, _)| {
+                    **price < next_price && swept.is_none_or(|(swept, _)| **price > swept)
+                })
+                .fold(S::new_zero(), |acc, (_, qty)| acc + *qty),
+            Side::Sell => self
+                .bids
+                .iter()
+                .filter(|(price, _)| {
+                    **price > next_price && swept.is_none_or(|(swept, _)| **price < swept)
+                })
+                .fold(S::new_zero(), |acc, (_, qty)| acc + *qty),
+        };
+        *available -= min(*available, between);
+    }
+}
+
+/// Check an individual resting order if it has been executed,
+/// disregarding its position in the queue.
+fn check_limit_order_execution<S>(limit_order: &Order<S>, market_update: &MarketUpdate<S>) -> bool
+where
+    S: Currency,
+{
+    let limit_price = limit_order.limit_price().expect(EXPECT_LIMIT_PRICE);
+
+    match market_update {
+        MarketUpdate::Bba { .. } => {
+            // Updates to the best bid and ask prices do not trigger limit orders for simulation purposes.
+            false
+        }
+        MarketUpdate::Trade {
+            price,
+            quantity: _,
+            side,
+        } => match limit_order.side() {
+            Side::Buy => *price <= limit_price && matches!(side, Side::Sell),
+            Side::Sell => *price >= limit_price && matches!(side, Side::Buy),
+        },
+        MarketUpdate::Candle { .. } => {
+            unreachable!("Candles are checked by the `FillModel`")
+        }
+        MarketUpdate::L2Update { .. } | MarketUpdate::L2Snapshot { .. } => {
+            // Changes in the resting liquidity do not trigger limit orders.
+            false
+        }
+        // The exchange closes the liquidated position in the market, away from the book of the simulation.
+        MarketUpdate::Liquidation { .. } => false,
+        MarketUpdate::Funding { .. } => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{l2_snapshot, l2_update, prelude::*, trade};
+
+    #[test]
+    fn check_limit_order_execution_buy_trade() {
+        let market_update = MarketUpdate::Trade {
+            price: quote!(100.0),
+            quantity: base!(1.0),
+            side: Side::Buy,
+        };
+        // Buys
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(90), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(99), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(100), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(101), base!(0.1)).unwrap(),
+            &market_update
+        ));
+
+        // Sells
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Sell, quote!(110), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Sell, quote!(101), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(check_limit_order_execution(
+            &Order::limit(Side::Sell, quote!(100), base!(0.1)).unwrap(),
+            &market_update
+        ));
+    }
+
+    #[test]
+    fn check_limit_order_execution_sell_trade() {
+        let market_update = MarketUpdate::Trade {
+            price: quote!(100.0),
+            quantity: base!(1.0),
+            side: Side::Sell,
+        };
+        // Buys
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(90), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(99), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(100), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(check_limit_order_execution(
+            &Order::limit(Side::Buy, quote!(101), base!(0.1)).unwrap(),
+            &market_update
+        ));
+
+        // Sells
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Sell, quote!(110), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Sell, quote!(101), base!(0.1)).unwrap(),
+            &market_update
+        ));
+        assert!(!check_limit_order_execution(
+            &Order::limit(Side::Sell, quote!(100), base!(0.1)).unwrap(),
+            &market_update
+        ));
+    }
+
+    fn limit_order(
+        id: u64,
+        side: Side,
+        price: QuoteCurrency,
+        qty: BaseCurrency,
+    ) -> Order<BaseCurrency> {
+        let mut order = Order::limit(side, price, qty).unwrap();
+        order.set_id(id);
+        order
+    }
+
+    #[test]
+    fn check_resting_orders_candle() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        let market_update = MarketUpdate::Candle {
+            bid: quote!(100),
+            ask: quote!(101),
+            open: quote!(100),
+            high: quote!(102),
+            low: quote!(98),
+            close: quote!(100),
+            volume: base!(10),
+        };
+        let orders = HashMap::from_iter(
+            [
+                limit_order(0, Side::Buy, quote!(90), base!(0.1)),
+                limit_order(1, Side::Buy, quote!(98), base!(0.1)),
+                limit_order(2, Side::Buy, quote!(99), base!(0.1)),
+                limit_order(3, Side::Sell, quote!(110), base!(0.1)),
+                limit_order(4, Side::Sell, quote!(102), base!(0.1)),
+                limit_order(5, Side::Sell, quote!(101), base!(0.1)),
+            ]
+            .map(|order| (order.id(), order)),
+        );
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &market_update,
+                Some(&mut CandleFills::new(&market_update, None)),
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(2, base!(0.1)), (5, base!(0.1))]
+        );
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &market_update,
+                Some(&mut CandleFills::new(&market_update, None)),
+                &mut OptimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![
+                (1, base!(0.1)),
+                (2, base!(0.1)),
+                (4, base!(0.1)),
+                (5, base!(0.1))
+            ]
+        );
+    }
+
+    #[test]
+    fn matching_engine_book() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        assert_eq!(engine.best_bid(), None);
+        assert_eq!(engine.best_ask(), None);
+
+        engine.update_book(&l2_update!(Side::Buy, quote!(99), base!(2)));
+        engine.update_book(&l2_update!(Side::Buy, quote!(100), base!(5)));
+        engine.update_book(&l2_update!(Side::Sell, quote!(101), base!(3)));
+        engine.update_book(&l2_update!(Side::Sell, quote!(102), base!(4)));
+        assert_eq!(engine.best_bid(), Some((quote!(100), base!(5))));
+        assert_eq!(engine.best_ask(), Some((quote!(101), base!(3))));
+
+        // A taker sell order partially consumes the best bid.
+        engine.update_book(&trade!(quote!(100), base!(1), Side::Sell));
+        assert_eq!(engine.level_quantity(Side::Buy, quote!(100)), base!(4));
+
+        // A taker buy sweeps the first ask level.
+        engine.update_book(&trade!(quote!(102), base!(1), Side::Buy));
+        assert_eq!(engine.best_ask(), Some((quote!(102), base!(3))));
+
+        // Removing a level
+        engine.update_book(&l2_update!(Side::Buy, quote!(100), base!(0)));
+        assert_eq!(engine.best_bid(), Some((quote!(99), base!(2))));
+
+        // A new best bid and ask removes all crossed levels.
+        engine.update_book(&bba!(quote!(98), quote!(103)));
+        assert_eq!(engine.best_bid(), None);
+        assert_eq!(engine.best_ask(), None);
+    }
+
+    #[test]
+    fn matching_engine_queue_position() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        engine.update_book(&l2_update!(Side::Buy, quote!(100), base!(5)));
+
+        let order = limit_order(0, Side::Buy, quote!(100), base!(1));
+        engine.insert_order(&order);
+        assert_eq!(engine.queue_position(0), Some(base!(5)));
+        let mut orders = HashMap::new();
+        orders.insert(0, order);
+
+        let update = trade!(quote!(100), base!(3), Side::Sell);
+        assert!(engine
+            .check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            )
+            .is_empty());
+        engine.update_book(&update);
+        assert_eq!(engine.queue_position(0), Some(base!(2)));
+
+        // Some of the volume ahead gets cancelled.
+        engine.update_book(&l2_update!(Side::Buy, quote!(100), base!(1)));
+        assert_eq!(engine.queue_position(0), Some(base!(1)));
+
+        // Consumes exactly the volume ahead.
+        let update = trade!(quote!(100), base!(1), Side::Sell);
+        assert!(engine
+            .check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            )
+            .is_empty());
+        engine.update_book(&update);
+        assert_eq!(engine.queue_position(0), Some(base!(0)));
+
+        let update = trade!(quote!(100), base!(0.5), Side::Sell);
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(0, base!(0.5))]
+        );
+
+        engine.remove_order(0);
+        assert_eq!(engine.queue_position(0), None);
+    }
+
+    #[test]
+    fn matching_engine_queue_priority() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        engine.update_book(&l2_update!(Side::Sell, quote!(101), base!(2)));
+
+        let mut orders = HashMap::new();
+        let order = limit_order(0, Side::Sell, quote!(101), base!(1));
+        engine.insert_order(&order);
+        orders.insert(0, order);
+        engine.update_book(&l2_update!(Side::Sell, quote!(101), base!(4)));
+        let order = limit_order(1, Side::Sell, quote!(101), base!(1));
+        engine.insert_order(&order);
+        orders.insert(1, order);
+        assert_eq!(engine.queue_position(0), Some(base!(2)));
+        assert_eq!(engine.queue_position(1), Some(base!(4)));
+
+        // The first order is filled, while the second one still waits for the volume placed between them.
+        let update = trade!(quote!(101), base!(4), Side::Buy);
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(0, base!(1))]
+        );
+        assert_eq!(engine.queue_position(1), Some(base!(1)));
+
+        // A trade through the price level executes both.
+        let update = trade!(quote!(102), base!(5), Side::Buy);
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(0, base!(1)), (1, base!(1))]
+        );
+    }
+
+    #[test]
+    fn matching_engine_trade_through_levels() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        engine.update_book(&l2_snapshot!(
+            vec![
+                (quote!(101), base!(2)),
+                (quote!(100), base!(3)),
+                (quote!(99), base!(4))
+            ],
+            vec![(quote!(102), base!(5))]
+        ));
+
+        let mut orders = HashMap::new();
+        let order = limit_order(0, Side::Buy, quote!(100), base!(10));
+        engine.insert_order(&order);
+        orders.insert(0, order);
+        assert_eq!(engine.queue_position(0), Some(base!(3)));
+
+        // The print consumes the better level and the volume ahead at 100, before reaching the order.
+        let update = trade!(quote!(99), base!(10), Side::Sell);
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(0, base!(5))]
+        );
+
+        // A print smaller than the volume ahead does not reach the order at all.
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        engine.update_book(&l2_snapshot!(
+            vec![(quote!(101), base!(2)), (quote!(100), base!(3))],
+            vec![(quote!(102), base!(5))]
+        ));
+        engine.insert_order(&orders[&0]);
+        let update = trade!(quote!(99), base!(4), Side::Sell);
+        assert!(engine
+            .check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn matching_engine_partial_fills() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+
+        let mut orders = HashMap::new();
+        let order = limit_order(0, Side::Buy, quote!(100), base!(100));
+        engine.insert_order(&order);
+        orders.insert(0, order);
+        let order = limit_order(1, Side::Buy, quote!(99), base!(2));
+        engine.insert_order(&order);
+        orders.insert(1, order);
+
+        // A small taker order only fills the small quantity of the better priced order.
+        let update = trade!(quote!(99), base!(1), Side::Sell);
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(0, base!(1))]
+        );
+
+        orders.get_mut(&0).unwrap().fill(base!(99), quote!(100));
+        let update = trade!(quote!(99), base!(2), Side::Sell);
+        assert_eq!(
+            engine.check_resting_orders(
+                &orders,
+                &update,
+                None,
+                &mut PessimisticFillModel::default(),
+                quote!(1)
+            ),
+            vec![(0, base!(1)), (1, base!(1))]
+        );
+    }
+
+    #[test]
+    fn matching_engine_market_order_fill_price() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        assert_eq!(engine.market_order_fill_price(Side::Buy, base!(1)), None);
+
+        engine.update_book(&l2_snapshot!(
+            vec![(quote!(100), base!(1)), (quote!(99), base!(3))],
+            vec![(quote!(101), base!(1)), (quote!(102), base!(3))]
+        ));
+        assert_eq!(engine.best_bid(), Some((quote!(100), base!(1))));
+        assert_eq!(engine.best_ask(), Some((quote!(101), base!(1))));
+
+        assert_eq!(
+            engine.market_order_fill_price(Side::Buy, base!(0.5)),
+            Some(quote!(101))
+        );
+        assert_eq!(
+            engine.market_order_fill_price(Side::Buy, base!(2)),
+            Some(quote!(101.5))
+        );
+        assert_eq!(
+            engine.market_order_fill_price(Side::Sell, base!(4)),
+            Some(quote!(99.25))
+        );
+        // Beyond the visible liquidity the worst level is assumed.
+        assert_eq!(
+            engine.market_order_fill_price(Side::Buy, base!(8)),
+            Some(quote!(101.875))
+        );
+
+        engine.consume_liquidity(Side::Buy, base!(2));
+        assert_eq!(engine.best_ask(), Some((quote!(102), base!(2))));
+        engine.consume_liquidity(Side::Sell, base!(5));
+        assert_eq!(engine.best_bid(), None);
+    }
+
+    #[test]
+    fn matching_engine_limit_order_fill() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        assert_eq!(
+            engine.limit_order_fill(Side::Buy, base!(1), quote!(101)),
+            None
+        );
+
+        engine.update_book(&l2_snapshot!(
+            vec![(quote!(100), base!(1)), (quote!(99), base!(3))],
+            vec![(quote!(101), base!(1)), (quote!(102), base!(3))]
+        ));
+        assert_eq!(
+            engine.limit_order_fill(Side::Buy, base!(1), quote!(100)),
+            Some((base!(0), quote!(100)))
+        );
+        assert_eq!(
+            engine.limit_order_fill(Side::Buy, base!(5), quote!(101)),
+            Some((base!(1), quote!(101)))
+        );
+        assert_eq!(
+            engine.limit_order_fill(Side::Buy, base!(2), quote!(105)),
+            Some((base!(2), quote!(101.5)))
+        );
+        assert_eq!(
+            engine.limit_order_fill(Side::Sell, base!(10), quote!(99)),
+            Some((base!(4), quote!(99.25)))
+        );
+    }
+
+    #[test]
+    fn matching_engine_snapshot_queue_position() {
+        let mut engine = MatchingEngine::<BaseCurrency>::new();
+        engine.update_book(&l2_snapshot!(
+            vec![(quote!(100), base!(5))],
+            vec![(quote!(101), base!(5))]
+        ));
+        let order = limit_order(0, Side::Buy, quote!(100), base!(1));
+        engine.insert_order(&order);
+        assert_eq!(engine.queue_position(0), Some(base!(5)));
+
+        engine.update_book(&l2_snapshot!(
+            vec![(quote!(100), base!(3))],
+            vec![(quote!(101), base!(5))]
+        ));
+        assert_eq!(engine.queue_position(0), Some(base!(3)));
+
+        engine.update_book(&l2_snapshot!(vec![], vec![(quote!(101), base!(5))]));
+        assert_eq!(engine.queue_position(0), Some(base!(0)));
+    }
+}
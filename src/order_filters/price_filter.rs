@@ -56,31 +56,36 @@ impl PriceFilter {
     where
         S: Currency,
     {
-        match order.limit_price() {
-            Some(limit_price) => {
-                if limit_price < self.min_price && self.min_price != QuoteCurrency::new_zero() {
-                    return Err(OrderError::LimitPriceBelowMin);
-                }
-                if limit_price > self.max_price && self.max_price != QuoteCurrency::new_zero() {
-                    return Err(OrderError::LimitPriceAboveMax);
-                }
-                if ((limit_price - self.min_price) % self.tick_size) != QuoteCurrency::new_zero() {
-                    return Err(OrderError::InvalidOrderPriceStepSize);
-                }
-                if limit_price > mark_price * self.multiplier_up
-                    && self.multiplier_up != Decimal::ZERO
-                {
-                    return Err(OrderError::LimitPriceAboveMultiple);
-                }
-                if limit_price < mark_price * self.multiplier_down
-                    && self.multiplier_down != Decimal::ZERO
-                {
-                    return Err(OrderError::LimitPriceBelowMultiple);
-                }
-                Ok(())
+        if let Some(limit_price) = order.limit_price() {
+            if limit_price < self.min_price && self.min_price != QuoteCurrency::new_zero() {
+                return Err(OrderError::LimitPriceBelowMin);
+            }
+            if limit_price > self.max_price && self.max_price != QuoteCurrency::new_zero() {
+                return Err(OrderError::LimitPriceAboveMax);
+            }
+            if ((limit_price - self.min_price) % self.tick_size) != QuoteCurrency::new_zero() {
+                return Err(OrderError::InvalidOrderPriceStepSize);
+            }
+            if limit_price > mark_price * self.multiplier_up && self.multiplier_up != Decimal::ZERO
+            {
+                return Err(OrderError::LimitPriceAboveMultiple);
+            }
+            if limit_price < mark_price * self.multiplier_down
+                && self.multiplier_down != Decimal::ZERO
+            {
+                return Err(OrderError::LimitPriceBelowMultiple);
+            }
+        }
+        // The trigger price of trailing stops is set by the exchange.
+        if let (Some(trigger_price), None) = (order.trigger_price(), order.trailing_offset()) {
+            if (trigger_price < self.min_price && self.min_price != QuoteCurrency::new_zero())
+                || (trigger_price > self.max_price && self.max_price != QuoteCurrency::new_zero())
+                || ((trigger_price - self.min_price) % self.tick_size) != QuoteCurrency::new_zero()
+            {
+                return Err(OrderError::InvalidTriggerPrice);
             }
-            None => Ok(()),
         }
+        Ok(())
     }
 
     /// Make sure the market update conforms to the `PriceFilter` rules
@@ -107,27 +112,55 @@ impl PriceFilter {
                 enforce_max_price(self.max_price, *price)?;
                 enforce_step_size(self.tick_size, *price)?;
             }
+            // The `volume` is not validated here, just like for `Trade`.
             MarketUpdate::Candle {
                 bid,
                 ask,
-                low,
+                open,
                 high,
+                low,
+                close,
+                volume: _,
             } => {
-                enforce_min_price(self.min_price, *bid)?;
-                enforce_min_price(self.min_price, *ask)?;
-                enforce_min_price(self.min_price, *low)?;
-                enforce_min_price(self.min_price, *high)?;
-                enforce_max_price(self.max_price, *bid)?;
-                enforce_max_price(self.max_price, *ask)?;
-                enforce_max_price(self.max_price, *low)?;
-                enforce_max_price(self.max_price, *high)?;
-                enforce_step_size(self.tick_size, *bid)?;
-                enforce_step_size(self.tick_size, *ask)?;
-                enforce_step_size(self.tick_size, *low)?;
-                enforce_step_size(self.tick_size, *high)?;
+                for price in [bid, ask, open, high, low, close] {
+                    enforce_min_price(self.min_price, *price)?;
+                    enforce_max_price(self.max_price, *price)?;
+                    enforce_step_size(self.tick_size, *price)?;
+                }
                 enforce_bid_ask_spread(*bid, *ask)?;
                 enforce_bid_ask_spread(*low, *high)?;
+                enforce_within_range(*low, *high, *open)?;
+                enforce_within_range(*low, *high, *close)?;
+            }
+            // The quantity is not validated here, just like for `Trade`.
+            MarketUpdate::L2Update { price, .. } => {
+                enforce_min_price(self.min_price, *price)?;
+                enforce_max_price(self.max_price, *price)?;
+                enforce_step_size(self.tick_size, *price)?;
             }
+            MarketUpdate::L2Snapshot { bids, asks } => {
+                for (price, _) in bids.iter().chain(asks.iter()) {
+                    enforce_min_price(self.min_price, *price)?;
+                    enforce_max_price(self.max_price, *price)?;
+                    enforce_step_size(self.tick_size, *price)?;
+                }
+                let best_bid = bids.iter().map(|(price, _)| *price).max();
+                let best_ask = asks.iter().map(|(price, _)| *price).min();
+                if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+                    enforce_bid_ask_spread(bid, ask)?;
+                }
+            }
+            // The bankruptcy price of another trader does not need to conform to the tick size.
+            MarketUpdate::Liquidation {
+                bankruptcy_price, ..
+            } => {
+                if *bankruptcy_price <= quote!(0) {
+                    return Err(Error::InvalidMarketUpdatePrice);
+                }
+                enforce_min_price(self.min_price, *bankruptcy_price)?;
+                enforce_max_price(self.max_price, *bankruptcy_price)?;
+            }
+            MarketUpdate::Funding { .. } => {}
         }
         Ok(())
     }
@@ -142,6 +175,19 @@ fn enforce_bid_ask_spread(bid: QuoteCurrency, ask: QuoteCurrency) -> Result<(),
     Ok(())
 }
 
+/// Errors if the open or close price of a candle is outside of its range.
+#[inline]
+fn enforce_within_range(
+    low: QuoteCurrency,
+    high: QuoteCurrency,
+    price: QuoteCurrency,
+) -> Result<(), Error> {
+    if price < low || price > high {
+        return Err(Error::InvalidMarketUpdatePrice);
+    }
+    Ok(())
+}
+
 /// Make sure the price is not too low
 /// Disabled if `min_price` == 0
 #[inline]
@@ -176,7 +222,7 @@ mod tests {
     use fpdec::Dec;
 
     use super::*;
-    use crate::prelude::*;
+    use crate::{candle, prelude::*};
 
     #[test]
     fn price_filter() {
@@ -238,5 +284,78 @@ mod tests {
             filter.validate_order(&order, mark_price),
             Err(OrderError::InvalidOrderPriceStepSize)
         );
+
+        // Test trigger price
+        let order = Order::stop_market(
+            Side::Sell,
+            quote!(95.0),
+            base!(0.1),
+            TriggerPriceSource::LastTradePrice,
+        )
+        .unwrap();
+        filter.validate_order(&order, mark_price).unwrap();
+        let order = Order::stop_market(
+            Side::Sell,
+            quote!(95.05),
+            base!(0.1),
+            TriggerPriceSource::LastTradePrice,
+        )
+        .unwrap();
+        assert_eq!(
+            filter.validate_order(&order, mark_price),
+            Err(OrderError::InvalidTriggerPrice)
+        );
+    }
+
+    #[test]
+    fn price_filter_validate_candle() {
+        let filter = PriceFilter::default();
+        filter
+            .validate_market_update(&candle!(
+                quote!(100),
+                quote!(101),
+                quote!(99),
+                quote!(102),
+                quote!(98),
+                quote!(101),
+                base!(5)
+            ))
+            .unwrap();
+        assert_eq!(
+            filter.validate_market_update(&candle!(
+                quote!(100),
+                quote!(101),
+                quote!(97),
+                quote!(102),
+                quote!(98),
+                quote!(101),
+                base!(5)
+            )),
+            Err(Error::InvalidMarketUpdatePrice)
+        );
+        assert_eq!(
+            filter.validate_market_update(&candle!(
+                quote!(100),
+                quote!(101),
+                quote!(99),
+                quote!(102),
+                quote!(98),
+                quote!(103),
+                base!(5)
+            )),
+            Err(Error::InvalidMarketUpdatePrice)
+        );
+        assert_eq!(
+            filter.validate_market_update(&candle!(
+                quote!(100),
+                quote!(101),
+                quote!(99.5),
+                quote!(102),
+                quote!(98),
+                quote!(101),
+                base!(5)
+            )),
+            Err(Error::MarketUpdatePriceStepSize)
+        );
     }
 }
@@ -56,6 +56,11 @@ where
         if ((order.quantity() - self.min_quantity) % self.step_size) != S::new_zero() {
             return Err(OrderError::InvalidQuantityStepSize);
         }
+        if let Some(display_quantity) = order.display_quantity() {
+            if (display_quantity % self.step_size) != S::new_zero() {
+                return Err(OrderError::InvalidQuantityStepSize);
+            }
+        }
         Ok(())
     }
 }
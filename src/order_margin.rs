@@ -8,15 +8,19 @@ use crate::{
 };
 
 /// Compute the current order margin requirement.
+/// `order_group_ids` maps the ids of linked orders to the id of their one-cancels-other group.
 pub(crate) fn compute_order_margin<M>(
     position: &Position<M>,
     active_limit_orders: &HashMap<u64, Order<M::PairedCurrency>>,
+    order_group_ids: &HashMap<u64, u64>,
     fee: Fee,
 ) -> M
 where
     M: Currency + MarginCurrency,
 {
     // New Algo:
+    // 0: Of each group of linked orders at most one can be filled,
+    //    so only the one with the largest notional value is considered.
     // 1: Orders are split into buys and sells
     // 2: They are sorted by ascending price
     // 3: Each side is handled separately
@@ -25,17 +29,42 @@ where
     //  - Anything that cannot be offset: notional value is accumulated
     // 5: For sells its the same but reversed.
 
+    let notional = |order: &Order<M::PairedCurrency>| {
+        order
+            .remaining_quantity()
+            .convert(order.limit_price().expect(EXPECT_LIMIT_PRICE))
+    };
+    let mut orders = Vec::with_capacity(active_limit_orders.len());
+    let mut group_orders = HashMap::<u64, &Order<M::PairedCurrency>>::new();
+    for order in active_limit_orders.values() {
+        let Some(group_id) = order_group_ids.get(&order.id()) else {
+            orders.push(order);
+            continue;
+        };
+        group_orders
+            .entry(*group_id)
+            .and_modify(|other| {
+                if (notional(order), other.id()) > (notional(other), order.id()) {
+                    *other = order;
+                }
+            })
+            .or_insert(order);
+    }
+    orders.extend(group_orders.into_values());
+
     let mut buys = Vec::from_iter(
-        active_limit_orders
-            .values()
+        orders
+            .iter()
+            .copied()
             .filter(|order| matches!(order.side(), Side::Buy)),
     );
     buys.sort_by_key(|order| order.limit_price().expect(EXPECT_LIMIT_PRICE));
     debug!("buys: {:?}", buys);
 
     let mut sells = Vec::from_iter(
-        active_limit_orders
-            .values()
+        orders
+            .iter()
+            .copied()
             .filter(|order| matches!(order.side(), Side::Sell)),
     );
     sells.sort_by_key(|order| order.limit_price().expect(EXPECT_LIMIT_PRICE));
@@ -45,7 +74,7 @@ where
     let mut buy_margin_req = M::new_zero();
     let mut remaining_short_size = min(position.size(), M::PairedCurrency::new_zero()).abs();
     for b in &buys {
-        let mut order_qty = b.quantity();
+        let mut order_qty = b.remaining_quantity();
         if remaining_short_size > M::PairedCurrency::new_zero() {
             // offset the order qty by as much as possible
             let offset = max(order_qty, remaining_short_size);
@@ -61,7 +90,7 @@ where
     let mut sell_margin_req = M::new_zero();
     let mut remaining_long_size = max(position.size(), M::PairedCurrency::new_zero());
     for s in &sells {
-        let mut order_qty = s.quantity();
+        let mut order_qty = s.remaining_quantity();
         if remaining_long_size > M::PairedCurrency::new_zero() {
             // offset the order qty by as much as possible
             let offset = max(order_qty, remaining_long_size);
@@ -88,7 +117,12 @@ mod tests {
         let mut account = Account::new(quote!(1000), leverage!(1), fee);
 
         assert_eq!(
-            compute_order_margin(account.position(), &account.active_limit_orders, fee),
+            compute_order_margin(
+                account.position(),
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(0)
         );
 
@@ -96,7 +130,12 @@ mod tests {
         order.set_id(0);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(90) + quote!(0.018)
         );
 
@@ -104,7 +143,12 @@ mod tests {
         order.set_id(1);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(100) + quote!(0.02)
         );
 
@@ -112,7 +156,12 @@ mod tests {
         order.set_id(2);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(220) + quote!(0.044)
         );
     }
@@ -130,7 +179,12 @@ mod tests {
             leverage: leverage!(1),
         };
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(0)
         );
 
@@ -138,7 +192,12 @@ mod tests {
         order.set_id(0);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(90) + quote!(0.018)
         );
 
@@ -146,7 +205,12 @@ mod tests {
         order.set_id(1);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(90) + quote!(0.018)
         );
 
@@ -154,7 +218,12 @@ mod tests {
         order.set_id(2);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(120) + quote!(0.024)
         );
 
@@ -162,7 +231,12 @@ mod tests {
         order.set_id(3);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(185) + quote!(0.037)
         );
     }
@@ -180,7 +254,12 @@ mod tests {
             leverage: leverage!(1),
         };
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(0)
         );
 
@@ -188,7 +267,12 @@ mod tests {
         order.set_id(0);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(0)
         );
 
@@ -196,7 +280,12 @@ mod tests {
         order.set_id(1);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(100) + quote!(0.02)
         );
 
@@ -204,7 +293,12 @@ mod tests {
         order.set_id(2);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(220) + quote!(0.044)
         );
 
@@ -212,8 +306,50 @@ mod tests {
         order.set_id(3);
         account.append_limit_order(order);
         assert_eq!(
-            compute_order_margin(&account.position, &account.active_limit_orders, fee),
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
             quote!(220) + quote!(0.044)
         );
     }
+
+    #[test]
+    fn order_margin_linked_orders() {
+        let fee = fee!(0.0002);
+        let mut account = Account::new(quote!(1000), leverage!(1), fee);
+        account.link_orders(vec![0, 1]);
+
+        let mut order = Order::limit(Side::Buy, quote!(90), base!(1)).unwrap();
+        order.set_id(0);
+        account.append_limit_order(order);
+        let mut order = Order::limit(Side::Buy, quote!(80), base!(2)).unwrap();
+        order.set_id(1);
+        account.append_limit_order(order);
+        // Only the larger of the linked orders counts.
+        assert_eq!(
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
+            quote!(160) + quote!(0.032)
+        );
+
+        let mut order = Order::limit(Side::Buy, quote!(70), base!(1)).unwrap();
+        order.set_id(2);
+        account.append_limit_order(order);
+        assert_eq!(
+            compute_order_margin(
+                &account.position,
+                &account.active_limit_orders,
+                &account.order_group_ids,
+                fee
+            ),
+            quote!(230) + quote!(0.046)
+        );
+    }
 }
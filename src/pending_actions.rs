@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+use crate::types::{Currency, Order};
+
+/// An order action of the user, which is still on its way to the exchange.
+#[derive(Debug, Clone)]
+pub(crate) enum PendingAction<S>
+where
+    S: Currency,
+{
+    /// A new order with its id already assigned.
+    Submit(Box<Order<S>>),
+    /// A new one-cancels-other group with the ids already assigned.
+    SubmitOco(Vec<Order<S>>),
+    /// A batch of new orders with the ids already assigned and cancellations, which is applied atomically.
+    SubmitBatch {
+        orders: Vec<Order<S>>,
+        cancellations: Vec<u64>,
+    },
+    /// Amend an active limit order to the price and quantity of the amended order.
+    Amend(Box<Order<S>>),
+    /// Cancel an open order.
+    Cancel(u64),
+    /// Cancel several open orders at once.
+    CancelMany(Vec<u64>),
+}
+
+/// The order actions in flight, ordered by their arrival time at the exchange.
+#[derive(Debug, Clone)]
+pub(crate) struct PendingActions<S>
+where
+    S: Currency,
+{
+    actions: VecDeque<(i64, PendingAction<S>)>,
+}
+
+impl<S> PendingActions<S>
+where
+    S: Currency,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            actions: VecDeque::new(),
+        }
+    }
+
+    /// Queue an action, which arrives at `arrival_ts_ns`.
+    /// Actions arriving at the same time are kept in the order they were sent.
+    pub(crate) fn push(&mut self, arrival_ts_ns: i64, action: PendingAction<S>) {
+        let idx = self.actions.partition_point(|(ts, _)| *ts <= arrival_ts_ns);
+        self.actions.insert(idx, (arrival_ts_ns, action));
+    }
+
+    /// Take all actions, which have arrived by `now_ts_ns`, in order of arrival.
+    pub(crate) fn pop_arrived(&mut self, now_ts_ns: i64) -> Vec<PendingAction<S>> {
+        let n = self.actions.partition_point(|(ts, _)| *ts <= now_ts_ns);
+        Vec::from_iter(self.actions.drain(..n).map(|(_, action)| action))
+    }
+
+    /// A submitted order, which has not arrived yet.
+    pub(crate) fn submitted_order(&self, order_id: u64) -> Option<&Order<S>> {
+        self.submitted_orders().find(|order| order.id() == order_id)
+    }
+
+    /// A submitted order with the `user_order_id`, which has not arrived yet.
+    pub(crate) fn submitted_order_by_user_id(&self, user_order_id: u64) -> Option<&Order<S>> {
+        self.submitted_orders()
+            .find(|order| order.user_order_id().is_some_and(|id| id == user_order_id))
+    }
+
+    /// All submitted orders, which have not arrived yet.
+    pub(crate) fn submitted_orders(&self) -> impl Iterator<Item = &Order<S>> {
+        self.actions.iter().flat_map(|(_, action)| match action {
+            PendingAction::Submit(order) => std::slice::from_ref(order.as_ref()),
+            PendingAction::SubmitOco(orders) | PendingAction::SubmitBatch { orders, .. } => {
+                orders.as_slice()
+            }
+            PendingAction::Amend(_) | PendingAction::Cancel(_) | PendingAction::CancelMany(_) => {
+                &[]
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn pending_actions_arrival_order() {
+        let mut pending = PendingActions::<BaseCurrency>::new();
+        pending.push(200, PendingAction::Cancel(0));
+        pending.push(100, PendingAction::Cancel(1));
+        pending.push(200, PendingAction::Cancel(2));
+
+        assert!(pending.pop_arrived(99).is_empty());
+        let arrived = pending.pop_arrived(200);
+        let ids = Vec::from_iter(arrived.iter().map(|action| match action {
+            PendingAction::Cancel(id) => *id,
+            _ => unreachable!(),
+        }));
+        assert_eq!(ids, vec![1, 0, 2]);
+        assert!(pending.pop_arrived(i64::MAX).is_empty());
+    }
+
+    #[test]
+    fn pending_actions_submitted_order() {
+        let mut pending = PendingActions::<BaseCurrency>::new();
+        let mut order = Order::limit(Side::Buy, quote!(100), base!(1)).unwrap();
+        order.set_id(5);
+        order.set_user_order_id(7);
+        pending.push(100, PendingAction::Submit(Box::new(order.clone())));
+
+        assert_eq!(pending.submitted_order(5), Some(&order));
+        assert_eq!(pending.submitted_order_by_user_id(7), Some(&order));
+        assert_eq!(pending.submitted_order(6), None);
+    }
+}
@@ -79,6 +79,34 @@ where
         }
     }
 
+    /// Return the price at which the loss of the position equals its position margin.
+    /// None, if there is no position or if no price can lose the whole position margin.
+    pub fn bankruptcy_price(&self) -> Option<QuoteCurrency> {
+        M::exit_price(
+            self.entry_price,
+            self.position_margin.into_negative(),
+            self.size,
+        )
+    }
+
+    /// Return the price at which only the maintenance margin is left of the position margin,
+    /// and the position gets liquidated.
+    /// None, if there is no position or if no price can cause a liquidation.
+    ///
+    /// # Arguments:
+    /// `maintenance_margin`: The required maintenance margin as a fraction of the position value at entry.
+    pub fn liquidation_price(&self, maintenance_margin: Decimal) -> Option<QuoteCurrency> {
+        if self.size.is_zero() {
+            return None;
+        }
+        let maint_margin = self.size.abs().convert(self.entry_price) * maintenance_margin;
+        M::exit_price(
+            self.entry_price,
+            maint_margin - self.position_margin,
+            self.size,
+        )
+    }
+
     /// Create a new position with all fields custom.
     ///
     /// # Arguments:
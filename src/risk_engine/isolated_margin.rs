@@ -1,10 +1,16 @@
+use std::cmp::min;
+
+use fpdec::Quantize;
+use hashbrown::HashMap;
+
 use super::{risk_engine_trait::RiskError, RiskEngine};
 use crate::{
     contract_specification::ContractSpecification,
     market_state::MarketState,
     order_margin::compute_order_margin,
+    position::Position,
     prelude::Account,
-    types::{Currency, MarginCurrency, Order, OrderType, QuoteCurrency, Side},
+    types::{Currency, LiquidationMode, MarginCurrency, Order, OrderType, QuoteCurrency, Side},
 };
 
 #[derive(Debug, Clone)]
@@ -13,14 +19,21 @@ where
     M: Currency + MarginCurrency,
 {
     contract_spec: ContractSpecification<M::PairedCurrency>,
+    liquidation_mode: LiquidationMode,
 }
 
 impl<M> IsolatedMarginRiskEngine<M>
 where
     M: Currency + MarginCurrency,
 {
-    pub(crate) fn new(contract_spec: ContractSpecification<M::PairedCurrency>) -> Self {
-        Self { contract_spec }
+    pub(crate) fn new(
+        contract_spec: ContractSpecification<M::PairedCurrency>,
+        liquidation_mode: LiquidationMode,
+    ) -> Self {
+        Self {
+            contract_spec,
+            liquidation_mode,
+        }
     }
 }
 
@@ -34,7 +47,13 @@ where
         order: &Order<M::PairedCurrency>,
         fill_price: QuoteCurrency,
     ) -> Result<(), RiskError> {
-        debug_assert!(matches!(order.order_type(), OrderType::Market));
+        debug_assert!(matches!(
+            order.order_type(),
+            OrderType::Market
+                | OrderType::StopMarket
+                | OrderType::TakeProfitMarket
+                | OrderType::TrailingStopMarket
+        ));
         match order.side() {
             Side::Buy => self.handle_market_buy_order(account, order, fill_price),
             Side::Sell => self.handle_market_sell_order(account, order, fill_price),
@@ -46,26 +65,18 @@ where
         account: &Account<M>,
         order: &Order<<M as Currency>::PairedCurrency>,
     ) -> Result<(), RiskError> {
-        debug_assert!(matches!(order.order_type(), OrderType::Limit));
+        debug_assert!(matches!(
+            order.order_type(),
+            OrderType::Limit | OrderType::StopLimit | OrderType::TakeProfitLimit
+        ));
 
         let mut orders = account.active_limit_orders.clone();
         orders.insert(order.id(), order.clone());
-        let new_order_margin =
-            compute_order_margin(&account.position, &orders, self.contract_spec.fee_maker);
-
-        // TODO: this calculation does not allow a fully loaded long (or short) position
-        // to be reversed into the opposite position of the same size,
-        // which should be possible and requires a slightly modified calculation that
-        let available_balance = account.wallet_balance - account.position.position_margin;
-        debug!(
-            "new_order_margin: {}, available_balance: {}",
-            new_order_margin, available_balance
-        );
-        if new_order_margin > available_balance {
-            return Err(RiskError::NotEnoughAvailableBalance);
-        }
+        self.check_order_margin_of(account, &orders)
+    }
 
-        Ok(())
+    fn check_order_margin(&self, account: &Account<M>) -> Result<(), RiskError> {
+        self.check_order_margin_of(account, &account.active_limit_orders)
     }
 
     fn check_maintenance_margin(
@@ -73,20 +84,16 @@ where
         market_state: &MarketState,
         account: &Account<M>,
     ) -> Result<(), RiskError> {
-        if account.position.size() == M::PairedCurrency::new_zero() {
+        let Some(liquidation_price) = self.liquidation_price(&account.position) else {
             return Ok(());
-        }
-        let pos_value = account
-            .position()
-            .size()
-            .abs()
-            .convert(market_state.mid_price());
-        let maint_margin = account
-            .position()
-            .size()
-            .convert(account.position.entry_price)
-            * self.contract_spec.maintenance_margin;
-        if pos_value < maint_margin {
+        };
+        let price = market_state.mid_price();
+        let liquidate = if account.position.size() > M::PairedCurrency::new_zero() {
+            price <= liquidation_price
+        } else {
+            price >= liquidation_price
+        };
+        if liquidate {
             return Err(RiskError::Liquidate);
         }
 
@@ -98,13 +105,100 @@ impl<M> IsolatedMarginRiskEngine<M>
 where
     M: Currency + MarginCurrency,
 {
+    /// The price at which the `position` gets liquidated, rounded to the tick size towards its entry price.
+    pub(crate) fn liquidation_price(&self, position: &Position<M>) -> Option<QuoteCurrency> {
+        position
+            .liquidation_price(self.contract_spec.maintenance_margin)
+            .map(|price| self.round_towards_entry(position, price))
+    }
+
+    /// The price at which the `position` loses its whole position margin,
+    /// rounded to the tick size towards its entry price.
+    pub(crate) fn bankruptcy_price(&self, position: &Position<M>) -> Option<QuoteCurrency> {
+        position
+            .bankruptcy_price()
+            .map(|price| self.round_towards_entry(position, price))
+    }
+
+    /// The quantity to close in the next step of a liquidation, according to the `LiquidationMode`.
+    ///
+    /// # Arguments:
+    /// `position`: The position, which is being liquidated.
+    /// `initial_size`: The absolute position size at the start of the liquidation.
+    pub(crate) fn liquidation_quantity(
+        &self,
+        position: &Position<M>,
+        initial_size: M::PairedCurrency,
+    ) -> M::PairedCurrency {
+        let size = position.size().abs();
+        match self.liquidation_mode {
+            LiquidationMode::Full => size,
+            LiquidationMode::Partial { fraction } => {
+                let step_size = self.contract_spec.quantity_filter.step_size;
+                let mut quantity = initial_size * fraction;
+                let remainder = quantity % step_size;
+                if remainder != M::PairedCurrency::new_zero() {
+                    quantity = quantity - remainder + step_size;
+                }
+                min(quantity, size)
+            }
+        }
+    }
+
+    /// Round the `price` to the tick size, such that the loss of the `position` is never larger.
+    fn round_towards_entry(&self, position: &Position<M>, price: QuoteCurrency) -> QuoteCurrency {
+        let tick_size = self.contract_spec.price_filter.tick_size;
+        let rounded = QuoteCurrency::new(price.inner().quantize(tick_size.inner()));
+        if position.size() > M::PairedCurrency::new_zero() && rounded < price {
+            rounded + tick_size
+        } else if position.size() < M::PairedCurrency::new_zero() && rounded > price {
+            rounded - tick_size
+        } else {
+            rounded
+        }
+    }
+
+    /// Check the order margin the account would require with the given active limit `orders`.
+    fn check_order_margin_of(
+        &self,
+        account: &Account<M>,
+        orders: &HashMap<u64, Order<M::PairedCurrency>>,
+    ) -> Result<(), RiskError> {
+        let new_order_margin = compute_order_margin(
+            &account.position,
+            orders,
+            &account.order_group_ids,
+            self.contract_spec.fee_maker,
+        );
+
+        // TODO: this calculation does not allow a fully loaded long (or short) position
+        // to be reversed into the opposite position of the same size,
+        // which should be possible and requires a slightly modified calculation that
+        let available_balance = account.wallet_balance - account.position.position_margin;
+        debug!(
+            "new_order_margin: {}, available_balance: {}",
+            new_order_margin, available_balance
+        );
+        if new_order_margin > available_balance {
+            return Err(RiskError::NotEnoughAvailableBalance);
+        }
+
+        Ok(())
+    }
+
     fn handle_market_buy_order(
         &self,
         account: &Account<M>,
         order: &Order<M::PairedCurrency>,
         fill_price: QuoteCurrency,
     ) -> Result<(), RiskError> {
-        debug_assert!(matches!(order.order_type(), OrderType::Market));
+        debug_assert!(matches!(
+            order.order_type(),
+            OrderType::Market
+                | OrderType::StopMarket
+                | OrderType::TakeProfitMarket
+                | OrderType::TrailingStopMarket
+        ));
         debug_assert!(matches!(order.side(), Side::Buy));
 
         if account.position.size() >= M::PairedCurrency::new_zero() {
@@ -142,7 +236,13 @@ where
         order: &Order<M::PairedCurrency>,
         fill_price: QuoteCurrency,
     ) -> Result<(), RiskError> {
-        debug_assert!(matches!(order.order_type(), OrderType::Market));
+        debug_assert!(matches!(
+            order.order_type(),
+            OrderType::Market
+                | OrderType::StopMarket
+                | OrderType::TakeProfitMarket
+                | OrderType::TrailingStopMarket
+        ));
         debug_assert!(matches!(order.side(), Side::Sell));
 
         if account.position.size() <= M::PairedCurrency::new_zero() {
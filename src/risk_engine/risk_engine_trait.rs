@@ -47,6 +47,10 @@ where
         order: &Order<M::PairedCurrency>,
     ) -> Result<(), RiskError>;
 
+    /// Checks if the account is able to satisfy the combined order margin of all its active limit orders,
+    /// e.g. after several orders have been placed and cancelled at once.
+    fn check_order_margin(&self, account: &Account<M>) -> Result<(), RiskError>;
+
     /// Ensure the account has enough maintenance margin, to keep the position open.
     /// The maintenance margin is the minimum amount of funds that must be maintained in a trader's account
     /// to ensure that they can meet any losses that may occur due to adverse price movements in the futures contract.
@@ -0,0 +1,150 @@
+use fpdec::Decimal;
+
+use super::mock_exchange_with;
+use crate::{
+    account_tracker::FullAccountTracker, l2_snapshot, l2_update, mock_exchange_base, prelude::*,
+    risk_engine::RiskError, trade,
+};
+
+#[test]
+fn amend_order_queue_priority() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(
+            0,
+            l2_snapshot!(
+                vec![(quote!(100), base!(5)), (quote!(99), base!(2))],
+                vec![(quote!(101), base!(5))]
+            ),
+        )
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(2)).unwrap())
+        .unwrap();
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(5))
+    );
+    // More liquidity joins the level behind the order.
+    exchange
+        .update_state(1, l2_update!(Side::Buy, quote!(100), base!(8)))
+        .unwrap();
+
+    // A size decrease keeps the priority.
+    let amended = exchange
+        .amend_order(order.id(), quote!(100), base!(1))
+        .unwrap();
+    assert_eq!(amended.id(), order.id());
+    assert_eq!(amended.quantity(), base!(1));
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(5))
+    );
+    assert_eq!(exchange.account().order_margin(), quote!(100.02));
+
+    // A size increase loses it.
+    exchange
+        .amend_order(order.id(), quote!(100), base!(3))
+        .unwrap();
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(8))
+    );
+
+    // So does a price change.
+    exchange
+        .amend_order(order.id(), quote!(99), base!(3))
+        .unwrap();
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(2))
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(
+        exchange.account().active_limit_orders()[&order.id()].limit_price(),
+        Some(quote!(99))
+    );
+}
+
+#[test]
+fn amend_order_partially_filled() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(2)).unwrap())
+        .unwrap();
+    exchange
+        .update_state(1, trade!(quote!(100), base!(1), Side::Sell))
+        .unwrap();
+
+    assert_eq!(
+        exchange.amend_order(order.id(), quote!(100), base!(1)),
+        Err(Error::OrderError(OrderError::AmendedQuantityBelowFilled))
+    );
+    let amended = exchange
+        .amend_order(order.id(), quote!(100), base!(4))
+        .unwrap();
+    assert_eq!(amended.filled_quantity(), base!(1));
+    assert_eq!(amended.remaining_quantity(), base!(3));
+    assert_eq!(exchange.account().order_margin(), quote!(300.06));
+}
+
+#[test]
+fn amend_order_rejected() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(2)).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        exchange.amend_order(order.id() + 1, quote!(100), base!(1)),
+        Err(Error::OrderIdNotFound)
+    );
+    assert_eq!(
+        exchange.amend_order(order.id(), quote!(101), base!(2)),
+        Err(Error::OrderError(OrderError::LimitPriceAboveAsk))
+    );
+    assert_eq!(
+        exchange.amend_order(order.id(), quote!(100), base!(2.001)),
+        Err(Error::OrderError(OrderError::InvalidQuantityStepSize))
+    );
+    assert_eq!(
+        exchange.amend_order(order.id(), quote!(100), base!(20)),
+        Err(Error::RiskError(RiskError::NotEnoughAvailableBalance))
+    );
+    // The order remains unchanged.
+    assert_eq!(exchange.account().active_limit_orders()[&order.id()], order);
+}
+
+#[test]
+fn amend_order_account_tracker() {
+    let mut exchange = mock_exchange_with(
+        FullAccountTracker::new(quote!(1000)),
+        PessimisticFillModel::default(),
+        |_| {},
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(2)).unwrap())
+        .unwrap();
+    exchange
+        .amend_order(order.id(), quote!(99), base!(2))
+        .unwrap();
+    exchange
+        .amend_order(order.id(), quote!(99), base!(1))
+        .unwrap();
+
+    assert_eq!(exchange.account_tracker().num_submitted_limit_orders(), 1);
+    assert_eq!(exchange.account_tracker().num_amended_limit_orders(), 2);
+    assert_eq!(
+        exchange.account_tracker().limit_order_cancellation_ratio(),
+        0.0
+    );
+}
@@ -0,0 +1,208 @@
+use super::{configure_insurance_fund, mock_exchange_liquidatable};
+use crate::{liquidation, mock_exchange_base, prelude::*};
+
+#[test]
+fn auto_deleverage_first_in_queue() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(config, quote!(0), LossPolicy::AutoDeleverage)
+    });
+    exchange
+        .update_state(1, bba!(quote!(110), quote!(111)))
+        .unwrap();
+    let wallet_balance = exchange.account().wallet_balance();
+
+    // A short liquidated with a bankruptcy price of 105 can only be closed at the ask of 111.
+    let (_, fills) = exchange
+        .update_state(2, liquidation!(Side::Buy, quote!(105), base!(2)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].side(), Side::Sell);
+    assert_eq!(fills[0].quantity(), base!(2));
+    assert_eq!(fills[0].price(), quote!(105));
+    assert_eq!(fills[0].fee(), quote!(0));
+    assert_eq!(fills[0].realized_pnl(), quote!(8));
+    assert_eq!(exchange.account().position().size(), base!(3));
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        wallet_balance + quote!(8)
+    );
+    assert_eq!(exchange.insurance_fund().balance(), quote!(0));
+    assert_eq!(exchange.account_tracker().num_auto_deleverages(), 1);
+    assert_eq!(exchange.account_tracker().num_liquidations(), 0);
+}
+
+#[test]
+fn auto_deleverage_only_uncovered_part() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(config, quote!(6), LossPolicy::AutoDeleverage)
+    });
+    exchange
+        .update_state(1, bba!(quote!(110), quote!(111)))
+        .unwrap();
+
+    // The insurance fund covers the shortfall of one half of the liquidation.
+    let (_, fills) = exchange
+        .update_state(2, liquidation!(Side::Buy, quote!(105), base!(2)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].quantity(), base!(1));
+    assert_eq!(exchange.account().position().size(), base!(4));
+    assert_eq!(exchange.insurance_fund().balance(), quote!(0));
+
+    // A surplus refills the insurance fund and deleverages no one.
+    let (_, fills) = exchange
+        .update_state(3, liquidation!(Side::Buy, quote!(113), base!(2)))
+        .unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(exchange.insurance_fund().balance(), quote!(4));
+    assert_eq!(exchange.account().position().size(), base!(4));
+    assert_eq!(exchange.account_tracker().num_auto_deleverages(), 1);
+}
+
+#[test]
+fn auto_deleverage_fixed_rank() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(config, quote!(0), LossPolicy::AutoDeleverage);
+        config
+            .set_adl_rank(AdlRank::Fixed {
+                quantity_ahead: base!(1.5),
+            })
+            .unwrap();
+    });
+    exchange
+        .update_state(1, bba!(quote!(110), quote!(111)))
+        .unwrap();
+
+    let (_, fills) = exchange
+        .update_state(2, liquidation!(Side::Buy, quote!(105), base!(2)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].quantity(), base!(0.5));
+    assert_eq!(exchange.account().position().size(), base!(4.5));
+
+    // The queue does not reach the account.
+    let (_, fills) = exchange
+        .update_state(3, liquidation!(Side::Buy, quote!(105), base!(1)))
+        .unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(exchange.account().position().size(), base!(4.5));
+}
+
+#[test]
+fn auto_deleverage_synthetic_population() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(config, quote!(0), LossPolicy::AutoDeleverage);
+        config
+            .set_adl_rank(AdlRank::Population(vec![
+                // More profitable at the same leverage, so it is ranked ahead of the account.
+                SyntheticPosition {
+                    size: base!(1.5),
+                    entry_price: quote!(100),
+                    leverage: leverage!(5),
+                },
+                // Less profitable at a lower leverage.
+                SyntheticPosition {
+                    size: base!(3),
+                    entry_price: quote!(105),
+                    leverage: leverage!(1),
+                },
+                // Not profitable at all.
+                SyntheticPosition {
+                    size: base!(10),
+                    entry_price: quote!(120),
+                    leverage: leverage!(10),
+                },
+            ]))
+            .unwrap();
+    });
+    exchange
+        .update_state(1, bba!(quote!(110), quote!(111)))
+        .unwrap();
+
+    let (_, fills) = exchange
+        .update_state(2, liquidation!(Side::Buy, quote!(105), base!(2)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].quantity(), base!(0.5));
+    assert_eq!(exchange.account().position().size(), base!(4.5));
+}
+
+#[test]
+fn auto_deleverage_only_opposing_profitable_positions() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(config, quote!(0), LossPolicy::AutoDeleverage)
+    });
+    exchange
+        .update_state(1, bba!(quote!(110), quote!(111)))
+        .unwrap();
+
+    // A liquidated long is opposed by shorts.
+    let (_, fills) = exchange
+        .update_state(2, liquidation!(Side::Sell, quote!(115), base!(2)))
+        .unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(exchange.account().position().size(), base!(5));
+
+    // Once the long is at a loss, it is not deleveraged either.
+    exchange
+        .update_state(3, bba!(quote!(90), quote!(91)))
+        .unwrap();
+    let (_, fills) = exchange
+        .update_state(4, liquidation!(Side::Buy, quote!(85), base!(2)))
+        .unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(exchange.account().position().size(), base!(5));
+    assert_eq!(exchange.account_tracker().num_auto_deleverages(), 0);
+}
+
+#[test]
+fn auto_deleverage_other_loss_policies() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(
+            config,
+            quote!(0),
+            LossPolicy::SocializedLoss { share: Dec!(0.1) },
+        )
+    });
+    exchange
+        .update_state(1, bba!(quote!(110), quote!(111)))
+        .unwrap();
+    let wallet_balance = exchange.account().wallet_balance();
+
+    // The account pays its share of the shortfall of another trader, instead of being deleveraged.
+    let (_, fills) = exchange
+        .update_state(2, liquidation!(Side::Buy, quote!(106), base!(2)))
+        .unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        wallet_balance - quote!(1)
+    );
+    assert_eq!(exchange.account_tracker().num_auto_deleverages(), 0);
+}
+
+#[test]
+fn auto_deleverage_invalid_config() {
+    let mut config = mock_exchange_base().config().clone();
+    assert_eq!(
+        config.set_adl_rank(AdlRank::Fixed {
+            quantity_ahead: base!(-1),
+        }),
+        Err(Error::InvalidAdlRank)
+    );
+    assert_eq!(
+        config.set_adl_rank(AdlRank::Population(vec![SyntheticPosition {
+            size: base!(0),
+            entry_price: quote!(100),
+            leverage: leverage!(1),
+        }])),
+        Err(Error::InvalidAdlRank)
+    );
+    assert_eq!(config.adl_rank(), &AdlRank::default());
+
+    let mut exchange = mock_exchange_base();
+    assert_eq!(
+        exchange.update_state(0, liquidation!(Side::Sell, quote!(0), base!(1))),
+        Err(Error::InvalidMarketUpdatePrice)
+    );
+}
@@ -0,0 +1,349 @@
+use crate::{candle, mock_exchange_base, prelude::*, trade};
+
+fn bracket(
+    exchange: &mut Exchange<crate::account_tracker::NoAccountTracker, BaseCurrency>,
+    entry: Order<BaseCurrency>,
+) -> Vec<Order<BaseCurrency>> {
+    exchange
+        .submit_bracket(
+            entry,
+            Order::limit(Side::Sell, quote!(110), base!(2)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(2),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .0
+}
+
+#[test]
+fn bracket_order_take_profit() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let orders = bracket(
+        &mut exchange,
+        Order::limit(Side::Buy, quote!(100), base!(2)).unwrap(),
+    );
+    assert_eq!(orders.len(), 3);
+    assert!(orders[1].reduce_only());
+    assert!(orders[2].reduce_only());
+    // The children wait for the entry to be filled.
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(
+        exchange.account().pending_child_orders()[&orders[0].id()].len(),
+        2
+    );
+
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(100), base!(2), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(exchange.account().position.size, base!(2));
+    assert!(exchange.account().pending_child_orders().is_empty());
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&orders[1].id()));
+    assert!(exchange
+        .account()
+        .untriggered_orders()
+        .contains_key(&orders[2].id()));
+    assert_eq!(
+        exchange.account().order_groups()[&orders[1].id()],
+        vec![orders[1].id(), orders[2].id()]
+    );
+
+    exchange
+        .update_state(2, bba!(quote!(109), quote!(110)))
+        .unwrap();
+    let (executed, _) = exchange
+        .update_state(3, trade!(quote!(110), base!(2), Side::Buy))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), orders[1].id());
+    assert_eq!(exchange.account().position.size, base!(0));
+    // The stop loss got cancelled.
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert!(exchange.account().order_groups().is_empty());
+}
+
+#[test]
+fn bracket_order_market_entry() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let orders = bracket(&mut exchange, Order::market(Side::Buy, base!(2)).unwrap());
+    // The entry is filled immediately, so the children are active right away.
+    assert_eq!(exchange.account().position.size, base!(2));
+    assert!(exchange.account().pending_child_orders().is_empty());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().untriggered_orders().len(), 1);
+
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(95), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), orders[2].id());
+    assert_eq!(exchange.account().position.size, base!(0));
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn bracket_order_cancel_entry() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let orders = bracket(
+        &mut exchange,
+        Order::limit(Side::Buy, quote!(100), base!(2)).unwrap(),
+    );
+    exchange.cancel_order(orders[0].id()).unwrap();
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().pending_child_orders().is_empty());
+    assert_eq!(
+        exchange.cancel_order(orders[1].id()),
+        Err(Error::OrderIdNotFound)
+    );
+}
+
+#[test]
+fn bracket_order_rejected() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    assert_eq!(
+        exchange.submit_bracket(
+            Order::limit(Side::Buy, quote!(100), base!(2)).unwrap(),
+            Order::limit(Side::Buy, quote!(110), base!(2)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(2),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        ),
+        Err(Error::OrderError(OrderError::InvalidOrderGroup))
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn bracket_order_partial_fill_grows_children() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let orders = bracket(
+        &mut exchange,
+        Order::limit(Side::Buy, quote!(100), base!(2)).unwrap(),
+    );
+
+    // The first fill activates the children, covering only the filled quantity.
+    exchange
+        .update_state(1, trade!(quote!(100), base!(0.5), Side::Sell))
+        .unwrap();
+    assert_eq!(exchange.account().position.size, base!(0.5));
+    assert!(exchange.account().pending_child_orders().is_empty());
+    assert_eq!(
+        exchange.account().active_limit_orders()[&orders[1].id()].quantity(),
+        base!(0.5)
+    );
+    assert_eq!(
+        exchange.account().untriggered_orders()[&orders[2].id()].quantity(),
+        base!(0.5)
+    );
+
+    // Further fills grow the children.
+    exchange
+        .update_state(2, trade!(quote!(100), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(exchange.account().position.size, base!(1.5));
+    assert_eq!(
+        exchange.account().active_limit_orders()[&orders[1].id()].quantity(),
+        base!(1.5)
+    );
+    assert_eq!(
+        exchange.account().untriggered_orders()[&orders[2].id()].quantity(),
+        base!(1.5)
+    );
+
+    // Cancelling the rest of the entry keeps the children, which protect the position.
+    exchange.cancel_order(orders[0].id()).unwrap();
+    assert!(!exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&orders[0].id()));
+    assert!(exchange.account().bracket_child_orders.is_empty());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().untriggered_orders().len(), 1);
+
+    let (executed, _) = exchange
+        .update_state(3, trade!(quote!(95), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), orders[2].id());
+    assert_eq!(exchange.account().position.size, base!(0));
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn bracket_order_partial_fill_expired_entry() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut entry = Order::limit(Side::Buy, quote!(100), base!(2)).unwrap();
+    entry.set_expiry_timestamp_ns(2);
+    let orders = bracket(&mut exchange, entry);
+    exchange
+        .update_state(1, trade!(quote!(100), base!(1), Side::Sell))
+        .unwrap();
+
+    // The entry expires, but the children covering its partial fill stay active.
+    let (executed, _) = exchange
+        .update_state(3, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert!(executed[0].is_expired());
+    assert_eq!(
+        exchange.account().active_limit_orders()[&orders[1].id()].quantity(),
+        base!(1)
+    );
+    assert_eq!(
+        exchange.account().untriggered_orders()[&orders[2].id()].quantity(),
+        base!(1)
+    );
+}
+
+#[test]
+fn bracket_order_children_rejected_on_activation() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    // The take profit would execute right away against the bid, once the entry is filled.
+    let (orders, _) = exchange
+        .submit_bracket(
+            Order::market(Side::Buy, base!(2)).unwrap(),
+            Order::limit(Side::Sell, quote!(100), base!(2)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(2),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(orders[0].rejection(), None);
+    let rejection = Some(&Error::OrderError(OrderError::LimitPriceBelowBid));
+    assert_eq!(orders[1].rejection(), rejection);
+    assert_eq!(orders[2].rejection(), rejection);
+    assert_eq!(exchange.account().position.size, base!(2));
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().untriggered_orders().is_empty());
+
+    // A fill by a candle reports the rejected children of the entry.
+    let orders = bracket(
+        &mut exchange,
+        Order::limit(Side::Buy, quote!(100), base!(2)).unwrap(),
+    );
+    let (executed, _) = exchange
+        .update_state(
+            1,
+            candle!(
+                quote!(111),
+                quote!(112),
+                quote!(100),
+                quote!(112),
+                quote!(99),
+                quote!(111),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 3);
+    assert_eq!(executed[0].id(), orders[0].id());
+    assert_eq!(executed[0].rejection(), None);
+    assert_eq!(executed[1].id(), orders[1].id());
+    assert_eq!(
+        executed[1].rejection(),
+        Some(&Error::OrderError(OrderError::LimitPriceBelowBid))
+    );
+    assert_eq!(executed[2].id(), orders[2].id());
+    assert!(executed[2].rejection().is_some());
+    assert!(exchange.account().order_groups().is_empty());
+}
+
+#[test]
+fn bracket_order_marketable_entry_fills() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (orders, fills) = exchange
+        .submit_bracket(
+            Order::market(Side::Buy, base!(2)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(2)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(2),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].order_id(), orders[0].id());
+    assert_eq!(fills[0].side(), Side::Buy);
+    assert_eq!(fills[0].quantity(), base!(2));
+    assert_eq!(fills[0].price(), quote!(101));
+    assert_eq!(fills[0].fee(), quote!(0.1212));
+    assert!(!fills[0].is_maker());
+    assert_eq!(fills[0].realized_pnl(), quote!(0));
+    // The children are active right away.
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&orders[1].id()));
+    assert!(exchange
+        .account()
+        .untriggered_orders()
+        .contains_key(&orders[2].id()));
+
+    // A resting entry has no fills yet.
+    let (_, fills) = exchange
+        .submit_bracket(
+            Order::limit(Side::Buy, quote!(99), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(1)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert!(fills.is_empty());
+}
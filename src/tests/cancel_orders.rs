@@ -0,0 +1,151 @@
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, mock_exchange_base, prelude::*, trade};
+
+/// Place two limit buys, a limit sell and a stop order.
+fn place_orders(exchange: &mut Exchange<NoAccountTracker, BaseCurrency>) -> Vec<u64> {
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let orders = [
+        Order::limit(Side::Buy, quote!(100), base!(1)).unwrap(),
+        Order::limit(Side::Buy, quote!(98), base!(1)).unwrap(),
+        Order::limit(Side::Sell, quote!(102), base!(1)).unwrap(),
+        Order::stop_market(
+            Side::Buy,
+            quote!(110),
+            base!(1),
+            TriggerPriceSource::LastTradePrice,
+        )
+        .unwrap(),
+    ];
+    Vec::from_iter(
+        orders
+            .into_iter()
+            .map(|order| exchange.submit_order(order).unwrap().0.id()),
+    )
+}
+
+#[test]
+fn cancel_all_orders() {
+    let mut exchange = mock_exchange_base();
+    let ids = place_orders(&mut exchange);
+    assert_eq!(exchange.account().active_limit_orders().len(), 3);
+
+    let cancelled = exchange.cancel_all_orders().unwrap();
+    assert_eq!(
+        Vec::from_iter(cancelled.iter().map(|order| order.id())),
+        ids
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+    assert_eq!(exchange.account().available_balance(), quote!(1000));
+    for id in ids {
+        assert_eq!(exchange.matching_engine().queue_position(id), None);
+    }
+
+    assert!(exchange.cancel_all_orders().unwrap().is_empty());
+}
+
+#[test]
+fn cancel_orders_by_side() {
+    let mut exchange = mock_exchange_base();
+    let ids = place_orders(&mut exchange);
+
+    let cancelled = exchange.cancel_orders_by_side(Side::Buy).unwrap();
+    assert_eq!(
+        Vec::from_iter(cancelled.iter().map(|order| order.id())),
+        vec![ids[0], ids[1], ids[3]]
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&ids[2]));
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(102.0204));
+
+    // The remaining sell order still gets filled.
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(103), base!(1), Side::Buy))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), ids[2]);
+}
+
+#[test]
+fn cancel_orders_matching() {
+    let mut exchange = mock_exchange_base();
+    let ids = place_orders(&mut exchange);
+
+    let cancelled = exchange
+        .cancel_orders_matching(|order| {
+            order.limit_price().is_some_and(|price| price < quote!(101))
+        })
+        .unwrap();
+    assert_eq!(
+        Vec::from_iter(cancelled.iter().map(|order| order.id())),
+        vec![ids[0], ids[1]]
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().untriggered_orders().len(), 1);
+}
+
+#[test]
+fn cancel_all_orders_with_bracket() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_bracket(
+            Order::limit(Side::Buy, quote!(100), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(1)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(90),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let cancelled = exchange.cancel_all_orders().unwrap();
+    assert_eq!(cancelled.len(), 1);
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().pending_child_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+}
+
+#[test]
+fn cancel_all_orders_with_latency() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    let ids = place_orders(&mut exchange);
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 3);
+
+    // An order which has not arrived yet is cancelled as well.
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+    let cancelled = exchange.cancel_all_orders().unwrap();
+    assert_eq!(
+        Vec::from_iter(cancelled.iter().map(|order| order.id())),
+        vec![ids[0], ids[1], ids[2], ids[3], order.id()]
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 3);
+
+    exchange
+        .update_state(200, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+}
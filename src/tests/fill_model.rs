@@ -0,0 +1,314 @@
+use fpdec::{Dec, Decimal};
+
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, candle, mock_exchange_base, prelude::*};
+
+/// Enter a long position, protected by a take profit at 105 and a stop loss at 95.
+fn enter_protected_long<F>(exchange: &mut Exchange<NoAccountTracker, BaseCurrency, F>) -> Vec<u64>
+where
+    F: FillModel,
+{
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+    let orders = exchange
+        .submit_oco(vec![
+            Order::limit(Side::Sell, quote!(105), base!(1)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        ])
+        .unwrap();
+    Vec::from_iter(orders.iter().map(|order| order.id()))
+}
+
+#[test]
+fn fill_model_default_requires_trade_through() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(98), base!(1)).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        exchange
+            .update_state(
+                1,
+                candle!(
+                    quote!(100),
+                    quote!(101),
+                    quote!(100),
+                    quote!(102),
+                    quote!(98),
+                    quote!(100),
+                    base!(10)
+                )
+            )
+            .unwrap()
+            .0,
+        vec![]
+    );
+    let (executed, _) = exchange
+        .update_state(
+            2,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(102),
+                quote!(97),
+                quote!(100),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(exchange.account().position().size(), base!(1));
+}
+
+#[test]
+fn fill_model_optimistic_fills_on_touch() {
+    let mut exchange = mock_exchange_with(NoAccountTracker, OptimisticFillModel::default(), |_| {});
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(98), base!(1)).unwrap())
+        .unwrap();
+
+    let (executed, _) = exchange
+        .update_state(
+            1,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(102),
+                quote!(98),
+                quote!(100),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(exchange.account().position().size(), base!(1));
+}
+
+#[test]
+fn fill_model_pessimistic_trade_through_ticks() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::new(3, IntrabarPath::OpenHighLowClose),
+        |_| {},
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(98), base!(1)).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        exchange
+            .update_state(
+                1,
+                candle!(
+                    quote!(100),
+                    quote!(101),
+                    quote!(100),
+                    quote!(102),
+                    quote!(96),
+                    quote!(100),
+                    base!(10)
+                )
+            )
+            .unwrap()
+            .0,
+        vec![]
+    );
+    let (executed, _) = exchange
+        .update_state(
+            2,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(102),
+                quote!(95),
+                quote!(100),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+}
+
+#[test]
+fn fill_model_intrabar_path_high_first() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::new(1, IntrabarPath::OpenHighLowClose),
+        |_| {},
+    );
+    let ids = enter_protected_long(&mut exchange);
+
+    // Both the take profit and the stop loss are hit, but the high comes first.
+    let (executed, _) = exchange
+        .update_state(
+            1,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(110),
+                quote!(90),
+                quote!(100),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), ids[0]);
+    assert_eq!(
+        executed[0].filled(),
+        Filled::Yes {
+            fill_price: quote!(105)
+        }
+    );
+    assert_eq!(exchange.account().position().size(), base!(0));
+    assert!(exchange.account().untriggered_orders().is_empty());
+}
+
+#[test]
+fn fill_model_intrabar_path_low_first() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        ProbabilisticFillModel::new(0, IntrabarPath::OpenLowHighClose, 0),
+        |_| {},
+    );
+    let ids = enter_protected_long(&mut exchange);
+
+    // Both the take profit and the stop loss are hit, but the low comes first.
+    let (executed, _) = exchange
+        .update_state(
+            1,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(110),
+                quote!(90),
+                quote!(100),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), ids[1]);
+    assert_eq!(exchange.account().position().size(), base!(0));
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn fill_model_volume_capped_fills() {
+    let mut fill_model = OptimisticFillModel::default();
+    fill_model.set_max_volume_fraction(Dec!(0.15)).unwrap();
+    let mut exchange = mock_exchange_with(NoAccountTracker, fill_model, |_| {});
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(98), base!(1)).unwrap())
+        .unwrap();
+
+    // Only 15% of the volume of the candle can be filled in total.
+    let (executed, _) = exchange
+        .update_state(
+            1,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(102),
+                quote!(97),
+                quote!(100),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 2);
+    assert_eq!(executed[0].remaining_quantity(), base!(0));
+    assert_eq!(executed[1].remaining_quantity(), base!(0.5));
+    assert_eq!(exchange.account().position().size(), base!(1.5));
+
+    let (executed, _) = exchange
+        .update_state(
+            2,
+            candle!(
+                quote!(100),
+                quote!(101),
+                quote!(100),
+                quote!(102),
+                quote!(97),
+                quote!(100),
+                base!(2)
+            ),
+        )
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].remaining_quantity(), base!(0.2));
+    assert_eq!(exchange.account().position().size(), base!(1.8));
+}
+
+#[test]
+fn fill_model_probabilistic_fill_rate() {
+    // Touching the limit price fills with a probability of 0.2.
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        ProbabilisticFillModel::new(4, IntrabarPath::OpenHighLowClose, 42),
+        |_| {},
+    );
+    exchange
+        .update_state(0, bba!(quote!(99), quote!(100)))
+        .unwrap();
+
+    // Both the open and the high leg reach the limit price, but the fill model decides only once per candle.
+    let num_candles = 1000;
+    let mut num_fills = 0;
+    for i in 1..=num_candles {
+        if exchange.account().active_limit_orders().is_empty() {
+            exchange
+                .submit_order(Order::limit(Side::Sell, quote!(100), base!(0.01)).unwrap())
+                .unwrap();
+        }
+        let (executed, _) = exchange
+            .update_state(
+                i,
+                candle!(
+                    quote!(99),
+                    quote!(100),
+                    quote!(100),
+                    quote!(100),
+                    quote!(98),
+                    quote!(99),
+                    base!(10)
+                ),
+            )
+            .unwrap();
+        num_fills += executed.len();
+    }
+    let fill_rate = num_fills as f64 / num_candles as f64;
+    assert!((0.17..0.23).contains(&fill_rate), "{fill_rate}");
+}
@@ -0,0 +1,71 @@
+use crate::{mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn fills_market_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(1, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut order = Order::market(Side::Buy, base!(2)).unwrap();
+    order.set_user_order_id(7);
+    let (order, fills) = exchange.submit_order(order).unwrap();
+    assert_eq!(fills.len(), 1);
+    let fill = &fills[0];
+    assert_eq!(fill.order_id(), order.id());
+    assert_eq!(fill.user_order_id(), Some(7));
+    assert_eq!(fill.side(), Side::Buy);
+    assert_eq!(fill.quantity(), base!(2));
+    assert_eq!(fill.price(), quote!(101));
+    assert_eq!(fill.fee(), quote!(0.1212));
+    assert!(!fill.is_maker());
+    assert_eq!(fill.realized_pnl(), quote!(0));
+    assert_eq!(fill.timestamp_ns(), 1);
+}
+
+#[test]
+fn fills_limit_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+    let (order, fills) = exchange
+        .submit_order(Order::limit(Side::Sell, quote!(105), base!(2)).unwrap())
+        .unwrap();
+    assert!(fills.is_empty());
+
+    // Each partial fill is reported on its own.
+    let (executed, fills) = exchange
+        .update_state(1, trade!(quote!(106), base!(1.5), Side::Buy))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].order_id(), order.id());
+    assert_eq!(fills[0].quantity(), base!(1.5));
+    assert_eq!(fills[0].price(), quote!(105));
+    assert_eq!(fills[0].fee(), quote!(0.0315));
+    assert!(fills[0].is_maker());
+    assert_eq!(fills[0].realized_pnl(), quote!(6));
+    assert_eq!(fills[0].timestamp_ns(), 1);
+
+    let (_, fills) = exchange
+        .update_state(2, trade!(quote!(106), base!(1), Side::Buy))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].quantity(), base!(0.5));
+    assert_eq!(fills[0].fee(), quote!(0.0105));
+    assert_eq!(fills[0].realized_pnl(), quote!(2));
+    assert_eq!(fills[0].timestamp_ns(), 2);
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        quote!(1000) - quote!(0.1212) - quote!(0.042) + quote!(8)
+    );
+
+    let (_, fills) = exchange
+        .update_state(3, trade!(quote!(106), base!(1), Side::Buy))
+        .unwrap();
+    assert!(fills.is_empty());
+}
@@ -0,0 +1,104 @@
+use super::{enter_position, mock_exchange_with};
+use crate::{account_tracker::FullAccountTracker, funding, mock_exchange_quote, prelude::*};
+
+#[test]
+fn funding_long_pays() {
+    let mut exchange = mock_exchange_with(
+        FullAccountTracker::new(quote!(1000)),
+        PessimisticFillModel::default(),
+        |_| {},
+    );
+    enter_position(&mut exchange, base!(5));
+    let wallet_balance = exchange.account().wallet_balance();
+
+    // The position is worth 502.5 at the mark price, which is the mid price.
+    let (executed, fills) = exchange.update_state(1, funding!(Dec!(0.0001))).unwrap();
+    assert!(executed.is_empty());
+    assert!(fills.is_empty());
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        wallet_balance - quote!(0.05025)
+    );
+    assert_eq!(exchange.account().position().size(), base!(5));
+    assert_eq!(
+        exchange.account_tracker().cumulative_funding(),
+        quote!(-0.05025)
+    );
+    // Funding is recorded separately from fees and realized profit and loss.
+    assert_eq!(exchange.account_tracker().cumulative_fees(), quote!(0.303));
+    assert_eq!(exchange.account_tracker().total_rpnl(), quote!(0));
+
+    // With a negative rate, shorts pay longs.
+    exchange
+        .update_state(2, bba!(quote!(110), quote!(111)))
+        .unwrap();
+    exchange.update_state(3, funding!(Dec!(-0.0002))).unwrap();
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        wallet_balance - quote!(0.05025) + quote!(0.1105)
+    );
+    assert_eq!(
+        exchange.account_tracker().cumulative_funding(),
+        quote!(0.06025)
+    );
+}
+
+#[test]
+fn funding_short_receives() {
+    let mut exchange = mock_exchange_with(
+        FullAccountTracker::new(quote!(1000)),
+        PessimisticFillModel::default(),
+        |_| {},
+    );
+    enter_position(&mut exchange, base!(-2));
+    let wallet_balance = exchange.account().wallet_balance();
+
+    exchange.update_state(1, funding!(Dec!(0.0001))).unwrap();
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        wallet_balance + quote!(0.0201)
+    );
+    assert_eq!(
+        exchange.account_tracker().cumulative_funding(),
+        quote!(0.0201)
+    );
+
+    exchange.update_state(2, funding!(Dec!(-0.0001))).unwrap();
+    assert_eq!(exchange.account().wallet_balance(), wallet_balance);
+    assert_eq!(exchange.account_tracker().cumulative_funding(), quote!(0));
+}
+
+#[test]
+fn funding_without_position() {
+    let mut exchange = mock_exchange_with(
+        FullAccountTracker::new(quote!(1000)),
+        PessimisticFillModel::default(),
+        |_| {},
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    exchange.update_state(1, funding!(Dec!(0.01))).unwrap();
+    assert_eq!(exchange.account().wallet_balance(), quote!(1000));
+    assert_eq!(exchange.account_tracker().cumulative_funding(), quote!(0));
+}
+
+#[test]
+fn funding_inverse_future() {
+    let mut exchange = mock_exchange_quote(base!(10));
+    exchange
+        .update_state(0, bba!(quote!(99), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Sell, quote!(500)).unwrap())
+        .unwrap();
+    let wallet_balance = exchange.account().wallet_balance();
+
+    // The value of the position at the mark price of 100 is denoted in the margin currency.
+    exchange.update_state(1, funding!(Dec!(0.0001))).unwrap();
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        wallet_balance + base!(0.0005)
+    );
+}
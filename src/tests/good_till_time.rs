@@ -0,0 +1,105 @@
+use fpdec::Decimal;
+
+use super::mock_exchange_with;
+use crate::{account_tracker::FullAccountTracker, mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn good_till_time_limit_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut order = Order::limit(Side::Buy, quote!(100), base!(2)).unwrap();
+    order.set_expiry_timestamp_ns(100);
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(order.expiry_timestamp_ns(), Some(100));
+    assert!(!order.is_expired());
+
+    assert_eq!(
+        exchange
+            .update_state(100, bba!(quote!(100), quote!(101)))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+
+    // The order expires before it could be filled by the trade.
+    let (updates, _) = exchange
+        .update_state(101, trade!(quote!(99), base!(2), Side::Sell))
+        .unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].id(), order.id());
+    assert!(updates[0].is_expired());
+    assert_eq!(updates[0].filled(), Filled::No);
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+    assert_eq!(exchange.account().position.size, base!(0));
+    assert_eq!(exchange.matching_engine().queue_position(order.id()), None);
+}
+
+#[test]
+fn good_till_time_stop_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut order = Order::stop_market(
+        Side::Buy,
+        quote!(110),
+        base!(1),
+        TriggerPriceSource::MidPrice,
+    )
+    .unwrap();
+    order.set_expiry_timestamp_ns(10);
+    exchange.submit_order(order).unwrap();
+
+    let (updates, _) = exchange
+        .update_state(20, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(updates.len(), 1);
+    assert!(updates[0].is_expired());
+    assert!(exchange.account().untriggered_orders().is_empty());
+}
+
+#[test]
+fn good_till_time_invalid_expiry() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut order = Order::limit(Side::Buy, quote!(100), base!(2)).unwrap();
+    order.set_expiry_timestamp_ns(100);
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(OrderError::InvalidExpiry))
+    );
+}
+
+#[test]
+fn good_till_time_account_tracker() {
+    let mut exchange = mock_exchange_with(
+        FullAccountTracker::new(quote!(1000)),
+        PessimisticFillModel::default(),
+        |_| {},
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut order = Order::limit(Side::Buy, quote!(100), base!(2)).unwrap();
+    order.set_expiry_timestamp_ns(10);
+    exchange.submit_order(order).unwrap();
+    exchange
+        .update_state(20, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    assert_eq!(exchange.account_tracker().num_expired_orders(), 1);
+    assert_eq!(
+        exchange.account_tracker().limit_order_cancellation_ratio(),
+        0.0
+    );
+}
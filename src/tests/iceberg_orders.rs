@@ -0,0 +1,74 @@
+use crate::{l2_snapshot, l2_update, mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn iceberg_order_invalid_display_quantity() {
+    assert_eq!(
+        Order::iceberg(Side::Buy, quote!(100), base!(2), base!(3)),
+        Err(OrderError::InvalidDisplayQuantity)
+    );
+    assert_eq!(
+        Order::iceberg(Side::Buy, quote!(100), base!(2), base!(0)),
+        Err(OrderError::InvalidDisplayQuantity)
+    );
+
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(
+        exchange
+            .submit_order(Order::iceberg(Side::Buy, quote!(100), base!(2), base!(0.001)).unwrap()),
+        Err(Error::OrderError(OrderError::InvalidQuantityStepSize))
+    );
+}
+
+#[test]
+fn iceberg_order_replenishes_slices() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(
+            0,
+            l2_snapshot!(vec![(quote!(100), base!(5))], vec![(quote!(101), base!(5))]),
+        )
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::iceberg(Side::Buy, quote!(100), base!(4), base!(2)).unwrap())
+        .unwrap();
+    // Margin is charged on the full size.
+    assert_eq!(exchange.account().order_margin(), quote!(400.08));
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(5))
+    );
+
+    // Only the volume traded beyond the queue ahead fills the visible slice.
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(100), base!(6), Side::Sell))
+        .unwrap();
+    assert_eq!(executed[0].filled_quantity(), base!(1));
+
+    // Others join the level behind the order.
+    exchange
+        .update_state(2, l2_update!(Side::Buy, quote!(100), base!(3)))
+        .unwrap();
+
+    // The rest of the slice is filled, even though more has been traded,
+    // and the next slice is placed at the back of the queue,
+    // behind the others which the rest of the trade did not consume.
+    let (executed, _) = exchange
+        .update_state(3, trade!(quote!(100), base!(2), Side::Sell))
+        .unwrap();
+    assert_eq!(executed[0].filled_quantity(), base!(2));
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(2))
+    );
+    assert_eq!(exchange.account().order_margin(), quote!(200.04));
+
+    let (executed, _) = exchange
+        .update_state(4, trade!(quote!(100), base!(3), Side::Sell))
+        .unwrap();
+    assert_eq!(executed[0].filled_quantity(), base!(3));
+    assert_eq!(exchange.account().position.size, base!(3));
+}
@@ -0,0 +1,79 @@
+use super::{configure_insurance_fund, mock_exchange_liquidatable};
+use crate::{mock_exchange_base, prelude::*};
+
+#[test]
+fn insurance_fund_receives_surplus() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        config.set_liquidation_close_price(LiquidationClosePrice::BankruptcyPrice);
+        configure_insurance_fund(config, quote!(0), LossPolicy::WriteOff);
+    });
+    assert_eq!(exchange.insurance_fund().balance(), quote!(0));
+
+    let (_, fills) = exchange
+        .update_state(1, bba!(quote!(82), quote!(83)))
+        .unwrap();
+    assert_eq!(fills[0].price(), quote!(81));
+    assert_eq!(fills[0].fee(), quote!(1));
+    // The position is closed in the market at the bid of 82, one above the bankruptcy price.
+    assert_eq!(exchange.insurance_fund().balance(), quote!(6));
+    assert_eq!(exchange.insurance_fund().history(), &[(1, quote!(6))]);
+}
+
+#[test]
+fn insurance_fund_covers_shortfall() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        configure_insurance_fund(config, quote!(100), LossPolicy::Clawback)
+    });
+
+    // The market gaps far beyond the liquidation price.
+    exchange
+        .update_state(1, bba!(quote!(70), quote!(71)))
+        .unwrap();
+    // The liquidation fee of 4.15 does not make up for the loss of 5 * (83 - 70).
+    assert_eq!(exchange.insurance_fund().balance(), quote!(39.15));
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        quote!(1000) - quote!(0.303) - quote!(90) - quote!(4.15)
+    );
+}
+
+#[test]
+fn insurance_fund_runs_dry() {
+    let wallet_balance = quote!(1000) - quote!(0.303) - quote!(90) - quote!(4.15);
+    for (loss_policy, loss) in [
+        (LossPolicy::WriteOff, quote!(0)),
+        (
+            LossPolicy::SocializedLoss { share: Dec!(0.5) },
+            quote!(25.425),
+        ),
+        (LossPolicy::Clawback, quote!(50.85)),
+    ] {
+        let mut exchange = mock_exchange_liquidatable(|config| {
+            configure_insurance_fund(config, quote!(10), loss_policy)
+        });
+        exchange
+            .update_state(1, bba!(quote!(70), quote!(71)))
+            .unwrap();
+        // Only 10 of the shortfall of 60.85 is covered.
+        assert_eq!(exchange.insurance_fund().balance(), quote!(0));
+        assert_eq!(exchange.account().wallet_balance(), wallet_balance - loss);
+        assert_eq!(
+            exchange.account().available_balance(),
+            wallet_balance - loss
+        );
+    }
+}
+
+#[test]
+fn insurance_fund_invalid_config() {
+    let mut config = mock_exchange_base().config().clone();
+    assert_eq!(
+        config.set_insurance_fund_balance(quote!(-1)),
+        Err(Error::InvalidInsuranceFundBalance)
+    );
+    assert_eq!(
+        config.set_loss_policy(LossPolicy::SocializedLoss { share: Dec!(1.5) }),
+        Err(Error::InvalidLossPolicy)
+    );
+    assert_eq!(config.loss_policy(), LossPolicy::WriteOff);
+}
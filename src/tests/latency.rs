@@ -0,0 +1,365 @@
+use fpdec::Decimal;
+
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn latency_invalid_config() {
+    let mut config = mock_exchange_base().config().clone();
+    assert_eq!(
+        config.set_order_entry_latency(Latency::Sampled {
+            samples: vec![],
+            seed: 0
+        }),
+        Err(Error::InvalidLatency)
+    );
+    assert_eq!(config.order_entry_latency(), &Latency::None);
+}
+
+#[test]
+fn latency_limit_order_arrives_later() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+
+    // A trade before the order arrived does not fill it.
+    assert_eq!(
+        exchange
+            .update_state(50, trade!(quote!(99), base!(1), Side::Sell))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&order.id()));
+    assert_eq!(exchange.account().order_margin(), quote!(100.02));
+}
+
+#[test]
+fn latency_market_order_executes_at_arrival() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.account().position().size(), base!(0));
+
+    // The market moves while the order is in flight.
+    assert_eq!(
+        exchange
+            .update_state(50, bba!(quote!(102), quote!(103)))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    let (executed, _) = exchange
+        .update_state(100, bba!(quote!(104), quote!(105)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), order.id());
+    assert_eq!(executed[0].remaining_quantity(), base!(0));
+    assert_eq!(exchange.account().position().size(), base!(1));
+    assert_eq!(exchange.account().position().entry_price(), quote!(103));
+}
+
+#[test]
+fn latency_order_rejected_upon_arrival() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    // The ask dropped to the limit price, so the order would take liquidity.
+    exchange
+        .update_state(50, bba!(quote!(99), quote!(100)))
+        .unwrap();
+    let (executed, _) = exchange
+        .update_state(100, bba!(quote!(99), quote!(100)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), order.id());
+    assert_eq!(
+        executed[0].rejection(),
+        Some(&Error::OrderError(OrderError::LimitPriceAboveAsk))
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+
+    // A one-cancels-other group is rejected as a whole.
+    let orders = exchange
+        .submit_oco(vec![
+            Order::limit(Side::Buy, quote!(97), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(101), base!(1)).unwrap(),
+        ])
+        .unwrap();
+    exchange
+        .update_state(150, bba!(quote!(101), quote!(102)))
+        .unwrap();
+    let (executed, _) = exchange
+        .update_state(200, bba!(quote!(101), quote!(102)))
+        .unwrap();
+    assert_eq!(executed.len(), 2);
+    for (order, rejected) in orders.iter().zip(executed.iter()) {
+        assert_eq!(rejected.id(), order.id());
+        assert_eq!(
+            rejected.rejection(),
+            Some(&Error::OrderError(OrderError::LimitPriceBelowBid))
+        );
+    }
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn latency_amend_rejected_upon_arrival() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let amended = exchange
+        .amend_order(order.id(), quote!(99), base!(2))
+        .unwrap();
+    // The order gets filled while the amendment is in flight.
+    exchange
+        .update_state(150, trade!(quote!(99), base!(1), Side::Sell))
+        .unwrap();
+    let (executed, _) = exchange
+        .update_state(200, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), order.id());
+    assert_eq!(executed[0].limit_price(), amended.limit_price());
+    assert_eq!(executed[0].quantity(), base!(2));
+    assert_eq!(executed[0].rejection(), Some(&Error::OrderIdNotFound));
+    assert_eq!(exchange.account().position().size(), base!(1));
+}
+
+#[test]
+fn latency_order_fills_before_cancel_arrives() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let cancelled = exchange.cancel_order(order.id()).unwrap();
+    assert_eq!(cancelled.id(), order.id());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+
+    // The order gets filled while the cancellation is in flight.
+    let (executed, _) = exchange
+        .update_state(150, trade!(quote!(99), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(
+        executed[0].filled(),
+        Filled::Yes {
+            fill_price: quote!(100)
+        }
+    );
+
+    // Now the cancellation has no effect.
+    assert_eq!(
+        exchange
+            .update_state(200, bba!(quote!(100), quote!(101)))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert_eq!(exchange.account().position().size(), base!(1));
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn latency_cancel_arrives_before_fill() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    exchange.cancel_order(order.id()).unwrap();
+    assert_eq!(
+        exchange
+            .update_state(200, trade!(quote!(99), base!(1), Side::Sell))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert_eq!(exchange.account().position().size(), base!(0));
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+    assert_eq!(exchange.matching_engine().queue_position(order.id()), None);
+
+    assert_eq!(
+        exchange.cancel_order(order.id()),
+        Err(Error::OrderIdNotFound)
+    );
+}
+
+#[test]
+fn latency_per_action() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_order_entry_latency(Latency::PerAction {
+                    submit: 100,
+                    amend: 50,
+                    cancel: 10,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let mut order = Order::limit(Side::Buy, quote!(100), base!(1)).unwrap();
+    order.set_user_order_id(42);
+    let (order, _) = exchange.submit_order(order).unwrap();
+
+    // The cancellation overtakes the submission, so it has no effect.
+    exchange.cancel_order_by_user_id(42).unwrap();
+    exchange
+        .update_state(10, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+
+    let amended = exchange
+        .amend_order(order.id(), quote!(99), base!(1))
+        .unwrap();
+    assert_eq!(amended.limit_price(), Some(quote!(99)));
+    assert_eq!(
+        exchange.account().active_limit_orders()[&order.id()].limit_price(),
+        Some(quote!(100))
+    );
+    exchange
+        .update_state(150, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(
+        exchange.account().active_limit_orders()[&order.id()].limit_price(),
+        Some(quote!(99))
+    );
+}
+
+#[test]
+fn latency_sampled() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_order_entry_latency(Latency::Sampled {
+                    samples: vec![10, 20, 30],
+                    seed: 0,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .update_state(9, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert!(exchange.account().active_limit_orders().is_empty());
+    // Every sample has arrived by then.
+    exchange
+        .update_state(30, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+}
+
+#[test]
+fn latency_market_data_delay() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_market_data_delay_ns(100),
+    );
+
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(0));
+    exchange
+        .update_state(100, bba!(quote!(102), quote!(103)))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(100));
+    assert_eq!(exchange.market_state().ask(), quote!(101));
+    assert_eq!(exchange.market_state().current_timestamp_ns(), 0);
+
+    // The exchange matches against the true state, which the strategy does not see yet.
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.account().position().entry_price(), quote!(103));
+}
@@ -1,4 +1,193 @@
+use super::mock_exchange_liquidatable;
+use crate::{mock_exchange_base, prelude::*};
+
 #[test]
 fn liquidate() {
-    todo!()
+    let mut exchange = mock_exchange_liquidatable(|_| {});
+    assert_eq!(exchange.account().position().position_margin(), quote!(101));
+    // The maintenance margin is 10.1, so the position is liquidated at 101 - (101 - 10.1) / 5 = 82.82,
+    // rounded to the tick size towards the entry price.
+    assert_eq!(exchange.liquidation_price(), Some(quote!(83)));
+    assert_eq!(
+        exchange.account().position().bankruptcy_price(),
+        Some(quote!(80.8))
+    );
+    let (limit_order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(85), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .submit_order(
+            Order::stop_market(
+                Side::Sell,
+                quote!(90),
+                base!(5),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    // Not liquidated yet.
+    exchange
+        .update_state(1, bba!(quote!(84), quote!(85)))
+        .unwrap();
+    assert_eq!(exchange.account().position().size(), base!(5));
+
+    let (executed, fills) = exchange
+        .update_state(2, bba!(quote!(82), quote!(83)))
+        .unwrap();
+    // The open orders are cancelled before they could be executed.
+    assert!(executed.is_empty());
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(
+        exchange.matching_engine().queue_position(limit_order.id()),
+        None
+    );
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].side(), Side::Sell);
+    assert_eq!(fills[0].quantity(), base!(5));
+    assert_eq!(fills[0].price(), quote!(83));
+    assert_eq!(fills[0].realized_pnl(), quote!(-90));
+    assert_eq!(fills[0].fee(), quote!(4.15));
+    assert!(exchange.account().position().size().is_zero());
+    assert_eq!(exchange.liquidation_price(), None);
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        quote!(1000) - quote!(0.303) - quote!(90) - quote!(4.15)
+    );
+    assert_eq!(exchange.account_tracker().num_liquidations(), 1);
+
+    // The simulation continues with the remaining balance.
+    exchange
+        .update_state(3, bba!(quote!(82), quote!(83)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.account().position().size(), base!(1));
+}
+
+#[test]
+fn liquidate_at_bankruptcy_price() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        config.set_liquidation_close_price(LiquidationClosePrice::BankruptcyPrice)
+    });
+
+    // The market gaps far beyond the bankruptcy price, but the loss is limited to the position margin.
+    let (_, fills) = exchange
+        .update_state(1, bba!(quote!(70), quote!(71)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    // The bankruptcy price of 80.8 is rounded up to the tick size.
+    assert_eq!(fills[0].price(), quote!(81));
+    assert_eq!(fills[0].realized_pnl(), quote!(-100));
+    // Only 1 is left of the position margin to pay the liquidation fee.
+    assert_eq!(fills[0].fee(), quote!(1));
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        quote!(1000) - quote!(0.303) - quote!(100) - quote!(1)
+    );
+    assert_eq!(exchange.account().available_balance(), quote!(898.697));
+}
+
+#[test]
+fn liquidate_short() {
+    let mut exchange = mock_exchange_liquidatable(|_| {});
+    exchange
+        .submit_order(Order::market(Side::Sell, base!(10)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.account().position().size(), base!(-5));
+    assert_eq!(exchange.account().position().entry_price(), quote!(100));
+    // The maintenance margin is 10, so the position is liquidated at 100 + (100 - 10) / 5 = 118.
+    assert_eq!(exchange.liquidation_price(), Some(quote!(118)));
+
+    exchange
+        .update_state(1, bba!(quote!(117), quote!(118)))
+        .unwrap();
+    assert_eq!(exchange.account().position().size(), base!(-5));
+    let (_, fills) = exchange
+        .update_state(2, bba!(quote!(118), quote!(119)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].side(), Side::Buy);
+    assert_eq!(fills[0].price(), quote!(118));
+    assert_eq!(fills[0].realized_pnl(), quote!(-90));
+    assert!(exchange.account().position().size().is_zero());
+}
+
+#[test]
+fn liquidation_fee_invalid() {
+    let mut config = mock_exchange_base().config().clone();
+    assert_eq!(
+        config.set_liquidation_fee(fee!(-0.01)),
+        Err(Error::InvalidLiquidationFee)
+    );
+    assert_eq!(
+        config.set_liquidation_fee(fee!(1)),
+        Err(Error::InvalidLiquidationFee)
+    );
+}
+
+#[test]
+fn liquidate_partially() {
+    let mut exchange = mock_exchange_liquidatable(|config| {
+        config
+            .set_liquidation_mode(LiquidationMode::Partial {
+                fraction: Dec!(0.4),
+            })
+            .unwrap()
+    });
+    assert_eq!(exchange.liquidation_price(), Some(quote!(83)));
+
+    // A single step restores the maintenance margin.
+    let (_, fills) = exchange
+        .update_state(1, bba!(quote!(82), quote!(83)))
+        .unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].quantity(), base!(2));
+    assert_eq!(fills[0].price(), quote!(83));
+    assert_eq!(fills[0].realized_pnl(), quote!(-36));
+    assert_eq!(fills[0].fee(), quote!(1.66));
+    assert_eq!(exchange.account().position().size(), base!(3));
+    // The margin of the closed part stays with the position, reduced by its loss.
+    assert_eq!(
+        exchange.account().position().position_margin(),
+        quote!(63.34)
+    );
+    assert_eq!(exchange.liquidation_price(), Some(quote!(82)));
+    assert_eq!(exchange.account_tracker().num_liquidations(), 1);
+    assert_eq!(
+        exchange.account().available_balance(),
+        quote!(1000) - quote!(0.303) - quote!(101)
+    );
+
+    // Once the market gaps further, the position is reduced in steps of 40% of its size until it is closed.
+    let (_, fills) = exchange
+        .update_state(2, bba!(quote!(70), quote!(71)))
+        .unwrap();
+    assert_eq!(
+        Vec::from_iter(fills.iter().map(|fill| (fill.quantity(), fill.price()))),
+        vec![
+            (base!(1.2), quote!(82)),
+            (base!(1.2), quote!(82)),
+            (base!(0.6), quote!(77)),
+        ]
+    );
+    assert!(exchange.account().position().size().is_zero());
+    assert_eq!(exchange.account_tracker().num_liquidations(), 4);
+}
+
+#[test]
+fn liquidation_mode_invalid() {
+    let mut config = mock_exchange_base().config().clone();
+    for fraction in [Dec!(0), Dec!(1), Dec!(-0.5)] {
+        assert_eq!(
+            config.set_liquidation_mode(LiquidationMode::Partial { fraction }),
+            Err(Error::InvalidLiquidationMode)
+        );
+    }
+    assert_eq!(config.liquidation_mode(), LiquidationMode::Full);
 }
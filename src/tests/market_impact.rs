@@ -0,0 +1,218 @@
+use fpdec::{Dec, Decimal};
+
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn market_impact_invalid_config() {
+    let mut config = mock_exchange_base().config().clone();
+    assert_eq!(
+        config.set_market_impact(MarketImpact::Linear {
+            coefficient: Dec!(-0.01),
+            duration_ns: 100
+        }),
+        Err(Error::InvalidMarketImpact)
+    );
+    assert_eq!(
+        config.set_market_impact(MarketImpact::Transient {
+            coefficient: Dec!(0.01),
+            half_life_ns: 0
+        }),
+        Err(Error::InvalidMarketImpact)
+    );
+    assert_eq!(config.market_impact(), &MarketImpact::None);
+}
+
+#[test]
+fn market_impact_linear() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_market_impact(MarketImpact::Linear {
+                    coefficient: Dec!(0.01),
+                    duration_ns: 100,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(103)
+        }
+    );
+    assert_eq!(exchange.account().position().entry_price(), quote!(103));
+    assert_eq!(exchange.market_state().bid(), quote!(102));
+    assert_eq!(exchange.market_state().ask(), quote!(103));
+
+    // The impact persists in the simulated prices for a while.
+    exchange
+        .update_state(50, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(102));
+    assert_eq!(exchange.market_state().ask(), quote!(103));
+    exchange
+        .update_state(100, trade!(quote!(100), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(102));
+    assert_eq!(exchange.market_state().ask(), quote!(103));
+
+    exchange
+        .update_state(101, trade!(quote!(100), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(100));
+    assert_eq!(exchange.market_state().ask(), quote!(101));
+}
+
+#[test]
+fn market_impact_linear_sell() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_market_impact(MarketImpact::Linear {
+                    coefficient: Dec!(0.01),
+                    duration_ns: 100,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::market(Side::Sell, base!(2)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(98)
+        }
+    );
+    assert_eq!(exchange.market_state().bid(), quote!(98));
+    assert_eq!(exchange.market_state().ask(), quote!(99));
+
+    // Buying back right away pays for the impact of the sell.
+    let (order, _) = exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(100)
+        }
+    );
+    assert_eq!(exchange.market_state().bid(), quote!(99));
+    assert_eq!(exchange.market_state().ask(), quote!(100));
+}
+
+#[test]
+fn market_impact_square_root() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_market_impact(MarketImpact::SquareRoot {
+                    coefficient: Dec!(0.01),
+                    duration_ns: 100,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::market(Side::Buy, base!(4)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(103)
+        }
+    );
+    assert_eq!(exchange.market_state().ask(), quote!(103));
+}
+
+#[test]
+fn market_impact_transient() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_market_impact(MarketImpact::Transient {
+                    coefficient: Dec!(0.01),
+                    half_life_ns: 100,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::market(Side::Buy, base!(4)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(105)
+        }
+    );
+    assert_eq!(exchange.market_state().ask(), quote!(105));
+
+    // The impact halves with every half life.
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(102));
+    assert_eq!(exchange.market_state().ask(), quote!(103));
+    exchange
+        .update_state(200, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(101));
+    assert_eq!(exchange.market_state().ask(), quote!(102));
+}
+
+#[test]
+fn market_impact_not_for_limit_orders() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_market_impact(MarketImpact::Linear {
+                    coefficient: Dec!(0.01),
+                    duration_ns: 100,
+                })
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(2)).unwrap())
+        .unwrap();
+    exchange
+        .update_state(1, trade!(quote!(99), base!(2), Side::Sell))
+        .unwrap();
+    assert_eq!(exchange.account().position().entry_price(), quote!(100));
+    assert_eq!(exchange.market_state().bid(), quote!(100));
+    assert_eq!(exchange.market_state().ask(), quote!(101));
+}
@@ -0,0 +1,116 @@
+use fpdec::Decimal;
+
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, l2_snapshot, prelude::*, trade};
+
+#[test]
+fn marketable_limit_order_without_depth() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_execute_marketable_limit_orders(true),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(102), base!(2)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(101)
+        }
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(
+        exchange.account().position,
+        Position {
+            size: base!(2),
+            entry_price: quote!(101),
+            position_margin: quote!(202),
+            leverage: leverage!(1),
+        }
+    );
+    // The taker fee is charged.
+    assert_eq!(
+        exchange.account().wallet_balance,
+        quote!(1000) - quote!(0.1212)
+    );
+}
+
+#[test]
+fn marketable_limit_order_rests_remainder() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_execute_marketable_limit_orders(true),
+    );
+    exchange
+        .update_state(
+            0,
+            l2_snapshot!(
+                vec![(quote!(100), base!(1))],
+                vec![(quote!(101), base!(1)), (quote!(102), base!(3))]
+            ),
+        )
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(101), base!(3)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::PartiallyFilled {
+            avg_fill_price: quote!(101)
+        }
+    );
+    assert_eq!(exchange.account().position.size, base!(1));
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    // The remainder is the best bid now.
+    assert_eq!(
+        exchange.matching_engine().queue_position(order.id()),
+        Some(base!(0))
+    );
+
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(101), base!(2), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(
+        executed[0].filled(),
+        Filled::Yes {
+            fill_price: quote!(101)
+        }
+    );
+    assert_eq!(exchange.account().position.size, base!(3));
+    // Taker fee for the first fill, maker fee for the remainder.
+    assert_eq!(
+        exchange.account().wallet_balance,
+        quote!(1000) - quote!(0.0606) - quote!(0.0404)
+    );
+}
+
+#[test]
+fn marketable_limit_sell_order() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_execute_marketable_limit_orders(true),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Sell, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(100)
+        }
+    );
+    assert_eq!(exchange.account().position.size, base!(-1));
+}
@@ -0,0 +1,192 @@
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, prelude::*, trade};
+
+#[test]
+fn max_open_orders_limit_and_stop_orders() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            // At most three open orders.
+            *config = Config::new(
+                quote!(1000),
+                3,
+                leverage!(1),
+                config.contract_specification().clone(),
+            )
+            .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Sell, quote!(102), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .submit_order(
+            Order::stop_market(
+                Side::Buy,
+                quote!(110),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(exchange.account().num_open_orders(), 3);
+
+    assert_eq!(
+        exchange.submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap()),
+        Err(Error::OrderError(OrderError::MaxActiveOrders))
+    );
+    assert_eq!(
+        exchange.submit_order(
+            Order::stop_market(
+                Side::Sell,
+                quote!(90),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap()
+        ),
+        Err(Error::OrderError(OrderError::MaxActiveOrders))
+    );
+    // Market orders never rest, so they are not limited.
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.account().num_open_orders(), 3);
+
+    // Once an order is filled, there is room for a new one.
+    exchange
+        .update_state(1, trade!(quote!(99), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(exchange.account().num_open_orders(), 2);
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+}
+
+#[test]
+fn max_open_orders_order_groups() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            // At most three open orders.
+            *config = Config::new(
+                quote!(1000),
+                3,
+                leverage!(1),
+                config.contract_specification().clone(),
+            )
+            .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+
+    assert_eq!(
+        exchange.submit_bracket(
+            Order::limit(Side::Buy, quote!(100), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(1)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(90),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        ),
+        Err(Error::OrderError(OrderError::MaxActiveOrders))
+    );
+    assert_eq!(exchange.account().num_open_orders(), 1);
+
+    // With a market entry, only the children are open orders.
+    exchange
+        .submit_bracket(
+            Order::market(Side::Buy, base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(1)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(90),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(exchange.account().num_open_orders(), 3);
+
+    assert_eq!(
+        exchange.submit_oco(vec![
+            Order::limit(Side::Buy, quote!(98), base!(1)).unwrap(),
+            Order::limit(Side::Buy, quote!(97), base!(1)).unwrap(),
+        ]),
+        Err(Error::OrderError(OrderError::MaxActiveOrders))
+    );
+}
+
+#[test]
+fn max_open_orders_batch() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            // At most three open orders.
+            *config = Config::new(
+                quote!(1000),
+                3,
+                leverage!(1),
+                config.contract_specification().clone(),
+            )
+            .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let batch = exchange
+        .submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(100), base!(1)).unwrap(),
+                Order::limit(Side::Buy, quote!(99), base!(1)).unwrap(),
+                Order::limit(Side::Sell, quote!(102), base!(1)).unwrap(),
+            ],
+            vec![],
+        )
+        .unwrap();
+    let orders = batch.orders();
+
+    assert_eq!(
+        exchange.submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(98), base!(1)).unwrap(),
+                Order::limit(Side::Buy, quote!(97), base!(1)).unwrap(),
+            ],
+            vec![orders[0].id()],
+        ),
+        Err(Error::OrderError(OrderError::MaxActiveOrders))
+    );
+    assert_eq!(exchange.account().num_open_orders(), 3);
+
+    // The cancelled orders make room for the new ones.
+    exchange
+        .submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(98), base!(1)).unwrap(),
+                Order::limit(Side::Buy, quote!(97), base!(1)).unwrap(),
+            ],
+            vec![orders[0].id(), orders[1].id()],
+        )
+        .unwrap();
+    assert_eq!(exchange.account().num_open_orders(), 3);
+}
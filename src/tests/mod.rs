@@ -1,4 +1,101 @@
+use crate::{account_tracker::FullAccountTracker, mock_exchange_base, prelude::*};
+
+mod amend_order;
+mod auto_deleverage;
+mod bracket_orders;
+mod cancel_orders;
+mod fill_model;
+mod fills;
+mod funding;
+mod good_till_time;
+mod iceberg_orders;
+mod insurance_fund;
+mod latency;
+mod liquidate;
+mod market_impact;
+mod marketable_limit_orders;
+mod max_open_orders;
+mod oco_orders;
+mod partial_fills;
+mod queue_position;
+mod rate_limits;
+mod reduce_only;
+mod stop_orders;
+mod submit_batch;
 mod submit_limit_buy_order;
 mod submit_limit_sell_order;
 mod submit_market_buy_order;
 mod submit_market_sell_order;
+mod take_profit_orders;
+mod time_in_force;
+mod trailing_stop_orders;
+
+/// Constructs a mock exchange (for linear futures) with the `account_tracker`, the `fill_model`
+/// and the config of `mock_exchange_base`, which `configure` overrides.
+pub(crate) fn mock_exchange_with<A, F>(
+    account_tracker: A,
+    fill_model: F,
+    configure: impl FnOnce(&mut Config<QuoteCurrency>),
+) -> Exchange<A, BaseCurrency, F>
+where
+    A: AccountTracker<QuoteCurrency>,
+    F: FillModel,
+{
+    let mut config = mock_exchange_base().config().clone();
+    configure(&mut config);
+    Exchange::with_fill_model(account_tracker, config, fill_model)
+}
+
+/// Enters a position of `size` with a market order at the touch of 100 / 101.
+pub(crate) fn enter_position<A, F>(exchange: &mut Exchange<A, BaseCurrency, F>, size: BaseCurrency)
+where
+    A: AccountTracker<QuoteCurrency>,
+    F: FillModel,
+{
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let side = if size > base!(0) {
+        Side::Buy
+    } else {
+        Side::Sell
+    };
+    exchange
+        .submit_order(Order::market(side, size.abs()).unwrap())
+        .unwrap();
+}
+
+/// Constructs a mock exchange (for linear futures) with a leverage of 5, a liquidation fee of 1%
+/// and the config overridden by `configure`, which holds a long position of 5 entered at 101.
+/// Unless `configure` changes the fee, the position is liquidated at 83 with a bankruptcy price of 80.8.
+pub(crate) fn mock_exchange_liquidatable(
+    configure: impl FnOnce(&mut Config<QuoteCurrency>),
+) -> Exchange<FullAccountTracker<QuoteCurrency>, BaseCurrency> {
+    let mut exchange = mock_exchange_with(
+        FullAccountTracker::new(quote!(1000)),
+        PessimisticFillModel::default(),
+        |config| {
+            *config = Config::new(
+                quote!(1000),
+                200,
+                leverage!(5),
+                config.contract_specification().clone(),
+            )
+            .unwrap();
+            config.set_liquidation_fee(fee!(0.01)).unwrap();
+            configure(config);
+        },
+    );
+    enter_position(&mut exchange, base!(5));
+    exchange
+}
+
+/// Configures an insurance fund of `balance` with the `loss_policy`.
+pub(crate) fn configure_insurance_fund(
+    config: &mut Config<QuoteCurrency>,
+    balance: QuoteCurrency,
+    loss_policy: LossPolicy,
+) {
+    config.set_insurance_fund_balance(balance).unwrap();
+    config.set_loss_policy(loss_policy).unwrap();
+}
@@ -0,0 +1,125 @@
+use crate::{mock_exchange_base, prelude::*, risk_engine::RiskError, trade};
+
+#[test]
+fn oco_orders_group_margin() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let orders = exchange
+        .submit_oco(vec![
+            Order::limit(Side::Buy, quote!(95), base!(2)).unwrap(),
+            Order::limit(Side::Buy, quote!(90), base!(4)).unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(orders[0].id(), 0);
+    assert_eq!(orders[1].id(), 1);
+    assert_eq!(exchange.account().order_groups()[&0], vec![0, 1]);
+    // Only the larger order requires margin.
+    assert_eq!(exchange.account().order_margin(), quote!(360.072));
+
+    // A partial fill cancels the sibling.
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(95), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), 0);
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert!(exchange.account().order_groups().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(95.019));
+}
+
+#[test]
+fn oco_orders_stop_triggered() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+
+    let orders = exchange
+        .submit_oco(vec![
+            Order::limit(Side::Sell, quote!(110), base!(2)).unwrap(),
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(2),
+                TriggerPriceSource::MidPrice,
+            )
+            .unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().untriggered_orders().len(), 1);
+
+    let (executed, _) = exchange
+        .update_state(1, bba!(quote!(94), quote!(95)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), orders[1].id());
+    assert_eq!(exchange.account().position.size, base!(0));
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert!(exchange.account().order_groups().is_empty());
+}
+
+#[test]
+fn oco_orders_cancel_one() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let orders = exchange
+        .submit_oco(vec![
+            Order::limit(Side::Buy, quote!(95), base!(2)).unwrap(),
+            Order::limit(Side::Buy, quote!(90), base!(4)).unwrap(),
+        ])
+        .unwrap();
+
+    // Cancelling an order only removes it from the group.
+    exchange.cancel_order(orders[1].id()).unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert!(exchange.account().order_groups().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(190.038));
+}
+
+#[test]
+fn oco_orders_rejected() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    assert_eq!(
+        exchange.submit_oco(vec![Order::limit(Side::Buy, quote!(95), base!(2)).unwrap()]),
+        Err(Error::OrderError(OrderError::InvalidOrderGroup))
+    );
+    assert_eq!(
+        exchange.submit_oco(vec![
+            Order::limit(Side::Buy, quote!(95), base!(2)).unwrap(),
+            Order::market(Side::Sell, base!(2)).unwrap(),
+        ]),
+        Err(Error::OrderError(OrderError::InvalidOrderGroup))
+    );
+    assert_eq!(
+        exchange.submit_oco(vec![
+            Order::limit(Side::Buy, quote!(95), base!(2)).unwrap(),
+            Order::limit(Side::Buy, quote!(101), base!(2)).unwrap(),
+        ]),
+        Err(Error::OrderError(OrderError::LimitPriceAboveAsk))
+    );
+    assert_eq!(
+        exchange.submit_oco(vec![
+            Order::limit(Side::Buy, quote!(95), base!(2)).unwrap(),
+            Order::limit(Side::Buy, quote!(90), base!(20)).unwrap(),
+        ]),
+        Err(Error::RiskError(RiskError::NotEnoughAvailableBalance))
+    );
+    // None of the orders got placed.
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert!(exchange.account().order_groups().is_empty());
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+}
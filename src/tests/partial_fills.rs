@@ -0,0 +1,53 @@
+use crate::{mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn limit_order_partial_fills() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(99), quote!(100)))
+        .unwrap();
+
+    let order = Order::limit(Side::Buy, quote!(98), base!(5)).unwrap();
+    exchange.submit_order(order).unwrap();
+    assert_eq!(exchange.account().order_margin(), quote!(490.098));
+
+    let (exec_orders, _) = exchange
+        .update_state(1, trade!(quote!(98), base!(2), Side::Sell))
+        .unwrap();
+    assert_eq!(exec_orders.len(), 1);
+    assert_eq!(exec_orders[0].filled_quantity(), base!(2));
+    assert_eq!(exec_orders[0].remaining_quantity(), base!(3));
+    assert_eq!(
+        exec_orders[0].filled(),
+        Filled::PartiallyFilled {
+            avg_fill_price: quote!(98)
+        }
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().position().size(), base!(2));
+    assert_eq!(exchange.account().position().position_margin(), quote!(196));
+    assert_eq!(exchange.account().order_margin(), quote!(294.0588));
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        quote!(1000) - quote!(0.0392)
+    );
+
+    let (exec_orders, _) = exchange
+        .update_state(2, trade!(quote!(97), base!(10), Side::Sell))
+        .unwrap();
+    assert_eq!(exec_orders.len(), 1);
+    assert_eq!(exec_orders[0].remaining_quantity(), base!(0));
+    assert_eq!(
+        exec_orders[0].filled(),
+        Filled::Yes {
+            fill_price: quote!(98)
+        }
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().position().size(), base!(5));
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+    assert_eq!(
+        exchange.account().wallet_balance(),
+        quote!(1000) - quote!(0.098)
+    );
+}
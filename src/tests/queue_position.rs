@@ -0,0 +1,38 @@
+use crate::{l2_update, mock_exchange_base, prelude::*, trade};
+
+// A resting limit order only gets filled once the volume ahead of it has traded.
+#[test]
+fn limit_order_queue_position() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, l2_update!(Side::Buy, quote!(99), base!(3)))
+        .unwrap();
+    exchange
+        .update_state(0, l2_update!(Side::Sell, quote!(100), base!(3)))
+        .unwrap();
+    assert_eq!(exchange.market_state().bid(), quote!(99));
+    assert_eq!(exchange.market_state().ask(), quote!(100));
+
+    let order = Order::limit(Side::Buy, quote!(99), base!(1)).unwrap();
+    let (mut order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(exchange.matching_engine().queue_position(0), Some(base!(3)));
+
+    assert!(exchange
+        .update_state(1, trade!(quote!(99), base!(2), Side::Sell))
+        .unwrap()
+        .0
+        .is_empty());
+    assert_eq!(exchange.matching_engine().queue_position(0), Some(base!(1)));
+    assert_eq!(exchange.account().position().size(), base!(0));
+
+    order.mark_filled(quote!(99));
+    assert_eq!(
+        exchange
+            .update_state(2, trade!(quote!(99), base!(2), Side::Sell))
+            .unwrap()
+            .0,
+        vec![order]
+    );
+    assert_eq!(exchange.matching_engine().queue_position(0), None);
+    assert_eq!(exchange.account().position().size(), base!(1));
+}
@@ -0,0 +1,312 @@
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, mock_exchange_base, prelude::*};
+
+#[test]
+fn rate_limits_invalid() {
+    let mut config = mock_exchange_base().config().clone();
+    assert_eq!(
+        config.set_rate_limits(vec![RateLimit::new(0, 10)]),
+        Err(Error::InvalidRateLimit)
+    );
+    assert_eq!(
+        config.set_rate_limits(vec![RateLimit::new(1_000, 10), RateLimit::new(1_000, 0)]),
+        Err(Error::InvalidRateLimit)
+    );
+    assert!(config.rate_limits().is_empty());
+}
+
+#[test]
+fn rate_limits_reject_until_next_window() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 3)])
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .amend_order(order.id(), quote!(99), base!(1))
+        .unwrap();
+    exchange.cancel_order(order.id()).unwrap();
+    assert_eq!(
+        exchange.submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap()),
+        Err(Error::RateLimitExceeded)
+    );
+
+    // The market clock has not reached the next window yet.
+    exchange
+        .update_state(999, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(
+        exchange.submit_order(Order::market(Side::Buy, base!(1)).unwrap()),
+        Err(Error::RateLimitExceeded)
+    );
+    assert!(exchange.account().position().size().is_zero());
+
+    exchange
+        .update_state(1_000, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(1)).unwrap())
+        .unwrap();
+}
+
+#[test]
+fn rate_limits_weights() {
+    let mut rate_limit = RateLimit::new(1_000, 5);
+    rate_limit.submit_weight = 2;
+    rate_limit.amend_weight = 3;
+    rate_limit.cancel_weight = 0;
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_rate_limits(vec![rate_limit]).unwrap(),
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(
+        exchange.submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(99), base!(1)).unwrap(),
+                Order::limit(Side::Buy, quote!(98), base!(1)).unwrap(),
+            ],
+            vec![order.id()],
+        ),
+        Err(Error::RateLimitExceeded)
+    );
+    exchange
+        .amend_order(order.id(), quote!(99), base!(1))
+        .unwrap();
+    // Cancellations are free, even when the budget is used up.
+    exchange.cancel_all_orders().unwrap();
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(
+        exchange.submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap()),
+        Err(Error::RateLimitExceeded)
+    );
+}
+
+#[test]
+fn rate_limits_with_latency() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 2)])
+                .unwrap();
+            config.set_order_entry_latency(Latency::Fixed(100)).unwrap();
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    // The budget is used up when a request is sent, not when it arrives.
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(100), base!(1)).unwrap())
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.cancel_all_orders(), Err(Error::RateLimitExceeded));
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+}
+
+#[test]
+fn rate_limits_bulk_cancel_per_order() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 5)])
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    for price in [quote!(98), quote!(99), quote!(100)] {
+        exchange
+            .submit_order(Order::limit(Side::Buy, price, base!(1)).unwrap())
+            .unwrap();
+    }
+
+    // Each of the cancelled orders uses up the cancel weight.
+    assert_eq!(exchange.cancel_all_orders(), Err(Error::RateLimitExceeded));
+    assert_eq!(exchange.account().active_limit_orders().len(), 3);
+    assert_eq!(
+        exchange
+            .cancel_orders_matching(|order| order.limit_price() < Some(quote!(100)))
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    // A request cancelling nothing still counts.
+    assert_eq!(
+        exchange.cancel_orders_by_side(Side::Sell),
+        Err(Error::RateLimitExceeded)
+    );
+}
+
+#[test]
+fn rate_limits_invalid_order_group() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 2)])
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    // Invalid requests are rejected before they use up any of the budget.
+    assert_eq!(
+        exchange.submit_oco(vec![Order::limit(Side::Buy, quote!(100), base!(1)).unwrap()]),
+        Err(Error::OrderError(OrderError::InvalidOrderGroup))
+    );
+    assert_eq!(
+        exchange.submit_oco(vec![
+            Order::limit(Side::Buy, quote!(99), base!(1)).unwrap(),
+            Order::limit(Side::Buy, quote!(98.5), base!(1)).unwrap(),
+        ]),
+        Err(Error::OrderError(OrderError::InvalidOrderPriceStepSize))
+    );
+    let orders = exchange
+        .submit_oco(vec![
+            Order::limit(Side::Buy, quote!(99), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(102), base!(1)).unwrap(),
+        ])
+        .unwrap();
+    assert_eq!(orders.len(), 2);
+}
+
+#[test]
+fn rate_limits_invalid_order() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 1)])
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    // Invalid requests are rejected before they use up any of the budget.
+    assert_eq!(
+        exchange.submit_order(Order::limit(Side::Buy, quote!(98.5), base!(1)).unwrap()),
+        Err(Error::OrderError(OrderError::InvalidOrderPriceStepSize))
+    );
+    assert_eq!(
+        exchange.submit_order(Order::limit(Side::Buy, quote!(99), base!(1.005)).unwrap()),
+        Err(Error::OrderError(OrderError::InvalidQuantityStepSize))
+    );
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+}
+
+#[test]
+fn rate_limits_invalid_bracket() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 3)])
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let stop_loss = Order::stop_market(
+        Side::Sell,
+        quote!(90),
+        base!(1),
+        TriggerPriceSource::LastTradePrice,
+    )
+    .unwrap();
+
+    // Invalid requests are rejected before they use up any of the budget.
+    assert_eq!(
+        exchange.submit_bracket(
+            Order::limit(Side::Buy, quote!(98.5), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(1)).unwrap(),
+            stop_loss.clone(),
+        ),
+        Err(Error::OrderError(OrderError::InvalidOrderPriceStepSize))
+    );
+    let (orders, _) = exchange
+        .submit_bracket(
+            Order::limit(Side::Buy, quote!(99), base!(1)).unwrap(),
+            Order::limit(Side::Sell, quote!(110), base!(1)).unwrap(),
+            stop_loss,
+        )
+        .unwrap();
+    assert_eq!(orders.len(), 3);
+}
+
+#[test]
+fn rate_limits_invalid_amend() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| {
+            config
+                .set_rate_limits(vec![RateLimit::new(1_000, 2)])
+                .unwrap()
+        },
+    );
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let (order, _) = exchange
+        .submit_order(Order::limit(Side::Buy, quote!(99), base!(1)).unwrap())
+        .unwrap();
+
+    // Invalid requests are rejected before they use up any of the budget.
+    assert_eq!(
+        exchange.amend_order(order.id(), quote!(98.5), base!(1)),
+        Err(Error::OrderError(OrderError::InvalidOrderPriceStepSize))
+    );
+    assert_eq!(
+        exchange.amend_order(order.id(), quote!(0), base!(1)),
+        Err(Error::OrderError(OrderError::LimitPriceBelowZero))
+    );
+    assert_eq!(
+        exchange.amend_order(order.id() + 1, quote!(98), base!(1)),
+        Err(Error::OrderIdNotFound)
+    );
+    let amended = exchange
+        .amend_order(order.id(), quote!(98), base!(1))
+        .unwrap();
+    assert_eq!(amended.limit_price(), Some(quote!(98)));
+}
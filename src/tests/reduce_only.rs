@@ -0,0 +1,99 @@
+use crate::{mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn reduce_only_order_rejected() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let mut order = Order::market(Side::Sell, base!(1)).unwrap();
+    order.set_reduce_only(true);
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(
+            OrderError::ReduceOnlyWouldIncreasePosition
+        ))
+    );
+
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+    let mut order = Order::limit(Side::Buy, quote!(99), base!(1)).unwrap();
+    order.set_reduce_only(true);
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(
+            OrderError::ReduceOnlyWouldIncreasePosition
+        ))
+    );
+    // Flipping the position is not allowed either.
+    let mut order = Order::limit(Side::Sell, quote!(105), base!(3)).unwrap();
+    order.set_reduce_only(true);
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(
+            OrderError::ReduceOnlyWouldIncreasePosition
+        ))
+    );
+}
+
+#[test]
+fn reduce_only_order_cancelled_once_flat() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+
+    let mut order = Order::limit(Side::Sell, quote!(105), base!(2)).unwrap();
+    order.set_reduce_only(true);
+    exchange.submit_order(order).unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+
+    // The position is closed by another order.
+    exchange
+        .submit_order(Order::market(Side::Sell, base!(2)).unwrap())
+        .unwrap();
+    assert_eq!(exchange.account().position.size, base!(0));
+
+    // So the reduce only order must not open a new short.
+    assert_eq!(
+        exchange
+            .update_state(1, trade!(quote!(105), base!(2), Side::Buy))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert_eq!(exchange.account().position.size, base!(0));
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn reduce_only_order_fill_capped_at_position() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+
+    let mut order = Order::limit(Side::Sell, quote!(105), base!(2)).unwrap();
+    order.set_reduce_only(true);
+    exchange.submit_order(order).unwrap();
+    exchange
+        .submit_order(Order::market(Side::Sell, base!(1)).unwrap())
+        .unwrap();
+
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(105), base!(2), Side::Buy))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].filled_quantity(), base!(1));
+    assert_eq!(exchange.account().position.size, base!(0));
+    // The remainder can no longer reduce the position.
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
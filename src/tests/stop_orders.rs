@@ -0,0 +1,304 @@
+use crate::{candle, mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn stop_market_buy_order_invalid_trigger_price() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(99), quote!(100)))
+        .unwrap();
+
+    // The trigger price is compared against the mid price, as no trade has happened yet.
+    let order = Order::stop_market(
+        Side::Buy,
+        quote!(99),
+        base!(1),
+        TriggerPriceSource::LastTradePrice,
+    )
+    .unwrap();
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(OrderError::InvalidTriggerPrice))
+    );
+    let order = Order::stop_market(
+        Side::Sell,
+        quote!(100),
+        base!(1),
+        TriggerPriceSource::MidPrice,
+    )
+    .unwrap();
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(OrderError::InvalidTriggerPrice))
+    );
+}
+
+#[test]
+fn stop_market_buy_order_triggered_by_trade() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(99), quote!(100)))
+        .unwrap();
+
+    let order = Order::stop_market(
+        Side::Buy,
+        quote!(105),
+        base!(2),
+        TriggerPriceSource::LastTradePrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(exchange.account().untriggered_orders().len(), 1);
+    assert!(exchange.account().active_limit_orders().is_empty());
+    // Untriggered orders don't reserve any margin.
+    assert_eq!(exchange.account().available_balance(), quote!(1000));
+
+    assert_eq!(
+        exchange
+            .update_state(1, trade!(quote!(104), base!(1), Side::Buy))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    exchange
+        .update_state(2, bba!(quote!(104), quote!(105)))
+        .unwrap();
+
+    let mut expected = order.clone();
+    expected.mark_filled(quote!(105));
+    assert_eq!(
+        exchange
+            .update_state(3, trade!(quote!(105), base!(1), Side::Buy))
+            .unwrap()
+            .0,
+        vec![expected]
+    );
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(
+        exchange.account().position,
+        Position {
+            size: base!(2),
+            entry_price: quote!(105),
+            position_margin: quote!(210),
+            leverage: leverage!(1),
+        }
+    );
+    // Triggered stop market orders pay the taker fee.
+    assert_eq!(
+        exchange.account().wallet_balance,
+        quote!(1000) - quote!(0.126)
+    );
+}
+
+#[test]
+fn stop_market_sell_order_triggered_by_candle() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, trade!(quote!(100), base!(1), Side::Buy))
+        .unwrap();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let order = Order::stop_market(
+        Side::Sell,
+        quote!(95),
+        base!(1),
+        TriggerPriceSource::LastTradePrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+
+    // Quotes alone don't move the last trade price.
+    assert_eq!(
+        exchange
+            .update_state(1, bba!(quote!(94), quote!(95)))
+            .unwrap()
+            .0,
+        vec![]
+    );
+
+    // The price passes through the stop price within the candle.
+    let mut expected = order.clone();
+    expected.mark_filled(quote!(95));
+    assert_eq!(
+        exchange
+            .update_state(
+                2,
+                candle!(
+                    quote!(96),
+                    quote!(97),
+                    quote!(100),
+                    quote!(101),
+                    quote!(94),
+                    quote!(96),
+                    base!(10)
+                )
+            )
+            .unwrap()
+            .0,
+        vec![expected]
+    );
+    assert_eq!(exchange.account().position.size, base!(-1));
+}
+
+#[test]
+fn stop_market_sell_order_gap_open() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, trade!(quote!(100), base!(1), Side::Buy))
+        .unwrap();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(
+            Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    // The candle opens below the stop price, so the order is filled at the open.
+    let mut expected = order.clone();
+    expected.mark_filled(quote!(92));
+    assert_eq!(
+        exchange
+            .update_state(
+                1,
+                candle!(
+                    quote!(93),
+                    quote!(94),
+                    quote!(92),
+                    quote!(94),
+                    quote!(90),
+                    quote!(93),
+                    base!(10)
+                )
+            )
+            .unwrap()
+            .0,
+        vec![expected]
+    );
+    assert_eq!(exchange.account().position.size, base!(-1));
+    assert_eq!(exchange.market_state().last_trade_price(), quote!(93));
+}
+
+#[test]
+fn stop_limit_sell_order_triggered_by_mid_price() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let order = Order::stop_limit(
+        Side::Sell,
+        quote!(95),
+        quote!(96),
+        base!(2),
+        TriggerPriceSource::MidPrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(exchange.account().order_margin(), quote!(0));
+
+    // Once triggered, the order rests in the book like a regular limit order.
+    assert_eq!(
+        exchange
+            .update_state(1, bba!(quote!(94), quote!(95)))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().order_margin(), quote!(192.0384));
+
+    let mut expected = order.clone();
+    expected.mark_filled(quote!(96));
+    assert_eq!(
+        exchange
+            .update_state(2, trade!(quote!(96), base!(2), Side::Buy))
+            .unwrap()
+            .0,
+        vec![expected]
+    );
+    assert_eq!(
+        exchange.account().position,
+        Position {
+            size: base!(-2),
+            entry_price: quote!(96),
+            position_margin: quote!(192),
+            leverage: leverage!(1),
+        }
+    );
+}
+
+#[test]
+fn cancel_untriggered_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let order = Order::stop_limit(
+        Side::Buy,
+        quote!(110),
+        quote!(111),
+        base!(1),
+        TriggerPriceSource::MarkPrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(exchange.cancel_order(order.id()), Ok(order));
+    assert!(exchange.account().untriggered_orders().is_empty());
+
+    assert_eq!(
+        exchange
+            .update_state(1, bba!(quote!(111), quote!(112)))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+}
+
+#[test]
+fn stop_limit_order_rejected_once_triggered() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    // An untriggered order requires no margin, so the account can use it up in the meantime.
+    let (stop, _) = exchange
+        .submit_order(
+            Order::stop_limit(
+                Side::Buy,
+                quote!(104),
+                quote!(105),
+                base!(9),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    exchange
+        .submit_order(Order::limit(Side::Buy, quote!(95), base!(5)).unwrap())
+        .unwrap();
+
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(104), base!(1), Side::Buy))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), stop.id());
+    assert!(executed[0].filled_quantity().is_zero());
+    assert!(matches!(executed[0].rejection(), Some(Error::RiskError(_))));
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert_eq!(exchange.account().position.size, base!(0));
+}
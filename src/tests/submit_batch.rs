@@ -0,0 +1,221 @@
+use super::mock_exchange_with;
+use crate::{account_tracker::NoAccountTracker, mock_exchange_base, prelude::*, trade};
+
+/// Place a two-sided quote, which uses most of the available balance.
+fn place_quotes(exchange: &mut Exchange<NoAccountTracker, BaseCurrency>) -> Vec<u64> {
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    let batch = exchange
+        .submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(100), base!(9)).unwrap(),
+                Order::limit(Side::Sell, quote!(101), base!(9)).unwrap(),
+            ],
+            vec![],
+        )
+        .unwrap();
+    Vec::from_iter(batch.orders().iter().map(|order| order.id()))
+}
+
+#[test]
+fn submit_batch_requote() {
+    let mut exchange = mock_exchange_base();
+    let ids = place_quotes(&mut exchange);
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+    assert_eq!(exchange.account().order_margin(), quote!(909.1818));
+
+    // On its own, the new bid does not fit next to the old one.
+    assert_eq!(
+        exchange.submit_order(Order::limit(Side::Buy, quote!(99), base!(9)).unwrap()),
+        Err(Error::RiskError(RiskError::NotEnoughAvailableBalance))
+    );
+
+    let BatchOrders::Applied { orders, cancelled } = exchange
+        .submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(99), base!(9)).unwrap(),
+                Order::limit(Side::Sell, quote!(102), base!(9)).unwrap(),
+            ],
+            ids.clone(),
+        )
+        .unwrap()
+    else {
+        panic!("Without latency, the batch is applied right away");
+    };
+    assert_eq!(
+        Vec::from_iter(cancelled.iter().map(|order| order.id())),
+        ids
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+    for order in orders.iter() {
+        assert!(exchange
+            .account()
+            .active_limit_orders()
+            .contains_key(&order.id()));
+        assert!(exchange
+            .matching_engine()
+            .queue_position(order.id())
+            .is_some());
+    }
+    for id in ids {
+        assert_eq!(exchange.matching_engine().queue_position(id), None);
+    }
+    assert_eq!(exchange.account().order_margin(), quote!(918.1836));
+
+    // The new quotes get filled as usual.
+    let (executed, _) = exchange
+        .update_state(1, trade!(quote!(98), base!(9), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), orders[0].id());
+}
+
+#[test]
+fn submit_batch_rejected_as_a_whole() {
+    let mut exchange = mock_exchange_base();
+    let ids = place_quotes(&mut exchange);
+    let order_margin = exchange.account().order_margin();
+
+    // Only the old bid is cancelled, so the new bids do not fit.
+    assert_eq!(
+        exchange.submit_batch(
+            vec![
+                Order::limit(Side::Buy, quote!(99), base!(5)).unwrap(),
+                Order::limit(Side::Buy, quote!(98), base!(6)).unwrap(),
+            ],
+            vec![ids[0]],
+        ),
+        Err(Error::RiskError(RiskError::NotEnoughAvailableBalance))
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&ids[0]));
+    assert!(exchange.matching_engine().queue_position(ids[0]).is_some());
+    assert_eq!(exchange.account().order_margin(), order_margin);
+
+    assert_eq!(
+        exchange.submit_batch(
+            vec![Order::limit(Side::Buy, quote!(99), base!(1)).unwrap()],
+            vec![ids[0], 42],
+        ),
+        Err(Error::OrderIdNotFound)
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+}
+
+#[test]
+fn submit_batch_only_resting_orders() {
+    let mut exchange = mock_exchange_base();
+    let ids = place_quotes(&mut exchange);
+
+    assert_eq!(
+        exchange.submit_batch(
+            vec![Order::market(Side::Buy, base!(1)).unwrap()],
+            vec![ids[0]]
+        ),
+        Err(Error::OrderError(OrderError::InvalidBatch))
+    );
+    let mut order = Order::limit(Side::Buy, quote!(100), base!(1)).unwrap();
+    order.set_time_in_force(TimeInForce::ImmediateOrCancel);
+    assert_eq!(
+        exchange.submit_batch(vec![order], vec![ids[0]]),
+        Err(Error::OrderError(OrderError::InvalidBatch))
+    );
+    assert_eq!(
+        exchange.submit_batch(
+            vec![Order::limit(Side::Buy, quote!(101), base!(1)).unwrap()],
+            vec![ids[0]]
+        ),
+        Err(Error::OrderError(OrderError::LimitPriceAboveAsk))
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+
+    // Conditional orders may be part of a batch.
+    let batch = exchange
+        .submit_batch(
+            vec![Order::stop_market(
+                Side::Sell,
+                quote!(95),
+                base!(1),
+                TriggerPriceSource::LastTradePrice,
+            )
+            .unwrap()],
+            vec![ids[0]],
+        )
+        .unwrap();
+    assert!(!batch.is_pending());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+    assert!(exchange
+        .account()
+        .untriggered_orders()
+        .contains_key(&batch.orders()[0].id()));
+}
+
+#[test]
+fn submit_batch_with_latency() {
+    let mut exchange = mock_exchange_with(
+        NoAccountTracker,
+        PessimisticFillModel::default(),
+        |config| config.set_order_entry_latency(Latency::Fixed(100)).unwrap(),
+    );
+    let ids = place_quotes(&mut exchange);
+    exchange
+        .update_state(100, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+
+    let batch = exchange
+        .submit_batch(
+            vec![Order::limit(Side::Buy, quote!(99), base!(9)).unwrap()],
+            vec![ids[0]],
+        )
+        .unwrap();
+    // Nothing is cancelled until the batch arrives.
+    let BatchOrders::Pending {
+        orders,
+        to_be_cancelled,
+    } = batch
+    else {
+        panic!("With latency, the batch is pending");
+    };
+    assert_eq!(to_be_cancelled[0].id(), ids[0]);
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&ids[0]));
+
+    exchange
+        .update_state(200, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+    assert!(exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&orders[0].id()));
+    assert!(!exchange
+        .account()
+        .active_limit_orders()
+        .contains_key(&ids[0]));
+
+    // A batch, which no longer fits once it arrives, is returned rejected.
+    let batch = exchange
+        .submit_batch(
+            vec![Order::limit(Side::Buy, quote!(98), base!(9)).unwrap()],
+            vec![],
+        )
+        .unwrap();
+    assert!(batch.is_pending());
+    let (executed, _) = exchange
+        .update_state(300, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), batch.orders()[0].id());
+    assert_eq!(
+        executed[0].rejection(),
+        Some(&Error::RiskError(RiskError::NotEnoughAvailableBalance))
+    );
+    assert_eq!(exchange.account().active_limit_orders().len(), 2);
+}
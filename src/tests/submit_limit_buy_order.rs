@@ -6,7 +6,8 @@ fn submit_limit_buy_order_no_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -28,8 +29,9 @@ fn submit_limit_buy_order_no_position() {
     order.mark_filled(order.limit_price().unwrap());
     assert_eq!(
         exchange
-            .update_state(0, trade!(quote!(98), base!(1), Side::Sell))
-            .unwrap(),
+            .update_state(0, trade!(quote!(98), base!(5), Side::Sell))
+            .unwrap()
+            .0,
         vec![order]
     );
     exchange
@@ -55,7 +57,8 @@ fn submit_limit_buy_order_no_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(96), quote!(97)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -66,8 +69,9 @@ fn submit_limit_buy_order_no_position() {
         .unwrap();
     assert_eq!(
         exchange
-            .update_state(0, trade!(quote!(98), base!(1), Side::Buy))
-            .unwrap(),
+            .update_state(0, trade!(quote!(98), base!(5), Side::Buy))
+            .unwrap()
+            .0,
         vec![order]
     );
     assert_eq!(
@@ -93,7 +97,8 @@ fn submit_limit_buy_order_no_position_max() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -124,7 +129,8 @@ fn submit_limit_buy_order_with_long() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
     let order = Order::market(Side::Buy, base!(9)).unwrap();
@@ -143,7 +149,8 @@ fn submit_limit_buy_order_with_long() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -162,8 +169,9 @@ fn submit_limit_buy_order_with_long() {
     order.mark_filled(order.limit_price().unwrap());
     assert_eq!(
         exchange
-            .update_state(0, trade!(quote!(101), base!(1), Side::Buy))
-            .unwrap(),
+            .update_state(0, trade!(quote!(101), base!(9), Side::Buy))
+            .unwrap()
+            .0,
         vec![order]
     );
 
@@ -184,7 +192,8 @@ fn submit_limit_buy_order_with_short() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
     let order = Order::market(Side::Sell, base!(9)).unwrap();
@@ -215,8 +224,9 @@ fn submit_limit_buy_order_with_short() {
     order.mark_filled(order.limit_price().unwrap());
     assert_eq!(
         exchange
-            .update_state(0, trade!(quote!(100), base!(1), Side::Sell))
-            .unwrap(),
+            .update_state(0, trade!(quote!(100), base!(9), Side::Sell))
+            .unwrap()
+            .0,
         vec![order]
     );
 
@@ -238,7 +248,8 @@ fn submit_limit_buy_order_above_ask() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
     let order = Order::limit(Side::Buy, quote!(100), base!(9)).unwrap();
@@ -6,7 +6,8 @@ fn submit_limit_sell_order_no_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -27,8 +28,9 @@ fn submit_limit_sell_order_no_position() {
     order.mark_filled(order.limit_price().unwrap());
     assert_eq!(
         exchange
-            .update_state(0, trade!(quote!(100), base!(1), Side::Buy))
-            .unwrap(),
+            .update_state(0, trade!(quote!(100), base!(9), Side::Buy))
+            .unwrap()
+            .0,
         vec![order]
     );
     exchange
@@ -55,8 +57,9 @@ fn submit_limit_sell_order_no_position() {
     order.mark_filled(order.limit_price().unwrap());
     assert_eq!(
         exchange
-            .update_state(0, trade!(quote!(100), base!(1), Side::Sell))
-            .unwrap(),
+            .update_state(0, trade!(quote!(100), base!(9), Side::Sell))
+            .unwrap()
+            .0,
         vec![order]
     );
     assert_eq!(
@@ -82,7 +85,8 @@ fn submit_limit_sell_order_no_position_max() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -113,7 +117,8 @@ fn submit_limit_sell_order_below_bid() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
     let order = Order::limit(Side::Sell, quote!(99), base!(9)).unwrap();
@@ -1,4 +1,4 @@
-use crate::{mock_exchange_base, prelude::*, risk_engine::RiskError};
+use crate::{l2_snapshot, mock_exchange_base, prelude::*, risk_engine::RiskError};
 
 #[test]
 fn submit_market_buy_order_reject() {
@@ -6,7 +6,8 @@ fn submit_market_buy_order_reject() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -22,7 +23,8 @@ fn submit_market_buy_order_no_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -52,7 +54,8 @@ fn submit_market_buy_order_with_long_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -86,7 +89,8 @@ fn submit_market_buy_order_with_short_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -119,7 +123,8 @@ fn submit_market_buy_order_turnaround_short() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -145,3 +150,41 @@ fn submit_market_buy_order_turnaround_short() {
         quote!(100) - quote!(0.5346) - quote!(1.08) - quote!(9)
     );
 }
+
+// A large market order walks the visible levels of the book.
+#[test]
+fn submit_market_buy_order_walks_book() {
+    let mut exchange = mock_exchange_base();
+    assert_eq!(
+        exchange
+            .update_state(
+                0,
+                l2_snapshot!(
+                    vec![(quote!(99), base!(1))],
+                    vec![(quote!(100), base!(1)), (quote!(102), base!(3))]
+                )
+            )
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert_eq!(exchange.market_state().bid(), quote!(99));
+    assert_eq!(exchange.market_state().ask(), quote!(100));
+
+    let order = Order::market(Side::Buy, base!(2)).unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(101)
+        }
+    );
+    assert_eq!(exchange.account().position().size(), base!(2));
+    assert_eq!(exchange.account().position().entry_price(), quote!(101));
+    // The consumed liquidity is removed from the book.
+    assert_eq!(exchange.market_state().ask(), quote!(102));
+    assert_eq!(
+        exchange.matching_engine().best_ask(),
+        Some((quote!(102), base!(2)))
+    );
+}
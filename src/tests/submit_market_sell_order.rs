@@ -6,7 +6,8 @@ fn submit_market_sell_order_reject() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -23,7 +24,8 @@ fn submit_market_sell_order() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -52,7 +54,8 @@ fn submit_market_sell_order_with_short_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -86,7 +89,8 @@ fn submit_market_sell_order_with_long_position() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(99), quote!(100)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -119,7 +123,8 @@ fn submit_market_sell_order_turnaround_long() {
     assert_eq!(
         exchange
             .update_state(0, bba!(quote!(100), quote!(101)))
-            .unwrap(),
+            .unwrap()
+            .0,
         vec![]
     );
 
@@ -0,0 +1,106 @@
+use crate::{mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn take_profit_market_order_invalid_trigger_price() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    // A sell take profit must trigger above the current price.
+    let order = Order::take_profit_market(
+        Side::Sell,
+        quote!(99),
+        base!(1),
+        TriggerPriceSource::MidPrice,
+    )
+    .unwrap();
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(OrderError::InvalidTriggerPrice))
+    );
+}
+
+#[test]
+fn take_profit_market_sell_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+    exchange
+        .submit_order(Order::market(Side::Buy, base!(2)).unwrap())
+        .unwrap();
+
+    let order = Order::take_profit_market(
+        Side::Sell,
+        quote!(110),
+        base!(2),
+        TriggerPriceSource::LastTradePrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+
+    assert_eq!(
+        exchange
+            .update_state(1, trade!(quote!(109), base!(1), Side::Buy))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    exchange
+        .update_state(2, bba!(quote!(110), quote!(111)))
+        .unwrap();
+
+    let mut expected = order.clone();
+    expected.mark_filled(quote!(110));
+    assert_eq!(
+        exchange
+            .update_state(3, trade!(quote!(111), base!(1), Side::Buy))
+            .unwrap()
+            .0,
+        vec![expected]
+    );
+    assert_eq!(exchange.account().position.size, base!(0));
+    assert!(exchange.account().untriggered_orders().is_empty());
+}
+
+#[test]
+fn take_profit_limit_buy_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let order = Order::take_profit_limit(
+        Side::Buy,
+        quote!(95),
+        quote!(94),
+        base!(2),
+        TriggerPriceSource::MidPrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+
+    // Rising prices don't trigger a buy take profit.
+    exchange
+        .update_state(1, bba!(quote!(105), quote!(106)))
+        .unwrap();
+    assert_eq!(exchange.account().untriggered_orders().len(), 1);
+
+    exchange
+        .update_state(2, bba!(quote!(94), quote!(95)))
+        .unwrap();
+    assert!(exchange.account().untriggered_orders().is_empty());
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+
+    let mut expected = order.clone();
+    expected.mark_filled(quote!(94));
+    assert_eq!(
+        exchange
+            .update_state(3, trade!(quote!(94), base!(2), Side::Sell))
+            .unwrap()
+            .0,
+        vec![expected]
+    );
+    assert_eq!(exchange.account().position.size, base!(2));
+}
@@ -0,0 +1,167 @@
+use crate::{l2_snapshot, mock_exchange_base, prelude::*};
+
+fn limit_order(
+    side: Side,
+    price: QuoteCurrency,
+    quantity: BaseCurrency,
+    tif: TimeInForce,
+) -> Order<BaseCurrency> {
+    let mut order = Order::limit(side, price, quantity).unwrap();
+    order.set_time_in_force(tif);
+    order
+}
+
+#[test]
+fn post_only_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    assert_eq!(
+        exchange.submit_order(limit_order(
+            Side::Buy,
+            quote!(101),
+            base!(1),
+            TimeInForce::PostOnly
+        )),
+        Err(Error::OrderError(OrderError::PostOnlyWouldTake))
+    );
+    let mut order = Order::market(Side::Sell, base!(1)).unwrap();
+    order.set_time_in_force(TimeInForce::PostOnly);
+    assert_eq!(
+        exchange.submit_order(order),
+        Err(Error::OrderError(OrderError::PostOnlyWouldTake))
+    );
+
+    exchange
+        .submit_order(limit_order(
+            Side::Buy,
+            quote!(100),
+            base!(1),
+            TimeInForce::PostOnly,
+        ))
+        .unwrap();
+    assert_eq!(exchange.account().active_limit_orders().len(), 1);
+}
+
+#[test]
+fn immediate_or_cancel_order_without_depth() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    assert_eq!(
+        exchange.submit_order(limit_order(
+            Side::Buy,
+            quote!(100),
+            base!(2),
+            TimeInForce::ImmediateOrCancel
+        )),
+        Err(Error::OrderError(
+            OrderError::ImmediateOrCancelNotExecutable
+        ))
+    );
+
+    // The best ask is assumed to provide unlimited liquidity.
+    let (order, _) = exchange
+        .submit_order(limit_order(
+            Side::Buy,
+            quote!(102),
+            base!(2),
+            TimeInForce::ImmediateOrCancel,
+        ))
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(101)
+        }
+    );
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().position.size, base!(2));
+    // The taker fee is charged.
+    assert_eq!(
+        exchange.account().wallet_balance,
+        quote!(1000) - quote!(0.1212)
+    );
+}
+
+#[test]
+fn immediate_or_cancel_order_partial_fill() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(
+            0,
+            l2_snapshot!(
+                vec![(quote!(100), base!(1))],
+                vec![(quote!(101), base!(1)), (quote!(102), base!(3))]
+            ),
+        )
+        .unwrap();
+
+    let (order, _) = exchange
+        .submit_order(limit_order(
+            Side::Buy,
+            quote!(101),
+            base!(3),
+            TimeInForce::ImmediateOrCancel,
+        ))
+        .unwrap();
+    assert_eq!(order.filled_quantity(), base!(1));
+    assert_eq!(
+        order.filled(),
+        Filled::PartiallyFilled {
+            avg_fill_price: quote!(101)
+        }
+    );
+    // The remainder does not rest in the book.
+    assert!(exchange.account().active_limit_orders().is_empty());
+    assert_eq!(exchange.account().position.size, base!(1));
+    assert_eq!(
+        exchange.matching_engine().best_ask(),
+        Some((quote!(102), base!(3)))
+    );
+}
+
+#[test]
+fn fill_or_kill_order() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(
+            0,
+            l2_snapshot!(
+                vec![(quote!(100), base!(1))],
+                vec![(quote!(101), base!(1)), (quote!(102), base!(3))]
+            ),
+        )
+        .unwrap();
+
+    assert_eq!(
+        exchange.submit_order(limit_order(
+            Side::Buy,
+            quote!(101),
+            base!(4),
+            TimeInForce::FillOrKill
+        )),
+        Err(Error::OrderError(OrderError::FillOrKillNotExecutable))
+    );
+    assert_eq!(exchange.account().position.size, base!(0));
+
+    let (order, _) = exchange
+        .submit_order(limit_order(
+            Side::Buy,
+            quote!(102),
+            base!(4),
+            TimeInForce::FillOrKill,
+        ))
+        .unwrap();
+    assert_eq!(
+        order.filled(),
+        Filled::Yes {
+            fill_price: quote!(101.75)
+        }
+    );
+    assert_eq!(exchange.account().position.size, base!(4));
+}
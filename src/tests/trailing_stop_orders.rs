@@ -0,0 +1,132 @@
+use fpdec::{Dec, Decimal};
+
+use crate::{candle, mock_exchange_base, prelude::*, trade};
+
+#[test]
+fn trailing_stop_market_sell_order_absolute_offset() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, trade!(quote!(100), base!(1), Side::Buy))
+        .unwrap();
+    exchange
+        .update_state(0, bba!(quote!(100), quote!(101)))
+        .unwrap();
+
+    let order = Order::trailing_stop_market(
+        Side::Sell,
+        TrailingOffset::Absolute(quote!(5)),
+        base!(1),
+        TriggerPriceSource::LastTradePrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(order.trigger_price(), Some(quote!(95)));
+
+    // The trigger price follows rising prices.
+    exchange
+        .update_state(1, trade!(quote!(110), base!(1), Side::Buy))
+        .unwrap();
+    assert_eq!(
+        exchange.account().untriggered_orders()[&order.id()].trigger_price(),
+        Some(quote!(105))
+    );
+    // But stays put when the price falls back.
+    assert_eq!(
+        exchange
+            .update_state(2, trade!(quote!(106), base!(1), Side::Sell))
+            .unwrap()
+            .0,
+        vec![]
+    );
+    assert_eq!(
+        exchange.account().untriggered_orders()[&order.id()].trigger_price(),
+        Some(quote!(105))
+    );
+
+    exchange
+        .update_state(3, bba!(quote!(104), quote!(105)))
+        .unwrap();
+    let (executed, _) = exchange
+        .update_state(4, trade!(quote!(105), base!(1), Side::Sell))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(executed[0].id(), order.id());
+    assert_eq!(
+        executed[0].filled(),
+        Filled::Yes {
+            fill_price: quote!(104)
+        }
+    );
+    assert_eq!(exchange.account().position.size, base!(-1));
+}
+
+#[test]
+fn trailing_stop_market_buy_order_fraction_offset() {
+    let mut exchange = mock_exchange_base();
+    exchange
+        .update_state(0, bba!(quote!(99), quote!(101)))
+        .unwrap();
+
+    let order = Order::trailing_stop_market(
+        Side::Buy,
+        TrailingOffset::Fraction(Dec!(0.1)),
+        base!(1),
+        TriggerPriceSource::MidPrice,
+    )
+    .unwrap();
+    let (order, _) = exchange.submit_order(order).unwrap();
+    assert_eq!(order.trigger_price(), Some(quote!(110)));
+
+    exchange
+        .update_state(
+            1,
+            candle!(
+                quote!(79),
+                quote!(81),
+                quote!(80),
+                quote!(90),
+                quote!(70),
+                quote!(80),
+                base!(10)
+            ),
+        )
+        .unwrap();
+    assert_eq!(
+        exchange.account().untriggered_orders()[&order.id()].trigger_price(),
+        Some(quote!(88))
+    );
+
+    let (executed, _) = exchange
+        .update_state(2, bba!(quote!(88), quote!(89)))
+        .unwrap();
+    assert_eq!(executed.len(), 1);
+    assert_eq!(
+        executed[0].filled(),
+        Filled::Yes {
+            fill_price: quote!(89)
+        }
+    );
+    assert_eq!(exchange.account().position.size, base!(1));
+}
+
+#[test]
+fn trailing_stop_market_order_invalid_offset() {
+    assert_eq!(
+        Order::trailing_stop_market(
+            Side::Sell,
+            TrailingOffset::Absolute(quote!(0)),
+            base!(1),
+            TriggerPriceSource::MidPrice,
+        ),
+        Err(OrderError::InvalidTrailingOffset)
+    );
+    assert_eq!(
+        Order::trailing_stop_market(
+            Side::Sell,
+            TrailingOffset::Fraction(Dec!(1)),
+            base!(1),
+            TriggerPriceSource::MidPrice,
+        ),
+        Err(OrderError::InvalidTrailingOffset)
+    );
+}
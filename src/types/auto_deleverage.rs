@@ -0,0 +1,201 @@
+use fpdec::Decimal;
+
+use crate::types::{Currency, Fill, Leverage, MarginCurrency, QuoteCurrency};
+
+/// The rank of the account in the auto-deleveraging queue,
+/// in which the profitable positions opposing a liquidation are force-reduced,
+/// once the insurance fund can't cover its shortfall.
+/// The queue is ordered by the ADL score, which is the profit and loss percentage times the leverage,
+/// so the most profitable and highest leveraged positions are deleveraged first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdlRank<S>
+where
+    S: Currency,
+{
+    /// A fixed quantity of opposing positions is ranked ahead of the account and gets deleveraged first,
+    /// regardless of the ADL score of the account.
+    Fixed {
+        /// The quantity ranked ahead of the account. Must not be negative.
+        quantity_ahead: S,
+    },
+    /// The account is ranked by its ADL score among a synthetic population of other traders,
+    /// which hold positions on the same side as the account.
+    /// The population is a static snapshot, which is not reduced by auto-deleveraging.
+    Population(Vec<SyntheticPosition<S>>),
+}
+
+impl<S> Default for AdlRank<S>
+where
+    S: Currency,
+{
+    /// The account is first in the queue.
+    fn default() -> Self {
+        Self::Fixed {
+            quantity_ahead: S::new_zero(),
+        }
+    }
+}
+
+impl<S> AdlRank<S>
+where
+    S: Currency,
+{
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            Self::Fixed { quantity_ahead } => *quantity_ahead >= S::new_zero(),
+            Self::Population(positions) => positions.iter().all(|position| {
+                position.size > S::new_zero() && position.entry_price > QuoteCurrency::new_zero()
+            }),
+        }
+    }
+}
+
+impl<S> AdlRank<S>
+where
+    S: Currency,
+    S::PairedCurrency: MarginCurrency,
+{
+    /// The quantity of opposing positions, which is deleveraged before the account.
+    /// Synthetic positions with an ADL score equal to the one of the account are ranked ahead of it.
+    ///
+    /// # Arguments:
+    /// `is_long`: Whether the position of the account is long.
+    /// `score`: The ADL score of the account.
+    /// `mark_price`: The price at which the profit and loss of the synthetic positions is evaluated.
+    pub(crate) fn quantity_ahead(
+        &self,
+        is_long: bool,
+        score: Decimal,
+        mark_price: QuoteCurrency,
+    ) -> S {
+        match self {
+            Self::Fixed { quantity_ahead } => *quantity_ahead,
+            Self::Population(positions) => positions
+                .iter()
+                .filter(|position| {
+                    position
+                        .adl_score(is_long, mark_price)
+                        .is_some_and(|other| other >= score)
+                })
+                .fold(S::new_zero(), |acc, position| acc + position.size),
+        }
+    }
+}
+
+/// The position of another trader in the synthetic population of an `AdlRank`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticPosition<S>
+where
+    S: Currency,
+{
+    /// The absolute position size. Must be > 0.
+    pub size: S,
+    /// The entry price of the position. Must be > 0.
+    pub entry_price: QuoteCurrency,
+    /// The leverage of the position, which determines its isolated margin.
+    pub leverage: Leverage,
+}
+
+impl<S> SyntheticPosition<S>
+where
+    S: Currency,
+    S::PairedCurrency: MarginCurrency,
+{
+    /// The ADL score of the position, if it is profitable.
+    fn adl_score(&self, is_long: bool, mark_price: QuoteCurrency) -> Option<Decimal> {
+        let size = if is_long {
+            self.size
+        } else {
+            self.size.into_negative()
+        };
+        let notional = self.size.convert(self.entry_price);
+        let margin = notional / self.leverage;
+
+        adl_score(
+            S::PairedCurrency::pnl(self.entry_price, mark_price, size),
+            notional,
+            margin,
+        )
+    }
+}
+
+/// The ADL score of a position, which is the profit and loss percentage of its margin times its leverage.
+///
+/// # Arguments:
+/// `upnl`: The unrealized profit and loss of the position.
+/// `notional`: The value of the position at its entry price.
+/// `margin`: The isolated margin of the position.
+///
+/// # Returns:
+/// None, if the position is not profitable and thus not deleveraged at all.
+pub(crate) fn adl_score<M>(upnl: M, notional: M, margin: M) -> Option<Decimal>
+where
+    M: Currency,
+{
+    if upnl <= M::new_zero() || margin <= M::new_zero() {
+        return None;
+    }
+
+    Some(upnl.inner() * notional.inner() / (margin.inner() * margin.inner()))
+}
+
+/// An auto-deleveraging, where the exchange force-reduced the profitable position of the account
+/// at the bankruptcy price of a liquidation on the opposing side, whose shortfall the insurance fund could not cover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoDeleverage<S>
+where
+    S: Currency,
+{
+    /// The execution reducing the position.
+    fill: Fill<S>,
+    /// The quantity of the liquidation, which had to be deleveraged.
+    deleveraged_quantity: S,
+    /// The quantity of opposing positions, which was deleveraged before the account.
+    quantity_ahead: S,
+    /// The size of the position left after the auto-deleveraging.
+    remaining_size: S,
+}
+
+impl<S> AutoDeleverage<S>
+where
+    S: Currency,
+{
+    pub(crate) fn new(
+        fill: Fill<S>,
+        deleveraged_quantity: S,
+        quantity_ahead: S,
+        remaining_size: S,
+    ) -> Self {
+        Self {
+            fill,
+            deleveraged_quantity,
+            quantity_ahead,
+            remaining_size,
+        }
+    }
+
+    /// The execution reducing the position at the bankruptcy price of the liquidation, without any fee.
+    /// Its quantity is how much of the position got deleveraged.
+    #[inline(always)]
+    pub fn fill(&self) -> &Fill<S> {
+        &self.fill
+    }
+
+    /// The quantity of the liquidation, which had to be deleveraged across all opposing positions.
+    #[inline(always)]
+    pub fn deleveraged_quantity(&self) -> S {
+        self.deleveraged_quantity
+    }
+
+    /// The quantity of opposing positions ranked ahead of the account, which was deleveraged first.
+    #[inline(always)]
+    pub fn quantity_ahead(&self) -> S {
+        self.quantity_ahead
+    }
+
+    /// The size of the position left after the auto-deleveraging.
+    #[inline(always)]
+    pub fn remaining_size(&self) -> S {
+        self.remaining_size
+    }
+}
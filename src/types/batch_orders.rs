@@ -0,0 +1,43 @@
+use crate::types::{Currency, Order};
+
+/// The outcome of `Exchange::submit_batch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOrders<S>
+where
+    S: Currency,
+{
+    /// The batch got applied right away.
+    Applied {
+        /// The new orders with timestamp and id filled in.
+        orders: Vec<Order<S>>,
+        /// The cancelled orders.
+        cancelled: Vec<Order<S>>,
+    },
+    /// With an order entry latency, the batch is on its way to the exchange and nothing changed yet.
+    /// Should it be rejected once it arrives, `Exchange::update_state` returns its new orders,
+    /// tagged by `Order::rejection`.
+    Pending {
+        /// The new orders with timestamp and id filled in.
+        orders: Vec<Order<S>>,
+        /// The open orders, which get cancelled once the batch arrives.
+        to_be_cancelled: Vec<Order<S>>,
+    },
+}
+
+impl<S> BatchOrders<S>
+where
+    S: Currency,
+{
+    /// The new orders of the batch with timestamp and id filled in.
+    pub fn orders(&self) -> &[Order<S>] {
+        match self {
+            Self::Applied { orders, .. } | Self::Pending { orders, .. } => orders,
+        }
+    }
+
+    /// Whether the batch is still on its way to the exchange.
+    #[inline(always)]
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending { .. })
+    }
+}
@@ -27,6 +27,8 @@ macro_rules! base {
     PartialEq,
     PartialOrd,
     Ord,
+    Serialize,
+    Deserialize,
     Add,
     Sub,
     Mul,
@@ -100,6 +102,22 @@ impl MarginCurrency for BaseCurrency {
         }
         quantity.convert(entry_price) - quantity.convert(exit_price)
     }
+
+    fn exit_price(
+        entry_price: QuoteCurrency,
+        pnl: Self,
+        quantity: QuoteCurrency,
+    ) -> Option<QuoteCurrency> {
+        if quantity.is_zero() {
+            return None;
+        }
+        let denominator = quantity.convert(entry_price) - pnl;
+        if denominator.is_zero() {
+            return None;
+        }
+        let exit_price = QuoteCurrency::new(quantity.inner() / denominator.0);
+        (exit_price > QuoteCurrency::new_zero()).then_some(exit_price)
+    }
 }
 
 /// ### Arithmetic with `Decimal` on the right hand side
@@ -196,4 +214,25 @@ mod tests {
             base!(2.5)
         );
     }
+
+    #[test]
+    fn inverse_futures_exit_price() {
+        assert_eq!(
+            BaseCurrency::exit_price(quote!(100.0), base!(2.0), quote!(1000.0)),
+            Some(quote!(125.0))
+        );
+        assert_eq!(
+            BaseCurrency::exit_price(quote!(100.0), base!(-2.5), quote!(1000.0)),
+            Some(quote!(80.0))
+        );
+        assert_eq!(
+            BaseCurrency::exit_price(quote!(100.0), base!(2.5), quote!(-1000.0)),
+            Some(quote!(80.0))
+        );
+        // A short can't lose its whole value.
+        assert_eq!(
+            BaseCurrency::exit_price(quote!(100.0), base!(-10.0), quote!(-1000.0)),
+            None
+        );
+    }
 }
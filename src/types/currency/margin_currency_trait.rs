@@ -23,4 +23,20 @@ pub trait MarginCurrency: Currency {
     ) -> S::PairedCurrency
     where
         S: Currency;
+
+    /// Compute the exit price at which a position realizes the given profit and loss,
+    /// which is the inverse of `pnl`.
+    ///
+    /// # Arguments:
+    /// `entry_price`: The price at which the position was initiated.
+    /// `pnl`: The profit and loss to realize.
+    /// `quantity`: The position size, negative denoting a short position.
+    ///
+    /// # Returns:
+    /// The exit price, if a positive price realizes the `pnl`.
+    fn exit_price(
+        entry_price: QuoteCurrency,
+        pnl: Self,
+        quantity: Self::PairedCurrency,
+    ) -> Option<QuoteCurrency>;
 }
@@ -27,6 +27,8 @@ macro_rules! quote {
     PartialEq,
     PartialOrd,
     Ord,
+    Serialize,
+    Deserialize,
     Add,
     Sub,
     Mul,
@@ -100,6 +102,18 @@ impl MarginCurrency for QuoteCurrency {
         }
         quantity.convert(exit_price) - quantity.convert(entry_price)
     }
+
+    fn exit_price(
+        entry_price: QuoteCurrency,
+        pnl: Self,
+        quantity: BaseCurrency,
+    ) -> Option<QuoteCurrency> {
+        if quantity.is_zero() {
+            return None;
+        }
+        let exit_price = entry_price + pnl.0 / quantity.inner();
+        (exit_price > QuoteCurrency::new_zero()).then_some(exit_price)
+    }
 }
 
 /// ### Arithmetic with `Rational` on the right hand side
@@ -196,4 +210,24 @@ mod tests {
             quote!(100.0)
         );
     }
+
+    #[test]
+    fn linear_futures_exit_price() {
+        assert_eq!(
+            QuoteCurrency::exit_price(quote!(100.0), quote!(100.0), base!(10.0)),
+            Some(quote!(110.0))
+        );
+        assert_eq!(
+            QuoteCurrency::exit_price(quote!(100.0), quote!(100.0), base!(-10.0)),
+            Some(quote!(90.0))
+        );
+        assert_eq!(
+            QuoteCurrency::exit_price(quote!(100.0), quote!(-900.0), base!(10.0)),
+            Some(quote!(10.0))
+        );
+        assert_eq!(
+            QuoteCurrency::exit_price(quote!(100.0), quote!(-1000.0), base!(10.0)),
+            None
+        );
+    }
 }
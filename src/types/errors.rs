@@ -34,6 +34,38 @@ pub enum OrderError {
     #[error("Invalid trigger price for order. e.g.: sell stop market order trigger price > ask")]
     InvalidTriggerPrice,
 
+    #[error(
+        "The offset of a trailing stop order must be positive and a fractional offset below 1"
+    )]
+    InvalidTrailingOffset,
+
+    #[error("A post only order would have been executed immediately as taker")]
+    PostOnlyWouldTake,
+
+    #[error("An immediate or cancel order could not be executed at all")]
+    ImmediateOrCancelNotExecutable,
+
+    #[error("A fill or kill order could not be executed in full")]
+    FillOrKillNotExecutable,
+
+    #[error("A reduce only order would increase the position")]
+    ReduceOnlyWouldIncreasePosition,
+
+    #[error("The amended order quantity must be larger than the filled quantity")]
+    AmendedQuantityBelowFilled,
+
+    #[error("The orders can't be linked, e.g.: a market order in a one-cancels-other group")]
+    InvalidOrderGroup,
+
+    #[error("A batch may only contain orders which rest in the book or wait for their trigger")]
+    InvalidBatch,
+
+    #[error("The expiry timestamp of the order has already passed")]
+    InvalidExpiry,
+
+    #[error("The display quantity of an iceberg order must be positive and not exceed its size")]
+    InvalidDisplayQuantity,
+
     #[error("order size must be > 0")]
     OrderSizeMustBePositive,
 
@@ -84,6 +116,36 @@ pub enum Error {
     #[error("The provided starting balance must be > 0")]
     InvalidStartingBalance,
 
+    #[error("A sampled latency requires at least one sample")]
+    InvalidLatency,
+
+    #[error("The fraction of the candle volume must be in the range (0, 1]")]
+    InvalidVolumeFraction,
+
+    #[error("The market impact requires a non negative coefficient and a positive duration")]
+    InvalidMarketImpact,
+
+    #[error("A rate limit requires a positive window and budget")]
+    InvalidRateLimit,
+
+    #[error("The liquidation fee must be in the range [0, 1)")]
+    InvalidLiquidationFee,
+
+    #[error("The fraction of a partial liquidation must be in the range (0, 1)")]
+    InvalidLiquidationMode,
+
+    #[error("The insurance fund balance must not be negative")]
+    InvalidInsuranceFundBalance,
+
+    #[error("The share of a socialized loss must be in the range [0, 1]")]
+    InvalidLossPolicy,
+
+    #[error("The ADL rank has a negative quantity ahead or a synthetic position without size or entry price")]
+    InvalidAdlRank,
+
+    #[error("The request budget of a rate limit is used up for the current window")]
+    RateLimitExceeded,
+
     #[error("Some price in MarketUpdate is too low.")]
     MarketUpdatePriceTooLow,
 
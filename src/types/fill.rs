@@ -0,0 +1,128 @@
+use crate::types::{Currency, Order, QuoteCurrency, Side};
+
+/// A single execution of an order, as settled by the `ClearingHouse`.
+/// Partially filled orders produce one `Fill` for every execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill<S>
+where
+    S: Currency,
+{
+    /// The id of the order, assigned by the exchange.
+    order_id: u64,
+    /// The order id provided by the user.
+    user_order_id: Option<u64>,
+    /// The side of the order.
+    side: Side,
+    /// The filled quantity, which is always positive.
+    quantity: S,
+    /// The execution price.
+    price: QuoteCurrency,
+    /// The fee paid for this execution, denoted in the margin currency.
+    fee: S::PairedCurrency,
+    /// Whether the order provided liquidity and paid the maker fee.
+    is_maker: bool,
+    /// The realized profit and loss of the part of the position this execution closed, excluding the fee.
+    realized_pnl: S::PairedCurrency,
+    /// The timestamp in nanoseconds of the execution.
+    timestamp_ns: i64,
+}
+
+impl<S> Fill<S>
+where
+    S: Currency,
+{
+    /// Create a new fill of the `order`, whose fee and realized profit and loss get set once it is settled.
+    pub(crate) fn new(
+        order: &Order<S>,
+        quantity: S,
+        price: QuoteCurrency,
+        is_maker: bool,
+        timestamp_ns: i64,
+    ) -> Self {
+        Self {
+            order_id: order.id(),
+            user_order_id: *order.user_order_id(),
+            side: order.side(),
+            quantity,
+            price,
+            fee: S::PairedCurrency::new_zero(),
+            is_maker,
+            realized_pnl: S::PairedCurrency::new_zero(),
+            timestamp_ns,
+        }
+    }
+
+    /// The id of the order, assigned by the exchange.
+    #[inline(always)]
+    pub fn order_id(&self) -> u64 {
+        self.order_id
+    }
+
+    /// The order id provided by the user.
+    #[inline(always)]
+    pub fn user_order_id(&self) -> Option<u64> {
+        self.user_order_id
+    }
+
+    /// The side of the order.
+    #[inline(always)]
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// The filled quantity, which is always positive.
+    #[inline(always)]
+    pub fn quantity(&self) -> S {
+        self.quantity
+    }
+
+    /// The execution price.
+    #[inline(always)]
+    pub fn price(&self) -> QuoteCurrency {
+        self.price
+    }
+
+    /// The fee paid for this execution, denoted in the margin currency.
+    #[inline(always)]
+    pub fn fee(&self) -> S::PairedCurrency {
+        self.fee
+    }
+
+    /// Whether the order provided liquidity and paid the maker fee, or took liquidity and paid the taker fee.
+    #[inline(always)]
+    pub fn is_maker(&self) -> bool {
+        self.is_maker
+    }
+
+    /// The realized profit and loss of the part of the position this execution closed,
+    /// excluding the fee and denoted in the margin currency.
+    /// Zero, if the execution only increased the position.
+    #[inline(always)]
+    pub fn realized_pnl(&self) -> S::PairedCurrency {
+        self.realized_pnl
+    }
+
+    /// The timestamp in nanoseconds of the execution.
+    #[inline(always)]
+    pub fn timestamp_ns(&self) -> i64 {
+        self.timestamp_ns
+    }
+
+    /// The quantity, where a negative number indicates a sell.
+    pub(crate) fn signed_quantity(&self) -> S {
+        match self.side {
+            Side::Buy => self.quantity,
+            Side::Sell => self.quantity.into_negative(),
+        }
+    }
+
+    /// Record the outcome of the settlement.
+    pub(crate) fn set_settlement(
+        &mut self,
+        fee: S::PairedCurrency,
+        realized_pnl: S::PairedCurrency,
+    ) {
+        self.fee = fee;
+        self.realized_pnl = realized_pnl;
+    }
+}
@@ -0,0 +1,118 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The actions of a user, which have to travel to the exchange.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub enum OrderAction {
+    /// Submitting a new order.
+    Submit,
+    /// Amending an active limit order.
+    Amend,
+    /// Cancelling an open order.
+    Cancel,
+}
+
+/// The one-way latency between the user and the exchange, in nanoseconds.
+/// With any latency, order actions become pending and only take effect,
+/// once the market clock has passed their arrival time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum Latency {
+    /// Order actions take effect immediately.
+    #[default]
+    None,
+    /// Every order action takes the same time to arrive.
+    Fixed(u64),
+    /// The latency of each order action is drawn from an empirical distribution,
+    /// e.g.: latencies measured against a live exchange.
+    Sampled {
+        /// The observed latencies, which are drawn uniformly. Must not be empty.
+        samples: Vec<u64>,
+        /// The seed of the random number generator, making a backtest reproducible.
+        seed: u64,
+    },
+    /// Each kind of order action has its own latency.
+    PerAction {
+        /// The latency of new order submissions.
+        submit: u64,
+        /// The latency of order amendments.
+        amend: u64,
+        /// The latency of order cancellations.
+        cancel: u64,
+    },
+}
+
+/// Draws the latency of each order action from the configured `Latency`.
+#[derive(Debug, Clone)]
+pub(crate) struct LatencySampler {
+    latency: Latency,
+    rng: StdRng,
+}
+
+impl LatencySampler {
+    pub(crate) fn new(latency: Latency) -> Self {
+        let seed = match &latency {
+            Latency::Sampled { seed, .. } => *seed,
+            _ => 0,
+        };
+        Self {
+            latency,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Whether order actions take effect immediately.
+    #[inline(always)]
+    pub(crate) fn is_immediate(&self) -> bool {
+        self.latency == Latency::None
+    }
+
+    /// Draw the latency of an `action` in nanoseconds.
+    pub(crate) fn sample(&mut self, action: OrderAction) -> u64 {
+        match &self.latency {
+            Latency::None => 0,
+            Latency::Fixed(latency) => *latency,
+            Latency::Sampled { samples, .. } => samples[self.rng.gen_range(0..samples.len())],
+            Latency::PerAction {
+                submit,
+                amend,
+                cancel,
+            } => match action {
+                OrderAction::Submit => *submit,
+                OrderAction::Amend => *amend,
+                OrderAction::Cancel => *cancel,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_sampler_per_action() {
+        let mut sampler = LatencySampler::new(Latency::PerAction {
+            submit: 10,
+            amend: 20,
+            cancel: 30,
+        });
+        assert!(!sampler.is_immediate());
+        assert_eq!(sampler.sample(OrderAction::Submit), 10);
+        assert_eq!(sampler.sample(OrderAction::Amend), 20);
+        assert_eq!(sampler.sample(OrderAction::Cancel), 30);
+    }
+
+    #[test]
+    fn latency_sampler_sampled_is_reproducible() {
+        let latency = Latency::Sampled {
+            samples: vec![100, 200, 300],
+            seed: 42,
+        };
+        let mut a = LatencySampler::new(latency.clone());
+        let mut b = LatencySampler::new(latency);
+        for _ in 0..100 {
+            let sample = a.sample(OrderAction::Submit);
+            assert!([100, 200, 300].contains(&sample));
+            assert_eq!(sample, b.sample(OrderAction::Submit));
+        }
+    }
+}
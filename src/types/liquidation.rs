@@ -0,0 +1,151 @@
+use fpdec::Decimal;
+
+use crate::types::{Currency, Fill, Order, QuoteCurrency};
+
+/// How much of a position is liquidated once it can't meet the maintenance margin.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum LiquidationMode {
+    /// Close the whole position at once.
+    #[default]
+    Full,
+    /// Reduce the position step by step until it meets the maintenance margin again,
+    /// like several large perpetual venues do.
+    /// The isolated margin of the closed part stays with the remaining position,
+    /// which moves its liquidation price away from the market.
+    Partial {
+        /// The fraction of the position at the start of the liquidation, which is closed per step,
+        /// rounded up to the quantity step size. Must be in the range (0, 1).
+        fraction: Decimal,
+    },
+}
+
+/// The price at which the exchange closes a liquidated position.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum LiquidationClosePrice {
+    /// Close at the liquidation price, so the liquidation fee is paid from the remaining maintenance margin.
+    #[default]
+    LiquidationPrice,
+    /// Close at the bankruptcy price, where the whole position margin is lost
+    /// and nothing is left to pay the liquidation fee.
+    BankruptcyPrice,
+}
+
+/// What happens to a shortfall of a liquidation, which the insurance fund can't cover.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum LossPolicy {
+    /// The exchange writes off the shortfall, so the account is not affected.
+    #[default]
+    WriteOff,
+    /// The shortfall is socialized across the profitable traders,
+    /// so the account pays its `share` of it, limited to its available balance.
+    SocializedLoss {
+        /// The fraction of the shortfall the account pays, e.g. its share of the open interest.
+        /// Must be in the range [0, 1].
+        share: Decimal,
+    },
+    /// The shortfall is clawed back from the liquidated account,
+    /// so it may lose more than its isolated position margin, limited to its available balance.
+    Clawback,
+    /// The shortfall is avoided by force-reducing the profitable positions opposing the liquidation
+    /// at its bankruptcy price, in the order of their ADL rank.
+    /// The account gets auto-deleveraged by the liquidations of other traders,
+    /// once the queue reaches its `AdlRank`, see `Config::set_adl_rank`.
+    AutoDeleverage,
+}
+
+/// A liquidation, where the exchange took over the position of the account,
+/// because it could not meet the maintenance margin anymore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Liquidation<S>
+where
+    S: Currency,
+{
+    /// The execution closing the position.
+    fill: Fill<S>,
+    /// The liquidation price of the position.
+    liquidation_price: QuoteCurrency,
+    /// The bankruptcy price of the position, if any.
+    bankruptcy_price: Option<QuoteCurrency>,
+    /// The open orders, which got cancelled.
+    cancelled_orders: Vec<Order<S>>,
+    /// The size of the position left after this liquidation.
+    remaining_size: S,
+    /// The surplus the insurance fund received, or the shortfall it covered if negative.
+    insurance_fund_change: S::PairedCurrency,
+    /// The part of an uncovered shortfall, which the account paid according to the `LossPolicy`.
+    socialized_loss: S::PairedCurrency,
+}
+
+impl<S> Liquidation<S>
+where
+    S: Currency,
+{
+    pub(crate) fn new(
+        fill: Fill<S>,
+        liquidation_price: QuoteCurrency,
+        bankruptcy_price: Option<QuoteCurrency>,
+        cancelled_orders: Vec<Order<S>>,
+        remaining_size: S,
+        insurance_fund_change: S::PairedCurrency,
+        socialized_loss: S::PairedCurrency,
+    ) -> Self {
+        Self {
+            fill,
+            liquidation_price,
+            bankruptcy_price,
+            cancelled_orders,
+            remaining_size,
+            insurance_fund_change,
+            socialized_loss,
+        }
+    }
+
+    /// The execution closing the position, or a part of it with `LiquidationMode::Partial`,
+    /// whose fee is the liquidation fee.
+    /// Its quantity is how much got liquidated.
+    #[inline(always)]
+    pub fn fill(&self) -> &Fill<S> {
+        &self.fill
+    }
+
+    /// The liquidation price of the position, at which it got liquidated.
+    #[inline(always)]
+    pub fn liquidation_price(&self) -> QuoteCurrency {
+        self.liquidation_price
+    }
+
+    /// The bankruptcy price of the position, at which its whole position margin would have been lost.
+    /// None, if no price could have lost the whole position margin.
+    #[inline(always)]
+    pub fn bankruptcy_price(&self) -> Option<QuoteCurrency> {
+        self.bankruptcy_price
+    }
+
+    /// The open orders of the account, which got cancelled.
+    /// With `LiquidationMode::Partial`, they are cancelled by the first step.
+    #[inline(always)]
+    pub fn cancelled_orders(&self) -> &[Order<S>] {
+        &self.cancelled_orders
+    }
+
+    /// The size of the position left after this liquidation,
+    /// which is only not zero for a step of `LiquidationMode::Partial`.
+    #[inline(always)]
+    pub fn remaining_size(&self) -> S {
+        self.remaining_size
+    }
+
+    /// The surplus the insurance fund received from this liquidation, including the liquidation fee,
+    /// or the shortfall it covered if negative.
+    #[inline(always)]
+    pub fn insurance_fund_change(&self) -> S::PairedCurrency {
+        self.insurance_fund_change
+    }
+
+    /// The part of a shortfall the insurance fund could not cover,
+    /// which the account paid according to the `LossPolicy`.
+    #[inline(always)]
+    pub fn socialized_loss(&self) -> S::PairedCurrency {
+        self.socialized_loss
+    }
+}
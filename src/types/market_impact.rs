@@ -0,0 +1,192 @@
+use fpdec::{Decimal, Quantize};
+
+use crate::{
+    types::{Currency, QuoteCurrency, Side},
+    utils::{decimal_sqrt, decimal_to_f64, f64_to_decimal, min},
+};
+
+/// Models how the taker fills of market orders move the price against the user,
+/// instead of assuming unlimited liquidity at the top of the book.
+/// The impact is relative to the price, rounded to the tick size and shifts the simulated prices for a while.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum MarketImpact {
+    /// Taker fills don't move the price.
+    #[default]
+    None,
+    /// The price moves by `coefficient` times the filled quantity.
+    Linear {
+        /// The relative price impact per unit of quantity.
+        coefficient: Decimal,
+        /// The impact disappears once no taker fill happened for this many nanoseconds.
+        duration_ns: u64,
+    },
+    /// The price moves by `coefficient` times the square root of the filled quantity,
+    /// as in the model of Almgren et al.
+    /// The `coefficient` is usually the volatility divided by the square root of the daily volume.
+    SquareRoot {
+        /// The relative price impact per square root of the quantity.
+        coefficient: Decimal,
+        /// The impact disappears once no taker fill happened for this many nanoseconds.
+        duration_ns: u64,
+    },
+    /// The price moves by `coefficient` times the filled quantity
+    /// and the accumulated impact decays exponentially over time.
+    Transient {
+        /// The relative price impact per unit of quantity.
+        coefficient: Decimal,
+        /// The time in nanoseconds after which half of the impact has decayed.
+        half_life_ns: u64,
+    },
+}
+
+/// Keeps track of the price impact of the taker fills of the user.
+#[derive(Debug, Clone)]
+pub(crate) struct PriceImpact {
+    model: MarketImpact,
+    /// The current shift of the prices, which is positive after buying.
+    offset: QuoteCurrency,
+    /// The timestamp of the last taker fill.
+    last_fill_ts_ns: i64,
+    /// The timestamp the offset was last decayed at.
+    last_decay_ts_ns: i64,
+}
+
+impl PriceImpact {
+    pub(crate) fn new(model: MarketImpact) -> Self {
+        Self {
+            model,
+            offset: QuoteCurrency::new_zero(),
+            last_fill_ts_ns: 0,
+            last_decay_ts_ns: 0,
+        }
+    }
+
+    /// The current shift of the prices, which is positive after buying.
+    #[inline(always)]
+    pub(crate) fn offset(&self) -> QuoteCurrency {
+        self.offset
+    }
+
+    /// The impact of a taker fill on the price, which is negative for sells.
+    ///
+    /// # Arguments:
+    /// `side`: The side of the taker order.
+    /// `quantity`: The filled quantity.
+    /// `price`: The price the order would be filled at without impact.
+    /// `tick_size`: The impact is rounded to the tick size.
+    pub(crate) fn fill_impact<S>(
+        &self,
+        side: Side,
+        quantity: S,
+        price: QuoteCurrency,
+        tick_size: QuoteCurrency,
+    ) -> QuoteCurrency
+    where
+        S: Currency,
+    {
+        let fraction = match self.model {
+            MarketImpact::None => return QuoteCurrency::new_zero(),
+            MarketImpact::Linear { coefficient, .. }
+            | MarketImpact::Transient { coefficient, .. } => coefficient * quantity.inner(),
+            MarketImpact::SquareRoot { coefficient, .. } => {
+                coefficient * decimal_sqrt(quantity.inner())
+            }
+        };
+        let impact = QuoteCurrency::new((price.inner() * fraction).quantize(tick_size.inner()));
+        match side {
+            Side::Buy => impact,
+            // A sell can never push the price to zero.
+            Side::Sell => min(impact, price - tick_size).into_negative(),
+        }
+    }
+
+    /// Add the `impact` of a taker fill at `ts_ns`.
+    pub(crate) fn add(&mut self, impact: QuoteCurrency, ts_ns: i64) {
+        self.offset += impact;
+        self.last_fill_ts_ns = ts_ns;
+    }
+
+    /// Let the impact decay until `ts_ns`.
+    ///
+    /// # Returns:
+    /// The remaining shift of the prices.
+    pub(crate) fn decay(&mut self, ts_ns: i64, tick_size: QuoteCurrency) -> QuoteCurrency {
+        match self.model {
+            MarketImpact::None => {}
+            MarketImpact::Linear { duration_ns, .. }
+            | MarketImpact::SquareRoot { duration_ns, .. } => {
+                if ts_ns - self.last_fill_ts_ns > duration_ns as i64 {
+                    self.offset = QuoteCurrency::new_zero();
+                }
+            }
+            MarketImpact::Transient { half_life_ns, .. } => {
+                let elapsed = (ts_ns - self.last_decay_ts_ns) as f64;
+                let factor = 0.5_f64.powf(elapsed / half_life_ns as f64);
+                let offset = decimal_to_f64(self.offset.inner()) * factor;
+                self.offset = QuoteCurrency::new(
+                    f64_to_decimal(offset, tick_size.inner()).quantize(tick_size.inner()),
+                );
+            }
+        }
+        self.last_decay_ts_ns = ts_ns;
+
+        self.offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fpdec::Dec;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn price_impact_linear() {
+        let mut impact = PriceImpact::new(MarketImpact::Linear {
+            coefficient: Dec!(0.01),
+            duration_ns: 100,
+        });
+        let tick = quote!(1);
+        assert_eq!(
+            impact.fill_impact(Side::Buy, base!(2), quote!(101), tick),
+            quote!(2)
+        );
+        assert_eq!(
+            impact.fill_impact(Side::Sell, base!(2), quote!(100), tick),
+            quote!(-2)
+        );
+        impact.add(quote!(2), 0);
+        assert_eq!(impact.decay(100, tick), quote!(2));
+        assert_eq!(impact.decay(101, tick), quote!(0));
+    }
+
+    #[test]
+    fn price_impact_square_root() {
+        let impact = PriceImpact::new(MarketImpact::SquareRoot {
+            coefficient: Dec!(0.01),
+            duration_ns: 100,
+        });
+        assert_eq!(
+            impact.fill_impact(Side::Buy, base!(4), quote!(100), quote!(1)),
+            quote!(2)
+        );
+        assert_eq!(
+            impact.fill_impact(Side::Buy, base!(16), quote!(100), quote!(1)),
+            quote!(4)
+        );
+    }
+
+    #[test]
+    fn price_impact_transient() {
+        let mut impact = PriceImpact::new(MarketImpact::Transient {
+            coefficient: Dec!(0.01),
+            half_life_ns: 100,
+        });
+        let tick = quote!(0.1);
+        impact.add(quote!(4), 0);
+        assert_eq!(impact.decay(100, tick), quote!(2));
+        assert_eq!(impact.decay(200, tick), quote!(1));
+        assert_eq!(impact.decay(400, tick), quote!(0.2));
+    }
+}
@@ -1,3 +1,5 @@
+use fpdec::Decimal;
+
 use super::{Currency, Side};
 use crate::types::QuoteCurrency;
 
@@ -35,10 +37,52 @@ where
         bid: QuoteCurrency,
         /// The best ask at the time of candle creation
         ask: QuoteCurrency,
-        /// The low price of the candle
-        low: QuoteCurrency,
+        /// The open price of the candle
+        open: QuoteCurrency,
         /// The high price of the candle
         high: QuoteCurrency,
+        /// The low price of the candle
+        low: QuoteCurrency,
+        /// The close price of the candle
+        close: QuoteCurrency,
+        /// The quantity traded over the period of the candle.
+        volume: S,
+    },
+    /// An update to a single price level of the level 2 order book.
+    /// This is used by the `MatchingEngine` to simulate the queue position of resting limit orders.
+    L2Update {
+        /// The side of the book, where `Buy` denotes the bids and `Sell` the asks.
+        side: Side,
+        /// The price of the level.
+        price: QuoteCurrency,
+        /// The new total quantity resting at this price level.
+        /// A quantity of zero removes the level from the book.
+        quantity: S,
+    },
+    /// A snapshot of the level 2 order book, which replaces all existing levels.
+    /// Market orders walk the visible levels of the book to determine their fill price.
+    L2Snapshot {
+        /// The price levels of the bids with their resting quantity.
+        bids: Vec<(QuoteCurrency, S)>,
+        /// The price levels of the asks with their resting quantity.
+        asks: Vec<(QuoteCurrency, S)>,
+    },
+    /// The liquidation of another trader, as published by the liquidation feed of many venues.
+    /// The exchange closes the position in the market and the insurance fund covers the difference
+    /// to its bankruptcy price, which may auto-deleverage the account with `LossPolicy::AutoDeleverage`.
+    Liquidation {
+        /// The side of the liquidation order, where `Sell` closes a long and `Buy` closes a short position.
+        side: Side,
+        /// The bankruptcy price of the liquidated position.
+        bankruptcy_price: QuoteCurrency,
+        /// The liquidated quantity.
+        quantity: S,
+    },
+    /// The end of a funding period of a perpetual futures contract,
+    /// where the value of the position at the mark price times the `rate` is exchanged between longs and shorts.
+    Funding {
+        /// The funding rate, where a positive rate means longs pay shorts and a negative one shorts pay longs.
+        rate: Decimal,
     },
 }
 
@@ -62,19 +106,66 @@ macro_rules! trade {
     }};
 }
 
-/// Creates the `MarketUpdate::Candle! variant.
+/// Creates the `MarketUpdate::Candle` variant.
+/// The arguments are the best bid and ask, followed by the open, high, low, close and volume of the candle.
 #[macro_export]
 macro_rules! candle {
-    ( $b:expr, $a:expr, $l:expr, $h:expr ) => {{
+    ( $b:expr, $a:expr, $o:expr, $h:expr, $l:expr, $c:expr, $v:expr ) => {{
         $crate::prelude::MarketUpdate::Candle {
             bid: $b,
             ask: $a,
-            low: $l,
+            open: $o,
             high: $h,
+            low: $l,
+            close: $c,
+            volume: $v,
         }
     }};
 }
 
+/// Creates the `MarketUpdate::L2Update` variant.
+#[macro_export]
+macro_rules! l2_update {
+    ( $side:expr, $price:expr, $quantity:expr ) => {{
+        $crate::prelude::MarketUpdate::L2Update {
+            side: $side,
+            price: $price,
+            quantity: $quantity,
+        }
+    }};
+}
+
+/// Creates the `MarketUpdate::L2Snapshot` variant.
+#[macro_export]
+macro_rules! l2_snapshot {
+    ( $bids:expr, $asks:expr ) => {{
+        $crate::prelude::MarketUpdate::L2Snapshot {
+            bids: $bids,
+            asks: $asks,
+        }
+    }};
+}
+
+/// Creates the `MarketUpdate::Liquidation` variant.
+#[macro_export]
+macro_rules! liquidation {
+    ( $side:expr, $bankruptcy_price:expr, $quantity:expr ) => {{
+        $crate::prelude::MarketUpdate::Liquidation {
+            side: $side,
+            bankruptcy_price: $bankruptcy_price,
+            quantity: $quantity,
+        }
+    }};
+}
+
+/// Creates the `MarketUpdate::Funding` variant.
+#[macro_export]
+macro_rules! funding {
+    ( $rate:expr ) => {{
+        $crate::prelude::MarketUpdate::Funding { rate: $rate }
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,17 +186,78 @@ mod tests {
 
     #[test]
     fn candle_macro() {
-        let c: MarketUpdate<BaseCurrency> =
-            candle!(quote!(100.0), quote!(100.1), quote!(100.0), quote!(100.1));
+        let c: MarketUpdate<BaseCurrency> = candle!(
+            quote!(100.0),
+            quote!(100.1),
+            quote!(100.05),
+            quote!(100.1),
+            quote!(100.0),
+            quote!(100.05),
+            base!(5)
+        );
 
         assert_eq!(
             c,
             MarketUpdate::Candle {
                 bid: quote!(100.0),
                 ask: quote!(100.1),
-                low: quote!(100.0),
+                open: quote!(100.05),
                 high: quote!(100.1),
+                low: quote!(100.0),
+                close: quote!(100.05),
+                volume: base!(5),
+            }
+        )
+    }
+
+    #[test]
+    fn l2_update_macro() {
+        let u: MarketUpdate<BaseCurrency> = l2_update!(Side::Buy, quote!(100.0), base!(2.5));
+
+        assert_eq!(
+            u,
+            MarketUpdate::L2Update {
+                side: Side::Buy,
+                price: quote!(100.0),
+                quantity: base!(2.5),
+            }
+        )
+    }
+
+    #[test]
+    fn l2_snapshot_macro() {
+        let s: MarketUpdate<BaseCurrency> = l2_snapshot!(
+            vec![(quote!(100.0), base!(1.0))],
+            vec![(quote!(100.5), base!(2.0))]
+        );
+
+        assert_eq!(
+            s,
+            MarketUpdate::L2Snapshot {
+                bids: vec![(quote!(100.0), base!(1.0))],
+                asks: vec![(quote!(100.5), base!(2.0))],
             }
         )
     }
+
+    #[test]
+    fn liquidation_macro() {
+        let l: MarketUpdate<BaseCurrency> = liquidation!(Side::Sell, quote!(95.5), base!(3));
+
+        assert_eq!(
+            l,
+            MarketUpdate::Liquidation {
+                side: Side::Sell,
+                bankruptcy_price: quote!(95.5),
+                quantity: base!(3),
+            }
+        )
+    }
+
+    #[test]
+    fn funding_macro() {
+        let f: MarketUpdate<BaseCurrency> = funding!(Dec!(0.0001));
+
+        assert_eq!(f, MarketUpdate::Funding { rate: Dec!(0.0001) })
+    }
 }
@@ -1,17 +1,41 @@
+mod auto_deleverage;
+mod batch_orders;
 mod currency;
 mod errors;
 mod fee;
+mod fill;
+mod latency;
 mod leverage;
+mod liquidation;
+mod market_impact;
 mod market_update;
 mod order;
 mod order_type;
+mod rate_limit;
 mod side;
+mod time_in_force;
+mod trailing_offset;
+mod trigger_price_source;
 
+pub(crate) use auto_deleverage::adl_score;
+pub use auto_deleverage::{AdlRank, AutoDeleverage, SyntheticPosition};
+pub use batch_orders::BatchOrders;
 pub use currency::{BaseCurrency, Currency, MarginCurrency, QuoteCurrency};
 pub use errors::*;
 pub use fee::{Fee, FeeType};
+pub use fill::Fill;
+pub(crate) use latency::LatencySampler;
+pub use latency::{Latency, OrderAction};
 pub use leverage::Leverage;
+pub use liquidation::{Liquidation, LiquidationClosePrice, LiquidationMode, LossPolicy};
+pub use market_impact::MarketImpact;
+pub(crate) use market_impact::PriceImpact;
 pub use market_update::MarketUpdate;
 pub use order::{Filled, Order};
 pub use order_type::OrderType;
+pub use rate_limit::RateLimit;
+pub(crate) use rate_limit::RateLimiter;
 pub use side::Side;
+pub use time_in_force::TimeInForce;
+pub use trailing_offset::TrailingOffset;
+pub use trigger_price_source::TriggerPriceSource;
@@ -1,4 +1,9 @@
-use crate::types::{Currency, OrderError, OrderType, QuoteCurrency, Side};
+use fpdec::Decimal;
+
+use crate::types::{
+    Currency, Error, OrderError, OrderType, QuoteCurrency, Side, TimeInForce, TrailingOffset,
+    TriggerPriceSource,
+};
 
 /// Defines an order
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,10 +18,28 @@ pub struct Order<S> {
     order_type: OrderType,
     /// the limit order price
     limit_price: Option<QuoteCurrency>,
+    /// The price at which a conditional order is triggered and the source of that price.
+    trigger: Option<(QuoteCurrency, TriggerPriceSource)>,
+    /// The distance of the trigger price to the best price, for trailing stop orders.
+    trailing_offset: Option<TrailingOffset>,
+    /// How long the order remains active and how it may be executed.
+    time_in_force: TimeInForce,
+    /// Whether the order may only reduce the position.
+    reduce_only: bool,
+    /// The quantity of an iceberg order that is visible in the queue at any time.
+    display_quantity: Option<S>,
+    /// The timestamp in nanoseconds after which the order is cancelled automatically.
+    expiry_timestamp_ns: Option<i64>,
+    /// Whether the order got cancelled because its expiry timestamp passed.
+    expired: bool,
+    /// Why the exchange rejected the order after it had accepted it.
+    rejection: Option<Error>,
     /// The amount of Currency `S` the order is for
     quantity: S,
     /// order side
     side: Side,
+    /// The amount of Currency `S` that has been filled so far.
+    filled_quantity: S,
     /// whether or not the order has been executed
     pub(crate) filled: Filled,
 }
@@ -26,6 +49,11 @@ pub struct Order<S> {
 pub enum Filled {
     /// The order has not been filled yet
     No,
+    /// Some of the order quantity has been filled, while the remainder is still active.
+    PartiallyFilled {
+        /// The volume weighted average price of the filled quantity.
+        avg_fill_price: QuoteCurrency,
+    },
     /// The order has been filled
     Yes {
         /// The average price this order has been filled at
@@ -60,12 +88,48 @@ where
             timestamp: 0,
             order_type: OrderType::Limit,
             limit_price: Some(limit_price),
+            trigger: None,
+            trailing_offset: None,
+            time_in_force: TimeInForce::default(),
+            reduce_only: false,
+            display_quantity: None,
+            expiry_timestamp_ns: None,
+            expired: false,
+            rejection: None,
             quantity: size,
             side,
+            filled_quantity: S::new_zero(),
             filled: Filled::No,
         })
     }
 
+    /// Create a new iceberg limit order,
+    /// of which only the `display_quantity` is visible in the queue at any time.
+    /// Each time the visible slice is filled, a new one is placed at the back of the queue.
+    ///
+    /// # Arguments:
+    /// - `side`: either buy or sell
+    /// - `limit_price`: price to execute at or better
+    /// - `size`: How many contracts should be traded in total
+    /// - `display_quantity`: The size of each visible slice.
+    ///
+    /// # Returns:
+    /// Either a successfully created order or an [`OrderError`]
+    #[inline]
+    pub fn iceberg(
+        side: Side,
+        limit_price: QuoteCurrency,
+        size: S,
+        display_quantity: S,
+    ) -> Result<Self, OrderError> {
+        let mut order = Self::limit(side, limit_price, size)?;
+        if display_quantity <= S::new_zero() || display_quantity > size {
+            return Err(OrderError::InvalidDisplayQuantity);
+        }
+        order.display_quantity = Some(display_quantity);
+        Ok(order)
+    }
+
     /// Create a new market order.
     ///
     /// # Arguments.
@@ -85,12 +149,166 @@ where
             timestamp: 0,
             order_type: OrderType::Market,
             limit_price: None,
+            trigger: None,
+            trailing_offset: None,
+            time_in_force: TimeInForce::default(),
+            reduce_only: false,
+            display_quantity: None,
+            expiry_timestamp_ns: None,
+            expired: false,
+            rejection: None,
             quantity: size,
             side,
+            filled_quantity: S::new_zero(),
             filled: Filled::No,
         })
     }
 
+    /// Create a new stop market order,
+    /// which becomes a market order once the `trigger_price` is reached.
+    /// A buy stop triggers once the price rises to or above the `trigger_price`,
+    /// a sell stop once the price falls to or below it.
+    ///
+    /// # Arguments:
+    /// - `side`: either buy or sell
+    /// - `trigger_price`: The price at which the order gets triggered.
+    /// - `size`: How many contracts to trade
+    /// - `trigger_price_source`: Which price is compared against the `trigger_price`.
+    ///
+    /// # Returns:
+    /// Either a successfully created instance or an [`OrderError`]
+    #[inline]
+    pub fn stop_market(
+        side: Side,
+        trigger_price: QuoteCurrency,
+        size: S,
+        trigger_price_source: TriggerPriceSource,
+    ) -> Result<Self, OrderError> {
+        if trigger_price <= QuoteCurrency::new_zero() {
+            return Err(OrderError::InvalidTriggerPrice);
+        }
+        let mut order = Self::market(side, size)?;
+        order.order_type = OrderType::StopMarket;
+        order.trigger = Some((trigger_price, trigger_price_source));
+        Ok(order)
+    }
+
+    /// Create a new stop limit order,
+    /// which becomes a limit order once the `trigger_price` is reached.
+    ///
+    /// # Arguments:
+    /// - `side`: either buy or sell
+    /// - `trigger_price`: The price at which the order gets triggered.
+    /// - `limit_price`: The price of the limit order, once triggered.
+    /// - `size`: How many contracts to trade
+    /// - `trigger_price_source`: Which price is compared against the `trigger_price`.
+    ///
+    /// # Returns:
+    /// Either a successfully created instance or an [`OrderError`]
+    #[inline]
+    pub fn stop_limit(
+        side: Side,
+        trigger_price: QuoteCurrency,
+        limit_price: QuoteCurrency,
+        size: S,
+        trigger_price_source: TriggerPriceSource,
+    ) -> Result<Self, OrderError> {
+        if trigger_price <= QuoteCurrency::new_zero() {
+            return Err(OrderError::InvalidTriggerPrice);
+        }
+        let mut order = Self::limit(side, limit_price, size)?;
+        order.order_type = OrderType::StopLimit;
+        order.trigger = Some((trigger_price, trigger_price_source));
+        Ok(order)
+    }
+
+    /// Create a new take profit market order,
+    /// which becomes a market order once the `trigger_price` is reached.
+    /// A buy take profit triggers once the price falls to or below the `trigger_price`,
+    /// a sell take profit once the price rises to or above it.
+    ///
+    /// # Arguments:
+    /// - `side`: either buy or sell
+    /// - `trigger_price`: The price at which the order gets triggered.
+    /// - `size`: How many contracts to trade
+    /// - `trigger_price_source`: Which price is compared against the `trigger_price`.
+    ///
+    /// # Returns:
+    /// Either a successfully created instance or an [`OrderError`]
+    #[inline]
+    pub fn take_profit_market(
+        side: Side,
+        trigger_price: QuoteCurrency,
+        size: S,
+        trigger_price_source: TriggerPriceSource,
+    ) -> Result<Self, OrderError> {
+        let mut order = Self::stop_market(side, trigger_price, size, trigger_price_source)?;
+        order.order_type = OrderType::TakeProfitMarket;
+        Ok(order)
+    }
+
+    /// Create a new take profit limit order,
+    /// which becomes a limit order once the `trigger_price` is reached.
+    ///
+    /// # Arguments:
+    /// - `side`: either buy or sell
+    /// - `trigger_price`: The price at which the order gets triggered.
+    /// - `limit_price`: The price of the limit order, once triggered.
+    /// - `size`: How many contracts to trade
+    /// - `trigger_price_source`: Which price is compared against the `trigger_price`.
+    ///
+    /// # Returns:
+    /// Either a successfully created instance or an [`OrderError`]
+    #[inline]
+    pub fn take_profit_limit(
+        side: Side,
+        trigger_price: QuoteCurrency,
+        limit_price: QuoteCurrency,
+        size: S,
+        trigger_price_source: TriggerPriceSource,
+    ) -> Result<Self, OrderError> {
+        let mut order =
+            Self::stop_limit(side, trigger_price, limit_price, size, trigger_price_source)?;
+        order.order_type = OrderType::TakeProfitLimit;
+        Ok(order)
+    }
+
+    /// Create a new trailing stop market order.
+    /// Its trigger price follows the best price seen since submission at a distance of `offset`,
+    /// so a sell trails below the highest price and a buy trails above the lowest price.
+    /// The initial trigger price is set by the exchange upon submission.
+    ///
+    /// # Arguments:
+    /// - `side`: either buy or sell
+    /// - `offset`: The distance of the trigger price to the best price.
+    /// - `size`: How many contracts to trade
+    /// - `trigger_price_source`: Which price is tracked.
+    ///
+    /// # Returns:
+    /// Either a successfully created instance or an [`OrderError`]
+    #[inline]
+    pub fn trailing_stop_market(
+        side: Side,
+        offset: TrailingOffset,
+        size: S,
+        trigger_price_source: TriggerPriceSource,
+    ) -> Result<Self, OrderError> {
+        let valid = match offset {
+            TrailingOffset::Absolute(offset) => offset > QuoteCurrency::new_zero(),
+            TrailingOffset::Fraction(fraction) => {
+                fraction > Decimal::ZERO && fraction < Decimal::ONE
+            }
+        };
+        if !valid {
+            return Err(OrderError::InvalidTrailingOffset);
+        }
+        let mut order = Self::market(side, size)?;
+        order.order_type = OrderType::TrailingStopMarket;
+        order.trigger = Some((QuoteCurrency::new_zero(), trigger_price_source));
+        order.trailing_offset = Some(offset);
+        Ok(order)
+    }
+
     /// Id of Order
     #[inline(always)]
     pub fn id(&self) -> u64 {
@@ -109,6 +327,67 @@ where
         self.user_order_id = Some(id)
     }
 
+    /// How long the order remains active and how it may be executed.
+    #[inline(always)]
+    pub fn time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    /// Set the time in force of the order, which is `GoodTillCancelled` by default.
+    #[inline(always)]
+    pub fn set_time_in_force(&mut self, time_in_force: TimeInForce) {
+        self.time_in_force = time_in_force
+    }
+
+    /// Whether the order may only reduce the position.
+    #[inline(always)]
+    pub fn reduce_only(&self) -> bool {
+        self.reduce_only
+    }
+
+    /// Set whether the order may only reduce the position.
+    #[inline(always)]
+    pub fn set_reduce_only(&mut self, reduce_only: bool) {
+        self.reduce_only = reduce_only
+    }
+
+    /// The timestamp in nanoseconds after which the order is cancelled automatically, if any.
+    #[inline(always)]
+    pub fn expiry_timestamp_ns(&self) -> Option<i64> {
+        self.expiry_timestamp_ns
+    }
+
+    /// Make the order good till time, so it is cancelled automatically
+    /// once the market timestamp passes `ts_ns`.
+    #[inline(always)]
+    pub fn set_expiry_timestamp_ns(&mut self, ts_ns: i64) {
+        self.expiry_timestamp_ns = Some(ts_ns)
+    }
+
+    /// Whether the order got cancelled because its expiry timestamp passed.
+    #[inline(always)]
+    pub fn is_expired(&self) -> bool {
+        self.expired
+    }
+
+    #[inline(always)]
+    pub(crate) fn mark_expired(&mut self) {
+        self.expired = true
+    }
+
+    /// Why the exchange rejected the order after it had accepted it,
+    /// e.g. once a conditional order got triggered, the children of a bracket got activated
+    /// or its submission or amendment arrived after the order entry latency.
+    #[inline(always)]
+    pub fn rejection(&self) -> Option<&Error> {
+        self.rejection.as_ref()
+    }
+
+    #[inline(always)]
+    pub(crate) fn mark_rejected(&mut self, error: Error) {
+        self.rejection = Some(error)
+    }
+
     /// Timestamp of Order
     #[inline(always)]
     pub fn timestamp(&self) -> i64 {
@@ -127,6 +406,30 @@ where
         self.limit_price
     }
 
+    /// The trigger price of a conditional order
+    #[inline(always)]
+    pub fn trigger_price(&self) -> Option<QuoteCurrency> {
+        self.trigger.map(|(price, _)| price)
+    }
+
+    /// Which price triggers a conditional order
+    #[inline(always)]
+    pub fn trigger_price_source(&self) -> Option<TriggerPriceSource> {
+        self.trigger.map(|(_, source)| source)
+    }
+
+    /// The distance of the trigger price to the best price, for trailing stop orders.
+    #[inline(always)]
+    pub fn trailing_offset(&self) -> Option<TrailingOffset> {
+        self.trailing_offset
+    }
+
+    /// The size of the visible slices of an iceberg order.
+    #[inline(always)]
+    pub fn display_quantity(&self) -> Option<S> {
+        self.display_quantity
+    }
+
     /// Quantity of Order
     #[inline(always)]
     pub fn quantity(&self) -> S {
@@ -139,16 +442,100 @@ where
         self.side
     }
 
+    /// The quantity of the `Order` that has been filled so far.
+    #[inline(always)]
+    pub fn filled_quantity(&self) -> S {
+        self.filled_quantity
+    }
+
+    /// The quantity of the `Order` that still remains to be filled.
+    #[inline(always)]
+    pub fn remaining_quantity(&self) -> S {
+        self.quantity - self.filled_quantity
+    }
+
     /// Fill status of the `Order`
     #[inline(always)]
     pub fn filled(&self) -> Filled {
         self.filled
     }
 
-    /// Marks the order as filled at the `fill_price`
+    /// Marks the remaining quantity of the order as filled at the `fill_price`
+    #[cfg(test)]
     #[inline(always)]
     pub(crate) fn mark_filled(&mut self, fill_price: QuoteCurrency) {
-        self.filled = Filled::Yes { fill_price }
+        self.fill(self.remaining_quantity(), fill_price)
+    }
+
+    /// Fill some `quantity` of the order at the `fill_price`,
+    /// updating the volume weighted average fill price.
+    pub(crate) fn fill(&mut self, quantity: S, fill_price: QuoteCurrency) {
+        debug_assert!(
+            quantity > S::new_zero(),
+            "The fill quantity must be positive"
+        );
+        debug_assert!(
+            quantity <= self.remaining_quantity(),
+            "Can't fill more than the remaining quantity"
+        );
+
+        let prev_avg_price = match self.filled {
+            Filled::No => QuoteCurrency::new_zero(),
+            Filled::PartiallyFilled { avg_fill_price } => avg_fill_price,
+            Filled::Yes { fill_price } => fill_price,
+        };
+        let new_filled_quantity = self.filled_quantity + quantity;
+        let avg_fill_price = (prev_avg_price * self.filled_quantity.inner()
+            + fill_price * quantity.inner())
+            / new_filled_quantity.inner();
+        self.filled_quantity = new_filled_quantity;
+
+        self.filled = if self.filled_quantity == self.quantity {
+            Filled::Yes {
+                fill_price: avg_fill_price,
+            }
+        } else {
+            Filled::PartiallyFilled { avg_fill_price }
+        };
+    }
+
+    /// Move the trigger price of a trailing stop order, given the best `price` seen so far.
+    /// The trigger price only ever moves in favour of the order.
+    pub(crate) fn update_trailing_stop(&mut self, price: QuoteCurrency) {
+        let (Some(offset), Some((trigger_price, _))) =
+            (self.trailing_offset, self.trigger.as_mut())
+        else {
+            return;
+        };
+        let new_trigger_price = offset.trigger_price(price, self.side);
+        let improves = match self.side {
+            Side::Buy => new_trigger_price < *trigger_price,
+            Side::Sell => new_trigger_price > *trigger_price,
+        };
+        if trigger_price.is_zero() || improves {
+            *trigger_price = new_trigger_price;
+        }
+    }
+
+    /// Change the limit price and the total quantity of a resting limit order.
+    pub(crate) fn amend(&mut self, limit_price: QuoteCurrency, quantity: S) {
+        debug_assert!(self.limit_price.is_some());
+        debug_assert!(quantity > self.filled_quantity);
+
+        self.limit_price = Some(limit_price);
+        self.quantity = quantity;
+        if let Filled::Yes { fill_price } = self.filled {
+            self.filled = Filled::PartiallyFilled {
+                avg_fill_price: fill_price,
+            };
+        }
+    }
+
+    /// Change the total quantity of an order, e.g. the child of a bracket, which grows with the fills of its entry.
+    pub(crate) fn set_quantity(&mut self, quantity: S) {
+        debug_assert!(quantity > self.filled_quantity);
+
+        self.quantity = quantity;
     }
 
     #[inline(always)]
@@ -177,4 +564,29 @@ mod tests {
             Order::limit(Side::Buy, quote!(100.0), base!(100.0)).unwrap()
         );
     }
+
+    #[test]
+    fn order_partial_fills() {
+        let mut order = Order::limit(Side::Buy, quote!(100.0), base!(3.0)).unwrap();
+        assert_eq!(order.remaining_quantity(), base!(3.0));
+
+        order.fill(base!(1.0), quote!(100.0));
+        assert_eq!(order.filled_quantity(), base!(1.0));
+        assert_eq!(order.remaining_quantity(), base!(2.0));
+        assert_eq!(
+            order.filled(),
+            Filled::PartiallyFilled {
+                avg_fill_price: quote!(100.0)
+            }
+        );
+
+        order.fill(base!(2.0), quote!(97.0));
+        assert_eq!(order.remaining_quantity(), base!(0.0));
+        assert_eq!(
+            order.filled(),
+            Filled::Yes {
+                fill_price: quote!(98.0)
+            }
+        );
+    }
 }
@@ -5,4 +5,14 @@ pub enum OrderType {
     Market,
     /// passive limit order
     Limit,
+    /// A conditional order which becomes a market order once the trigger price is reached.
+    StopMarket,
+    /// A conditional order which becomes a limit order once the trigger price is reached.
+    StopLimit,
+    /// A conditional order which becomes a market order once the price moves favourably to the trigger price.
+    TakeProfitMarket,
+    /// A conditional order which becomes a limit order once the price moves favourably to the trigger price.
+    TakeProfitLimit,
+    /// A stop market order whose trigger price follows the best price seen since submission.
+    TrailingStopMarket,
 }
@@ -0,0 +1,147 @@
+use crate::types::{Error, OrderAction, Result};
+
+/// A request budget per time window of the market clock, as enforced by many venues.
+/// Every order action uses up its weight of the budget and once it is used up,
+/// further actions are rejected with `Error::RateLimitExceeded` until the next window starts.
+/// The windows are aligned to multiples of `window_ns`, e.g.: every full minute.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RateLimit {
+    /// The length of a window in nanoseconds. Must be > 0.
+    pub window_ns: u64,
+    /// The total weight of the order actions allowed per window. Must be > 0.
+    pub budget: u32,
+    /// The weight of submitting an order.
+    pub submit_weight: u32,
+    /// The weight of amending an order.
+    pub amend_weight: u32,
+    /// The weight of cancelling an order.
+    pub cancel_weight: u32,
+}
+
+impl RateLimit {
+    /// Create a new `RateLimit`, where every order action has a weight of one.
+    ///
+    /// # Arguments:
+    /// `window_ns`: The length of a window in nanoseconds.
+    /// `budget`: The total weight of the order actions allowed per window.
+    pub fn new(window_ns: u64, budget: u32) -> Self {
+        Self {
+            window_ns,
+            budget,
+            submit_weight: 1,
+            amend_weight: 1,
+            cancel_weight: 1,
+        }
+    }
+
+    /// The weight of an order `action`.
+    #[inline(always)]
+    pub fn weight(&self, action: OrderAction) -> u32 {
+        match action {
+            OrderAction::Submit => self.submit_weight,
+            OrderAction::Amend => self.amend_weight,
+            OrderAction::Cancel => self.cancel_weight,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.window_ns == 0 || self.budget == 0 {
+            return Err(Error::InvalidRateLimit);
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps track of the used budget of every configured `RateLimit`.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    /// Each limit with the index of its current window and the weight used in it.
+    limits: Vec<(RateLimit, i64, u32)>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: &[RateLimit]) -> Self {
+        Self {
+            limits: Vec::from_iter(limits.iter().map(|limit| (*limit, 0, 0))),
+        }
+    }
+
+    /// Use up the budget of a request, consisting of some order actions, if every limit allows it.
+    ///
+    /// # Arguments:
+    /// `actions`: The kinds of order actions in the request and how many of each.
+    /// `ts_ns`: The market clock.
+    ///
+    /// # Returns:
+    /// `Error::RateLimitExceeded` if any budget would be exceeded, in which case nothing is used up.
+    pub(crate) fn acquire(&mut self, actions: &[(OrderAction, u32)], ts_ns: i64) -> Result<()> {
+        for (limit, window, used) in self.limits.iter_mut() {
+            let current_window = ts_ns.div_euclid(limit.window_ns as i64);
+            if current_window != *window {
+                *window = current_window;
+                *used = 0;
+            }
+            let weight: u32 = actions
+                .iter()
+                .map(|(action, n)| limit.weight(*action) * n)
+                .sum();
+            if *used + weight > limit.budget {
+                return Err(Error::RateLimitExceeded);
+            }
+        }
+        for (limit, _, used) in self.limits.iter_mut() {
+            *used += actions
+                .iter()
+                .map(|(action, n)| limit.weight(*action) * n)
+                .sum::<u32>();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_windows() {
+        let mut limiter = RateLimiter::new(&[RateLimit::new(100, 2)]);
+        assert!(limiter.acquire(&[(OrderAction::Submit, 1)], 0).is_ok());
+        assert!(limiter.acquire(&[(OrderAction::Cancel, 1)], 50).is_ok());
+        assert_eq!(
+            limiter.acquire(&[(OrderAction::Amend, 1)], 99),
+            Err(Error::RateLimitExceeded)
+        );
+        // The budget is restored once the next window starts.
+        assert!(limiter.acquire(&[(OrderAction::Submit, 2)], 100).is_ok());
+        assert_eq!(
+            limiter.acquire(&[(OrderAction::Submit, 1)], 150),
+            Err(Error::RateLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn rate_limiter_weights_and_multiple_limits() {
+        let mut limit = RateLimit::new(100, 10);
+        limit.submit_weight = 2;
+        limit.cancel_weight = 0;
+        let mut limiter = RateLimiter::new(&[limit, RateLimit::new(1_000, 8)]);
+
+        assert!(limiter.acquire(&[(OrderAction::Submit, 4)], 0).is_ok());
+        assert!(limiter.acquire(&[(OrderAction::Cancel, 2)], 10).is_ok());
+        assert_eq!(
+            limiter.acquire(&[(OrderAction::Submit, 2)], 20),
+            Err(Error::RateLimitExceeded)
+        );
+        // Nothing is used up by a rejected request.
+        assert!(limiter.acquire(&[(OrderAction::Cancel, 2)], 20).is_ok());
+        // The longer window is used up as well.
+        assert_eq!(
+            limiter.acquire(&[(OrderAction::Submit, 1)], 100),
+            Err(Error::RateLimitExceeded)
+        );
+        assert!(limiter.acquire(&[(OrderAction::Submit, 1)], 1_000).is_ok());
+    }
+}
@@ -0,0 +1,15 @@
+/// Defines how long an order remains active and how it may be executed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum TimeInForce {
+    /// The order remains active until it is either filled or cancelled.
+    #[default]
+    GoodTillCancelled,
+    /// The order is executed immediately, as far as possible,
+    /// and the unfilled remainder is cancelled.
+    ImmediateOrCancel,
+    /// The order is either executed immediately in full, or rejected.
+    FillOrKill,
+    /// The order only ever adds liquidity to the book (GTX).
+    /// It is rejected if it would be executed immediately as taker.
+    PostOnly,
+}
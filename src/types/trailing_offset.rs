@@ -0,0 +1,28 @@
+use fpdec::Decimal;
+
+use crate::types::{QuoteCurrency, Side};
+
+/// Defines how far the trigger price of a trailing stop order follows behind
+/// the best price seen since the order has been submitted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub enum TrailingOffset {
+    /// A fixed distance to the best price.
+    Absolute(QuoteCurrency),
+    /// A fraction of the best price between 0 and 1 (exclusive), e.g. 0.01 for a 1% offset.
+    Fraction(Decimal),
+}
+
+impl TrailingOffset {
+    /// The trigger price of a trailing stop, given the best `price` seen so far.
+    /// A sell trails below the price, a buy trails above it.
+    pub(crate) fn trigger_price(&self, price: QuoteCurrency, side: Side) -> QuoteCurrency {
+        let offset = match self {
+            TrailingOffset::Absolute(offset) => *offset,
+            TrailingOffset::Fraction(fraction) => price * *fraction,
+        };
+        match side {
+            Side::Buy => price + offset,
+            Side::Sell => price - offset,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+/// Defines which price is used to trigger conditional orders, such as stop orders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub enum TriggerPriceSource {
+    /// The price of the last `Trade`, or the `high` and `low` of a `Candle`.
+    LastTradePrice,
+    /// The mid price of the best bid and ask.
+    MidPrice,
+    /// The mark price according to the `MarkMethod` of the contract.
+    MarkPrice,
+}
@@ -4,11 +4,11 @@ use lfest::{mock_exchange_base, prelude::*, trade};
 
 #[test]
 fn limit_orders_only() {
-    if let Err(_) = pretty_env_logger::try_init() {}
+    let _ = pretty_env_logger::try_init();
 
     let mut exchange = mock_exchange_base();
 
-    let exec_orders = exchange
+    let (exec_orders, _) = exchange
         .update_state(
             0,
             MarketUpdate::Bba {
@@ -24,7 +24,7 @@ fn limit_orders_only() {
     assert_eq!(exchange.account().order_margin(), quote!(990.198));
     assert_eq!(exchange.account().available_balance(), quote!(9.802));
 
-    let exec_orders = exchange
+    let (exec_orders, _) = exchange
         .update_state(1, trade!(quote!(100), base!(10), Side::Sell))
         .unwrap();
     let _ = exchange
@@ -52,7 +52,7 @@ fn limit_orders_only() {
     exchange.submit_order(o).unwrap();
     assert_eq!(exchange.account().order_margin(), quote!(0));
 
-    let exec_orders = exchange
+    let (exec_orders, _) = exchange
         .update_state(2, trade!(quote!(105), base!(10), Side::Buy))
         .unwrap();
     let _ = exchange
@@ -75,11 +75,11 @@ fn limit_orders_only() {
 
 #[test]
 fn limit_orders_2() {
-    if let Err(_) = pretty_env_logger::try_init() {}
+    let _ = pretty_env_logger::try_init();
 
     let mut exchange = mock_exchange_base();
 
-    let exec_orders = exchange
+    let (exec_orders, _) = exchange
         .update_state(
             0,
             MarketUpdate::Bba {
@@ -96,7 +96,7 @@ fn limit_orders_2() {
     let o = Order::limit(Side::Buy, quote!(100), base!(0.5)).unwrap();
     exchange.submit_order(o).unwrap();
 
-    let exec_orders = exchange
+    let (exec_orders, _) = exchange
         .update_state(1, trade!(quote!(98), base!(2), Side::Sell))
         .unwrap();
     let _ = exchange